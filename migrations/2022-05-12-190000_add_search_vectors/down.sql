@@ -0,0 +1,5 @@
+DROP INDEX post_comment_search_idx;
+DROP INDEX post_search_idx;
+
+ALTER TABLE PostComment DROP COLUMN search;
+ALTER TABLE Post DROP COLUMN search;
@@ -0,0 +1,11 @@
+ALTER TABLE Post ADD COLUMN search TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A') ||
+        setweight(to_tsvector('english', text), 'B')
+    ) STORED;
+
+ALTER TABLE PostComment ADD COLUMN search TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('english', text)) STORED;
+
+CREATE INDEX post_search_idx ON Post USING GIN (search);
+CREATE INDEX post_comment_search_idx ON PostComment USING GIN (search);
@@ -0,0 +1 @@
+DROP TABLE Draft;
\ No newline at end of file
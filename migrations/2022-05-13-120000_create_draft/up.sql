@@ -0,0 +1,10 @@
+CREATE TABLE Draft (
+    draft_id SERIAL UNIQUE PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    post_id INTEGER,
+    title TEXT NOT NULL DEFAULT '',
+    text TEXT NOT NULL,
+    last_saved TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE CASCADE
+);
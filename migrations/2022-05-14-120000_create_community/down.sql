@@ -0,0 +1,3 @@
+DROP TABLE Subscription;
+DROP TABLE CommunityPost;
+DROP TABLE Community;
\ No newline at end of file
@@ -0,0 +1,22 @@
+CREATE TABLE Community (
+    community_id SERIAL UNIQUE PRIMARY KEY,
+    name VARCHAR(50) UNIQUE NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    creator_id INTEGER NOT NULL,
+    FOREIGN KEY (creator_id) REFERENCES Users(user_id)
+);
+
+CREATE TABLE CommunityPost (
+    post_id INTEGER PRIMARY KEY,
+    community_id INTEGER NOT NULL,
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE CASCADE,
+    FOREIGN KEY (community_id) REFERENCES Community(community_id) ON DELETE CASCADE
+);
+
+CREATE TABLE Subscription (
+    user_id INTEGER,
+    community_id INTEGER,
+    PRIMARY KEY (user_id, community_id),
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (community_id) REFERENCES Community(community_id) ON DELETE CASCADE
+);
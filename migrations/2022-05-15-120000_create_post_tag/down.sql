@@ -0,0 +1 @@
+DROP TABLE PostTag;
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE PostTag (
+    post_id INTEGER,
+    tag VARCHAR(30),
+    PRIMARY KEY (post_id, tag),
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE CASCADE
+);
+
+CREATE INDEX post_tag_tag_idx ON PostTag (tag);
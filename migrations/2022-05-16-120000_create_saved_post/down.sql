@@ -0,0 +1 @@
+DROP TABLE SavedPost;
\ No newline at end of file
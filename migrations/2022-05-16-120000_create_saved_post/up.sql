@@ -0,0 +1,10 @@
+CREATE TABLE SavedPost (
+    saved_id SERIAL UNIQUE PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    -- Set to NULL when the post is deleted, so that the bookmark can show that it was removed
+    post_id INTEGER,
+    saved_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (user_id, post_id),
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE SET NULL
+);
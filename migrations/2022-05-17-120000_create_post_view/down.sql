@@ -0,0 +1,2 @@
+DROP TABLE PostView;
+ALTER TABLE PostComment DROP COLUMN date_posted;
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Comments existing before this have no real date, so they are all given the time of the migration
+ALTER TABLE PostComment ADD COLUMN date_posted TIMESTAMP NOT NULL DEFAULT now();
+
+CREATE TABLE PostView (
+    user_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    last_viewed TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, post_id),
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+ALTER TABLE Users DROP COLUMN date_joined;
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Users existing before this have no real date, so they are all given the time of the migration
+ALTER TABLE Users ADD COLUMN date_joined TIMESTAMP NOT NULL DEFAULT now();
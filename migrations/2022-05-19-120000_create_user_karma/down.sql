@@ -0,0 +1,9 @@
+DROP TRIGGER comment_reaction_karma ON CommentReaction;
+DROP FUNCTION comment_reaction_karma;
+DROP TRIGGER post_reaction_karma ON PostReaction;
+DROP FUNCTION post_reaction_karma;
+DROP TRIGGER user_karma ON Users;
+DROP FUNCTION user_karma_on_insert;
+DROP FUNCTION add_karma;
+DROP FUNCTION vote_value;
+DROP TABLE UserKarma;
\ No newline at end of file
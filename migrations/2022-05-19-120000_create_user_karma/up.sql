@@ -0,0 +1,74 @@
+-- The sum of the votes on everything each user has written, kept up to date by the triggers below
+-- so that it never has to be added up when it is shown
+CREATE TABLE UserKarma (
+    user_id INTEGER UNIQUE PRIMARY KEY,
+    karma INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE
+);
+
+-- An upvote is worth 1 and a downvote -1. Comment reactions without a direction count for nothing
+CREATE FUNCTION vote_value(upvote BOOLEAN) RETURNS INTEGER AS $$
+    SELECT CASE WHEN upvote THEN 1 WHEN NOT upvote THEN -1 ELSE 0 END
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE FUNCTION add_karma(author_id INTEGER, amount INTEGER) RETURNS VOID AS $$
+    INSERT INTO UserKarma (user_id, karma) VALUES (author_id, amount)
+    ON CONFLICT (user_id) DO UPDATE SET karma = UserKarma.karma + amount
+$$ LANGUAGE SQL;
+
+CREATE FUNCTION user_karma_on_insert() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO UserKarma (user_id) VALUES (NEW.user_id) ON CONFLICT DO NOTHING;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER user_karma AFTER INSERT ON Users
+FOR EACH ROW EXECUTE FUNCTION user_karma_on_insert();
+
+CREATE FUNCTION post_reaction_karma() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM add_karma(p.user_id, -vote_value(OLD.upvote))
+        FROM Posts p WHERE p.post_id = OLD.post_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM add_karma(p.user_id, vote_value(NEW.upvote))
+        FROM Posts p WHERE p.post_id = NEW.post_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER post_reaction_karma AFTER INSERT OR UPDATE OR DELETE ON PostReaction
+FOR EACH ROW EXECUTE FUNCTION post_reaction_karma();
+
+CREATE FUNCTION comment_reaction_karma() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM add_karma(c.user_id, -vote_value(OLD.upvote))
+        FROM PostComments c WHERE c.comment_id = OLD.comment_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM add_karma(c.user_id, vote_value(NEW.upvote))
+        FROM PostComments c WHERE c.comment_id = NEW.comment_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER comment_reaction_karma AFTER INSERT OR UPDATE OR DELETE ON CommentReaction
+FOR EACH ROW EXECUTE FUNCTION comment_reaction_karma();
+
+-- Add up the votes cast so far
+INSERT INTO UserKarma (user_id, karma)
+SELECT u.user_id, (
+    SELECT COALESCE(SUM(vote_value(r.upvote)), 0)
+    FROM PostReaction r JOIN Posts p ON p.post_id = r.post_id
+    WHERE p.user_id = u.user_id
+) + (
+    SELECT COALESCE(SUM(vote_value(r.upvote)), 0)
+    FROM CommentReaction r JOIN PostComments c ON c.comment_id = r.comment_id
+    WHERE c.user_id = u.user_id
+)
+FROM Users u;
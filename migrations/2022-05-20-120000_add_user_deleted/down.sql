@@ -0,0 +1 @@
+ALTER TABLE Users DROP COLUMN deleted;
\ No newline at end of file
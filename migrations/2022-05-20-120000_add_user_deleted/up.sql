@@ -0,0 +1,3 @@
+-- Deleted accounts are kept, without any personal details, so that what they wrote can still be
+-- shown as written by "[deleted user]"
+ALTER TABLE Users ADD COLUMN deleted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+DROP TRIGGER queue_comment_reply_email ON ReplyTo;
+DROP FUNCTION queue_comment_reply_email;
+DROP TRIGGER queue_post_reply_email ON PostCommentOn;
+DROP FUNCTION queue_post_reply_email;
+DROP TABLE EmailQueue;
\ No newline at end of file
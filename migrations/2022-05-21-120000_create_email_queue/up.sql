@@ -0,0 +1,71 @@
+-- Emails waiting to be sent, which are sent in the background so that nothing waits on the mail
+-- server. Rows are kept after sending so that nothing is sent twice
+CREATE TABLE EmailQueue (
+    email_id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    queued_at TIMESTAMP NOT NULL DEFAULT now(),
+    sent_at TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX email_queue_unsent ON EmailQueue (queued_at) WHERE sent_at IS NULL;
+
+-- Tells the authors of a post about a comment on it. This runs when the transaction commits, so
+-- that the comment's text and author can be written in any order
+CREATE FUNCTION queue_post_reply_email() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO EmailQueue (user_id, subject, body)
+    SELECT p.user_id, replier.name || ' replied to your post "' || post.title || '"', comment.text
+    FROM Posts p
+    JOIN Users author ON author.user_id = p.user_id
+    JOIN Post post ON post.post_id = p.post_id
+    JOIN PostComments c ON c.comment_id = NEW.comment_id
+    JOIN Users replier ON replier.user_id = c.user_id
+    JOIN PostComment comment ON comment.comment_id = NEW.comment_id
+    WHERE p.post_id = NEW.post_id
+        AND p.user_id <> c.user_id
+        AND author.email_notifications_enabled
+        AND NOT author.deleted;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER queue_post_reply_email AFTER INSERT ON PostCommentOn
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION queue_post_reply_email();
+
+-- Tells the author of a comment about a reply to it
+CREATE FUNCTION queue_comment_reply_email() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO EmailQueue (user_id, subject, body)
+    WITH RECURSIVE thread (comment_id) AS (
+        SELECT NEW.parent_comment
+        UNION
+        SELECT r.parent_comment
+        FROM ReplyTo r JOIN thread t ON r.child_comment = t.comment_id
+    )
+    SELECT c.user_id,
+        replier.name || ' replied to your comment on "' || post.title || '"',
+        comment.text
+    FROM PostComments c
+    JOIN Users author ON author.user_id = c.user_id
+    JOIN PostComments rc ON rc.comment_id = NEW.child_comment
+    JOIN Users replier ON replier.user_id = rc.user_id
+    JOIN PostComment comment ON comment.comment_id = NEW.child_comment
+    JOIN PostCommentOn o ON o.comment_id IN (SELECT comment_id FROM thread)
+    JOIN Post post ON post.post_id = o.post_id
+    WHERE c.comment_id = NEW.parent_comment
+        AND c.user_id <> rc.user_id
+        AND author.email_notifications_enabled
+        AND NOT author.deleted;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER queue_comment_reply_email AFTER INSERT ON ReplyTo
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION queue_comment_reply_email();
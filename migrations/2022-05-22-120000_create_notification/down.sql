@@ -0,0 +1,10 @@
+DROP TRIGGER notify_comment_vote ON CommentReaction;
+DROP FUNCTION notify_comment_vote;
+DROP TRIGGER notify_post_vote ON PostReaction;
+DROP FUNCTION notify_post_vote;
+DROP TRIGGER notify_comment_reply ON ReplyTo;
+DROP FUNCTION notify_comment_reply;
+DROP TRIGGER notify_post_reply ON PostCommentOn;
+DROP FUNCTION notify_post_reply;
+DROP FUNCTION comment_post_id;
+DROP TABLE Notification;
\ No newline at end of file
@@ -0,0 +1,117 @@
+-- Things that happened to what a user has written: replies, mentions, and votes. `actor_id` is who
+-- did it, and `comment_id` is the comment to show, if it isn't about the post itself
+CREATE TABLE Notification (
+    notification_id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('post_reply', 'comment_reply', 'mention', 'vote')),
+    actor_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL,
+    comment_id INTEGER,
+    upvote BOOLEAN,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    is_read BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (post_id) REFERENCES Post(post_id) ON DELETE CASCADE,
+    FOREIGN KEY (comment_id) REFERENCES PostComment(comment_id) ON DELETE CASCADE
+);
+
+CREATE INDEX notification_unread ON Notification (user_id) WHERE NOT is_read;
+
+-- The post whose thread a comment is in
+CREATE FUNCTION comment_post_id(comment INTEGER) RETURNS INTEGER AS $$
+    WITH RECURSIVE thread (comment_id) AS (
+        SELECT comment
+        UNION
+        SELECT r.parent_comment
+        FROM ReplyTo r JOIN thread t ON r.child_comment = t.comment_id
+    )
+    SELECT o.post_id FROM PostCommentOn o JOIN thread t ON o.comment_id = t.comment_id LIMIT 1
+$$ LANGUAGE SQL STABLE;
+
+-- Like the reply emails, these run when the transaction commits so that the comment's author and
+-- thread can be written in any order
+CREATE FUNCTION notify_post_reply() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO Notification (user_id, kind, actor_id, post_id, comment_id)
+    SELECT p.user_id, 'post_reply', c.user_id, NEW.post_id, NEW.comment_id
+    FROM Posts p
+    JOIN PostComments c ON c.comment_id = NEW.comment_id
+    WHERE p.post_id = NEW.post_id AND p.user_id <> c.user_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER notify_post_reply AFTER INSERT ON PostCommentOn
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION notify_post_reply();
+
+CREATE FUNCTION notify_comment_reply() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO Notification (user_id, kind, actor_id, post_id, comment_id)
+    SELECT parent.user_id, 'comment_reply', child.user_id, post_id, NEW.child_comment
+    FROM PostComments parent
+    JOIN PostComments child ON child.comment_id = NEW.child_comment
+    CROSS JOIN comment_post_id(NEW.parent_comment) post_id
+    WHERE parent.comment_id = NEW.parent_comment
+        AND parent.user_id <> child.user_id
+        AND post_id IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER notify_comment_reply AFTER INSERT ON ReplyTo
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION notify_comment_reply();
+
+-- A vote that is changed or taken back replaces the notification about it while it is unread, so
+-- that changing a vote back and forth doesn't pile them up
+CREATE FUNCTION notify_post_vote() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.upvote IS NOT DISTINCT FROM NEW.upvote THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM Notification
+        WHERE kind = 'vote' AND NOT is_read AND actor_id = OLD.user_id
+            AND post_id = OLD.post_id AND comment_id IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO Notification (user_id, kind, actor_id, post_id, upvote)
+        SELECT p.user_id, 'vote', NEW.user_id, NEW.post_id, NEW.upvote
+        FROM Posts p
+        WHERE p.post_id = NEW.post_id AND p.user_id <> NEW.user_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_post_vote AFTER INSERT OR UPDATE OR DELETE ON PostReaction
+FOR EACH ROW EXECUTE FUNCTION notify_post_vote();
+
+-- Comment reactions without a direction aren't votes, so they aren't notified about
+CREATE FUNCTION notify_comment_vote() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.upvote IS NOT DISTINCT FROM NEW.upvote THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM Notification
+        WHERE kind = 'vote' AND NOT is_read AND actor_id = OLD.user_id
+            AND comment_id = OLD.comment_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.upvote IS NOT NULL THEN
+        INSERT INTO Notification (user_id, kind, actor_id, post_id, comment_id, upvote)
+        SELECT c.user_id, 'vote', NEW.user_id, post_id, NEW.comment_id, NEW.upvote
+        FROM PostComments c
+        CROSS JOIN comment_post_id(NEW.comment_id) post_id
+        WHERE c.comment_id = NEW.comment_id
+            AND c.user_id <> NEW.user_id
+            AND post_id IS NOT NULL;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_comment_vote AFTER INSERT OR UPDATE OR DELETE ON CommentReaction
+FOR EACH ROW EXECUTE FUNCTION notify_comment_vote();
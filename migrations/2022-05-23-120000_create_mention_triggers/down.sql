@@ -0,0 +1,7 @@
+DROP TRIGGER notify_comment_mention ON PostComments;
+DROP FUNCTION notify_comment_mention;
+DROP TRIGGER notify_post_edit_mention ON Post;
+DROP FUNCTION notify_post_edit_mention;
+DROP TRIGGER notify_post_mention ON Posts;
+DROP FUNCTION notify_post_mention;
+DROP FUNCTION mentioned_users;
\ No newline at end of file
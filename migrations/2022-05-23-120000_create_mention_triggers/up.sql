@@ -0,0 +1,63 @@
+-- The users mentioned in some text with an @ followed by their name, which can't be part of a
+-- longer word on either side
+CREATE FUNCTION mentioned_users(body TEXT) RETURNS TABLE (user_id INTEGER) AS $$
+    SELECT u.user_id
+    FROM Users u
+    WHERE NOT u.deleted
+        AND body ~* ('(^|[^[:alnum:]_])@'
+            || regexp_replace(u.name, '([.^$*+?()\[\]{}|\\])', '\\\1', 'g')
+            || '($|[^[:alnum:]_])')
+$$ LANGUAGE SQL STABLE;
+
+-- Runs when the transaction commits, once the post's text and author have both been written
+CREATE FUNCTION notify_post_mention() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO Notification (user_id, kind, actor_id, post_id)
+    SELECT m.user_id, 'mention', NEW.user_id, NEW.post_id
+    FROM Post p
+    CROSS JOIN mentioned_users(p.text) m
+    WHERE p.post_id = NEW.post_id AND m.user_id <> NEW.user_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER notify_post_mention AFTER INSERT ON Posts
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION notify_post_mention();
+
+-- Only the users who weren't mentioned before the post was edited are told about it
+CREATE FUNCTION notify_post_edit_mention() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO Notification (user_id, kind, actor_id, post_id)
+    SELECT m.user_id, 'mention', author.user_id, NEW.post_id
+    FROM (
+        SELECT user_id FROM mentioned_users(NEW.text)
+        EXCEPT
+        SELECT user_id FROM mentioned_users(OLD.text)
+    ) m
+    CROSS JOIN (SELECT user_id FROM Posts WHERE post_id = NEW.post_id LIMIT 1) author
+    WHERE m.user_id NOT IN (SELECT user_id FROM Posts WHERE post_id = NEW.post_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_post_edit_mention AFTER UPDATE OF text ON Post
+FOR EACH ROW EXECUTE FUNCTION notify_post_edit_mention();
+
+CREATE FUNCTION notify_comment_mention() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO Notification (user_id, kind, actor_id, post_id, comment_id)
+    SELECT m.user_id, 'mention', NEW.user_id, post_id, NEW.comment_id
+    FROM PostComment c
+    CROSS JOIN mentioned_users(c.text) m
+    CROSS JOIN comment_post_id(NEW.comment_id) post_id
+    WHERE c.comment_id = NEW.comment_id
+        AND m.user_id <> NEW.user_id
+        AND post_id IS NOT NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER notify_comment_mention AFTER INSERT ON PostComments
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION notify_comment_mention();
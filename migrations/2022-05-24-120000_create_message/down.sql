@@ -0,0 +1,4 @@
+DROP TRIGGER check_message_allowed ON Message;
+DROP FUNCTION check_message_allowed;
+DROP TABLE BlockedSender;
+DROP TABLE Message;
\ No newline at end of file
@@ -0,0 +1,44 @@
+-- Private messages between two users
+CREATE TABLE Message (
+    message_id SERIAL PRIMARY KEY,
+    sender_id INTEGER NOT NULL,
+    recipient_id INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT now(),
+    is_read BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY (sender_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (recipient_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    CHECK (sender_id <> recipient_id)
+);
+
+CREATE INDEX message_sender ON Message (sender_id, sent_at);
+CREATE INDEX message_recipient ON Message (recipient_id, sent_at);
+CREATE INDEX message_unread ON Message (recipient_id) WHERE NOT is_read;
+
+-- The senders that a user doesn't want any more messages from
+CREATE TABLE BlockedSender (
+    user_id INTEGER NOT NULL,
+    blocked_id INTEGER NOT NULL,
+    blocked_at TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, blocked_id),
+    FOREIGN KEY (user_id) REFERENCES Users(user_id) ON DELETE CASCADE,
+    FOREIGN KEY (blocked_id) REFERENCES Users(user_id) ON DELETE CASCADE
+);
+
+-- Messages can't be sent to someone who has blocked the sender, or whose account is deleted
+CREATE FUNCTION check_message_allowed() RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM BlockedSender
+        WHERE user_id = NEW.recipient_id AND blocked_id = NEW.sender_id
+    ) OR EXISTS (
+        SELECT 1 FROM Users WHERE user_id = NEW.recipient_id AND deleted
+    ) THEN
+        RAISE EXCEPTION 'This user isn''t accepting messages from you';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_message_allowed BEFORE INSERT ON Message
+FOR EACH ROW EXECUTE FUNCTION check_message_allowed();
@@ -0,0 +1 @@
+ALTER TABLE Draft DROP COLUMN recipient_id;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Drafts of direct messages are kept for the user they are to. A draft is of a new post, a reply
+-- to a post, or a message, so it can't be both a reply and a message
+ALTER TABLE Draft ADD COLUMN recipient_id INTEGER REFERENCES Users(user_id) ON DELETE CASCADE;
+ALTER TABLE Draft ADD CHECK (post_id IS NULL OR recipient_id IS NULL);
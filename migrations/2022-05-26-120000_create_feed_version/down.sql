@@ -0,0 +1,13 @@
+DROP TRIGGER feed_version ON Post;
+DROP TRIGGER feed_version ON Posts;
+DROP TRIGGER feed_version ON PostComment;
+DROP TRIGGER feed_version ON PostComments;
+DROP TRIGGER feed_version ON PostCommentOn;
+DROP TRIGGER feed_version ON ReplyTo;
+DROP TRIGGER feed_version ON PostReaction;
+DROP TRIGGER feed_version ON CommentReaction;
+DROP TRIGGER feed_version ON PostTag;
+DROP TRIGGER feed_version ON CommunityPost;
+DROP TRIGGER feed_version ON Community;
+DROP FUNCTION bump_feed_version;
+DROP TABLE FeedVersion;
\ No newline at end of file
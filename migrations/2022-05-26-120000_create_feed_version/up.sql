@@ -0,0 +1,49 @@
+-- A number that goes up whenever anything shown in the list of posts changes, so that checking
+-- for changes only has to read this one row. There is only ever one row
+CREATE TABLE FeedVersion (
+    only_row BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (only_row),
+    version BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO FeedVersion DEFAULT VALUES;
+
+-- Runs once per statement rather than per row, since any change at all is enough
+CREATE FUNCTION bump_feed_version() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE FeedVersion SET version = version + 1;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON Post
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON Posts
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON PostComment
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON PostComments
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON PostCommentOn
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON ReplyTo
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON PostReaction
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON CommentReaction
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON PostTag
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON CommunityPost
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
+
+CREATE TRIGGER feed_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON Community
+FOR EACH STATEMENT EXECUTE FUNCTION bump_feed_version();
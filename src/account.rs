@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use st_read::models::{Message, User};
+use st_read::util::format_datetime;
+
+use crate::author;
+use crate::json::Json;
+use crate::profile::check_password;
+
+/// The name shown in place of the author of anything written by a deleted account
+pub const DELETED_NAME: &str = "[deleted user]";
+/// The text shown in place of a removed comment that still has replies
+const DELETED_TEXT: &str = "[deleted]";
+/// How many numbered names are tried for an export before giving up
+const EXPORT_ATTEMPTS: usize = 100;
+
+/// Writes everything the user has written and their settings to a JSON file in the current
+/// directory, returning where it was written
+pub fn export_account(user_id: i32) -> Result<PathBuf, String> {
+    use st_read::schema::commentreaction::dsl as comment_reaction_dsl;
+    use st_read::schema::community::dsl as community_dsl;
+    use st_read::schema::communitypost::dsl as community_post_dsl;
+    use st_read::schema::message::dsl as message_dsl;
+    use st_read::schema::post::dsl as post_dsl;
+    use st_read::schema::postreaction::dsl as post_reaction_dsl;
+    use st_read::schema::posts::dsl as posts_dsl;
+    use st_read::schema::posttag::dsl as post_tag_dsl;
+    use st_read::schema::savedpost::dsl as saved_dsl;
+    use st_read::schema::subscription::dsl as subscription_dsl;
+    use st_read::schema::users::dsl as users_dsl;
+    let connection = st_read::establish_connection();
+
+    let failed = |e: diesel::result::Error| format!("Couldn't export your account: {}", e);
+
+    let user: User = users_dsl::users
+        .find(user_id)
+        .first(&connection)
+        .map_err(failed)?;
+
+    let subscriptions: Vec<String> = subscription_dsl::subscription
+        .inner_join(community_dsl::community)
+        .filter(subscription_dsl::user_id.eq(user_id))
+        .select(community_dsl::name)
+        .order(community_dsl::name)
+        .load(&connection)
+        .map_err(failed)?;
+
+    let saved_posts: Vec<Option<i32>> = saved_dsl::savedpost
+        .filter(saved_dsl::user_id.eq(user_id))
+        .select(saved_dsl::post_id)
+        .order(saved_dsl::saved_at)
+        .load(&connection)
+        .map_err(failed)?;
+
+    let posts: Vec<st_read::models::Post> = posts_dsl::posts
+        .inner_join(post_dsl::post)
+        .filter(posts_dsl::user_id.eq(user_id))
+        .select(post_dsl::post::all_columns())
+        .order(post_dsl::date_posted)
+        .load(&connection)
+        .map_err(failed)?;
+
+    let mut post_values = Vec::new();
+    for post in posts {
+        let tags: Vec<String> = post_tag_dsl::posttag
+            .filter(post_tag_dsl::post_id.eq(post.post_id))
+            .select(post_tag_dsl::tag)
+            .load(&connection)
+            .map_err(failed)?;
+        let community: Option<String> = community_post_dsl::communitypost
+            .inner_join(community_dsl::community)
+            .filter(community_post_dsl::post_id.eq(post.post_id))
+            .select(community_dsl::name)
+            .first(&connection)
+            .optional()
+            .map_err(failed)?;
+
+        post_values.push(
+            Json::object()
+                .field("post_id", post.post_id)
+                .field("title", post.title)
+                .field("text", post.text)
+                .field("date_posted", format_datetime(post.date_posted))
+                .field("community", community)
+                .field("tags", tags),
+        );
+    }
+
+    let comments: Vec<Json> = author::load_comments(user_id)
+        .map_err(failed)?
+        .into_iter()
+        .map(|comment| {
+            Json::object()
+                .field("comment_id", comment.comment_id)
+                .field("post_id", comment.post_id)
+                .field("text", comment.text)
+                .field("date_posted", format_datetime(comment.date_posted))
+        })
+        .collect();
+
+    let post_reactions: Vec<(i32, bool)> = post_reaction_dsl::postreaction
+        .filter(post_reaction_dsl::user_id.eq(user_id))
+        .select((post_reaction_dsl::post_id, post_reaction_dsl::upvote))
+        .load(&connection)
+        .map_err(failed)?;
+    let comment_reactions: Vec<(i32, Option<bool>)> = comment_reaction_dsl::commentreaction
+        .filter(comment_reaction_dsl::user_id.eq(user_id))
+        .select((
+            comment_reaction_dsl::comment_id,
+            comment_reaction_dsl::upvote,
+        ))
+        .load(&connection)
+        .map_err(failed)?;
+
+    let post_reactions: Vec<Json> = post_reactions
+        .into_iter()
+        .map(|(post_id, upvote)| {
+            Json::object()
+                .field("post_id", post_id)
+                .field("upvote", upvote)
+        })
+        .collect();
+    let comment_reactions: Vec<Json> = comment_reactions
+        .into_iter()
+        .map(|(comment_id, upvote)| {
+            Json::object()
+                .field("comment_id", comment_id)
+                .field("upvote", upvote)
+        })
+        .collect();
+
+    let messages: Vec<Message> = message_dsl::message
+        .filter(
+            message_dsl::sender_id
+                .eq(user_id)
+                .or(message_dsl::recipient_id.eq(user_id)),
+        )
+        .order((message_dsl::sent_at, message_dsl::message_id))
+        .load(&connection)
+        .map_err(failed)?;
+    let other_ids: Vec<i32> = messages
+        .iter()
+        .map(|message| {
+            if message.sender_id == user_id {
+                message.recipient_id
+            } else {
+                message.sender_id
+            }
+        })
+        .collect();
+    let names: HashMap<i32, String> = users_dsl::users
+        .filter(users_dsl::user_id.eq_any(&other_ids))
+        .select((users_dsl::user_id, users_dsl::name))
+        .load(&connection)
+        .map_err(failed)?
+        .into_iter()
+        .collect();
+
+    let messages: Vec<Json> = messages
+        .into_iter()
+        .zip(other_ids)
+        .map(|(message, other_id)| {
+            Json::object()
+                .field("with", names.get(&other_id).cloned())
+                .field("sent", message.sender_id == user_id)
+                .field("text", message.text)
+                .field("sent_at", format_datetime(message.sent_at))
+        })
+        .collect();
+
+    let export = Json::object()
+        .field(
+            "account",
+            Json::object()
+                .field("user_id", user.user_id)
+                .field("name", user.name)
+                .field("email", user.email)
+                .field("date_joined", format_datetime(user.date_joined)),
+        )
+        .field(
+            "settings",
+            Json::object()
+                .field("dark_mode", user.dark_mode)
+                .field("email_notifications", user.email_notifications)
+                .field("subscriptions", subscriptions)
+                .field("saved_posts", saved_posts),
+        )
+        .field("posts", post_values)
+        .field("comments", comments)
+        .field("messages", messages)
+        .field(
+            "reactions",
+            Json::object()
+                .field("posts", post_reactions)
+                .field("comments", comment_reactions),
+        );
+
+    write_export(user_id, &format!("{}\n", export))
+        .map_err(|e| format!("Couldn't write the export: {}", e))
+}
+
+/// Writes an export to a new file that only the user can read, since it has their email and
+/// messages in it. Earlier exports are kept, with a number added to the name of the new one
+fn write_export(user_id: i32, export: &str) -> io::Result<PathBuf> {
+    for attempt in 1..=EXPORT_ATTEMPTS {
+        let path = PathBuf::from(match attempt {
+            1 => format!("st-read-export-{}.json", user_id),
+            _ => format!("st-read-export-{}-{}.json", user_id, attempt),
+        });
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(export.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "there are too many exports in this directory already",
+    ))
+}
+
+/// Deletes the user's account, after checking their password. Their account is kept without any
+/// personal details, so that anything left behind is shown as written by "[deleted user]".
+/// With `remove_content`, their posts, comments, and votes are removed as well, and the text of
+/// the messages they sent
+pub fn delete_account(user_id: i32, password: &str, remove_content: bool) -> Result<(), String> {
+    use st_read::schema::blockedsender::dsl as blocked_dsl;
+    use st_read::schema::draft::dsl as draft_dsl;
+    use st_read::schema::emailqueue::dsl as email_queue_dsl;
+    use st_read::schema::postview::dsl as post_view_dsl;
+    use st_read::schema::savedpost::dsl as saved_dsl;
+    use st_read::schema::subscription::dsl as subscription_dsl;
+    use st_read::schema::users::dsl as users_dsl;
+
+    check_password(user_id, password)?;
+
+    let connection = st_read::establish_connection();
+
+    connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            if remove_content {
+                remove_content_of(&connection, user_id)?;
+            }
+
+            diesel::delete(draft_dsl::draft.filter(draft_dsl::user_id.eq(user_id)))
+                .execute(&connection)?;
+            diesel::delete(
+                email_queue_dsl::emailqueue.filter(email_queue_dsl::user_id.eq(user_id)),
+            )
+            .execute(&connection)?;
+            diesel::delete(
+                subscription_dsl::subscription.filter(subscription_dsl::user_id.eq(user_id)),
+            )
+            .execute(&connection)?;
+            diesel::delete(saved_dsl::savedpost.filter(saved_dsl::user_id.eq(user_id)))
+                .execute(&connection)?;
+            // The other side of each conversation belongs to someone else, so the messages are
+            // kept like posts are. Blocks others have made are theirs to keep too
+            diesel::delete(blocked_dsl::blockedsender.filter(blocked_dsl::user_id.eq(user_id)))
+                .execute(&connection)?;
+            diesel::delete(post_view_dsl::postview.filter(post_view_dsl::user_id.eq(user_id)))
+                .execute(&connection)?;
+
+            // The email has to stay unique, and an empty hash never matches a password
+            diesel::update(users_dsl::users.find(user_id))
+                .set((
+                    users_dsl::name.eq(DELETED_NAME),
+                    users_dsl::email.eq(format!("deleted-{}", user_id)),
+                    users_dsl::password_hash.eq(Vec::<u8>::new()),
+                    users_dsl::email_notifications_enabled.eq(false),
+                    users_dsl::deleted.eq(true),
+                ))
+                .execute(&connection)?;
+
+            Ok(())
+        })
+        .map_err(|e| format!("Couldn't delete your account: {}", e))
+}
+
+/// Removes the user's posts along with every comment on them, their votes, and their comments on
+/// other posts. Comments that others have replied to are kept with their text removed, so that
+/// the replies still have somewhere to go. The same goes for the messages they sent
+fn remove_content_of(connection: &PgConnection, user_id: i32) -> QueryResult<()> {
+    use st_read::schema::commentreaction::dsl as comment_reaction_dsl;
+    use st_read::schema::message::dsl as message_dsl;
+    use st_read::schema::post::dsl as post_dsl;
+    use st_read::schema::postcomment::dsl as post_comment_dsl;
+    use st_read::schema::postcomments::dsl as post_comments_dsl;
+    use st_read::schema::postreaction::dsl as post_reaction_dsl;
+    use st_read::schema::posts::dsl as posts_dsl;
+    use st_read::schema::replyto::dsl as reply_to_dsl;
+
+    // The votes the user cast
+    diesel::delete(post_reaction_dsl::postreaction.filter(post_reaction_dsl::user_id.eq(user_id)))
+        .execute(connection)?;
+    diesel::delete(
+        comment_reaction_dsl::commentreaction.filter(comment_reaction_dsl::user_id.eq(user_id)),
+    )
+    .execute(connection)?;
+
+    // The user's posts, and every comment on them
+    let post_ids: Vec<i32> = posts_dsl::posts
+        .filter(posts_dsl::user_id.eq(user_id))
+        .select(posts_dsl::post_id)
+        .load(connection)?;
+
+    let thread = thread_comments(connection, &post_ids)?;
+    delete_comments(connection, &thread)?;
+
+    diesel::delete(
+        post_reaction_dsl::postreaction.filter(post_reaction_dsl::post_id.eq_any(&post_ids)),
+    )
+    .execute(connection)?;
+    diesel::delete(posts_dsl::posts.filter(posts_dsl::post_id.eq_any(&post_ids)))
+        .execute(connection)?;
+    diesel::delete(post_dsl::post.filter(post_dsl::post_id.eq_any(&post_ids)))
+        .execute(connection)?;
+
+    // The user's comments on other posts
+    let comment_ids: Vec<i32> = post_comments_dsl::postcomments
+        .filter(post_comments_dsl::user_id.eq(user_id))
+        .select(post_comments_dsl::comment_id)
+        .load(connection)?;
+
+    let replied_to: Vec<i32> = reply_to_dsl::replyto
+        .filter(reply_to_dsl::parent_comment.eq_any(&comment_ids))
+        .select(reply_to_dsl::parent_comment)
+        .load(connection)?;
+
+    let (kept, removed): (Vec<i32>, Vec<i32>) = comment_ids
+        .into_iter()
+        .partition(|id| replied_to.contains(id));
+
+    delete_comments(connection, &removed)?;
+    diesel::update(
+        post_comment_dsl::postcomment.filter(post_comment_dsl::comment_id.eq_any(&kept)),
+    )
+    .set(post_comment_dsl::text.eq(DELETED_TEXT))
+    .execute(connection)?;
+
+    diesel::update(message_dsl::message.filter(message_dsl::sender_id.eq(user_id)))
+        .set(message_dsl::text.eq(DELETED_TEXT))
+        .execute(connection)?;
+
+    Ok(())
+}
+
+/// Finds every comment on the posts, including the replies to other comments
+fn thread_comments(connection: &PgConnection, post_ids: &[i32]) -> QueryResult<Vec<i32>> {
+    use st_read::schema::postcommenton::dsl as post_comment_on_dsl;
+    use st_read::schema::replyto::dsl as reply_to_dsl;
+
+    let mut comments: Vec<i32> = post_comment_on_dsl::postcommenton
+        .filter(post_comment_on_dsl::post_id.eq_any(post_ids))
+        .select(post_comment_on_dsl::comment_id)
+        .load(connection)?;
+
+    // Each pass finds the replies to the comments found in the last one
+    let mut parents = comments.clone();
+    while !parents.is_empty() {
+        parents = reply_to_dsl::replyto
+            .filter(reply_to_dsl::parent_comment.eq_any(&parents))
+            .select(reply_to_dsl::child_comment)
+            .load(connection)?;
+        comments.extend(&parents);
+    }
+
+    Ok(comments)
+}
+
+/// Deletes comments along with their votes, authors, and the links to their posts and replies
+fn delete_comments(connection: &PgConnection, comment_ids: &[i32]) -> QueryResult<()> {
+    use st_read::schema::commentreaction::dsl as comment_reaction_dsl;
+    use st_read::schema::postcomment::dsl as post_comment_dsl;
+    use st_read::schema::postcommenton::dsl as post_comment_on_dsl;
+    use st_read::schema::postcomments::dsl as post_comments_dsl;
+    use st_read::schema::replyto::dsl as reply_to_dsl;
+
+    diesel::delete(
+        comment_reaction_dsl::commentreaction
+            .filter(comment_reaction_dsl::comment_id.eq_any(comment_ids)),
+    )
+    .execute(connection)?;
+    diesel::delete(
+        reply_to_dsl::replyto.filter(
+            reply_to_dsl::parent_comment
+                .eq_any(comment_ids)
+                .or(reply_to_dsl::child_comment.eq_any(comment_ids)),
+        ),
+    )
+    .execute(connection)?;
+    diesel::delete(
+        post_comment_on_dsl::postcommenton
+            .filter(post_comment_on_dsl::comment_id.eq_any(comment_ids)),
+    )
+    .execute(connection)?;
+    diesel::delete(
+        post_comments_dsl::postcomments.filter(post_comments_dsl::comment_id.eq_any(comment_ids)),
+    )
+    .execute(connection)?;
+    diesel::delete(
+        post_comment_dsl::postcomment.filter(post_comment_dsl::comment_id.eq_any(comment_ids)),
+    )
+    .execute(connection)?;
+
+    Ok(())
+}
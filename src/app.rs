@@ -1,30 +1,45 @@
 use std::{
-    io,
-    time::{Duration, Instant},
+    collections::HashMap,
+    io::{self, Write},
+    mem,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
 use crossterm::event::{self, Event, KeyCode};
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use st_read::models::{
-    NewPost, PostComment, PostCommentOn, PostComments, PostReaction, Posts, User,
+    CommunityPost, NewPost, PostComment, PostCommentOn, PostComments, PostReaction, PostTag, Posts,
+    User,
 };
-use st_read::models::{Post as DbPost, ReplyTo, User as DbUser};
+use st_read::models::{Post as DbPost, ReplyTo, SearchResult, User as DbUser};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    text::Span,
     widgets::{Block, Borders, Paragraph},
     Frame, Terminal,
 };
 
 use crate::{
+    author::AuthorFrame,
+    communities::CommunitiesFrame,
+    compose::Compose,
     create_post::CreatePostFrame,
+    drafts::{self, DraftsFrame},
     initial::InitialFrame,
+    leaderboard::{self, LeaderboardFrame},
     login::LoginFrame,
-    posts_list::PostsListFrame,
+    markdown,
+    messages::{self, MessagesFrame},
+    notifications::{self, NotificationsFrame},
+    posts_list::{FeedFilter, PostsListFrame},
     profile::UserProfileFrame,
     register::RegisterFrame,
+    saved::{self, SavedFrame},
     viewing_post::{Comment, ViewingPostFrame},
 };
 
@@ -37,6 +52,13 @@ pub enum AppView {
     Login,
     Register,
     Initial,
+    Drafts,
+    Communities,
+    Saved,
+    Author,
+    Leaderboard,
+    Notifications,
+    Messages,
 }
 
 /// homepage left / right selection
@@ -46,13 +68,59 @@ pub enum SelectedFrame {
     ViewPost,
 }
 
+/// Ranks posts by how well their title and text match the query, plus how well any of the comments
+/// in their thread match. The matched words in the title and snippet are wrapped in `\u{1}` and
+/// `\u{2}` so that they can be highlighted
+const SEARCH_POSTS: &str = "
+WITH RECURSIVE query (q) AS (
+    SELECT websearch_to_tsquery('english', $1)
+),
+thread (comment_id, post_id) AS (
+    SELECT comment_id, post_id FROM PostCommentOn
+    UNION
+    SELECT r.child_comment, t.post_id
+    FROM ReplyTo r JOIN thread t ON r.parent_comment = t.comment_id
+),
+matches (post_id, rank) AS (
+    SELECT p.post_id, ts_rank(p.search, q)
+    FROM Post p, query
+    WHERE p.search @@ q
+    UNION ALL
+    SELECT t.post_id, ts_rank(c.search, q)
+    FROM PostComment c JOIN thread t ON t.comment_id = c.comment_id, query
+    WHERE c.search @@ q
+)
+SELECT
+    p.post_id,
+    SUM(m.rank) AS rank,
+    ts_headline('english', p.title, q,
+        'HighlightAll=true, StartSel=' || chr(1) || ', StopSel=' || chr(2)) AS title,
+    ts_headline('english', p.text, q,
+        'MaxWords=12, MinWords=4, StartSel=' || chr(1) || ', StopSel=' || chr(2)) AS snippet
+FROM matches m JOIN Post p ON p.post_id = m.post_id, query
+GROUP BY p.post_id, q
+ORDER BY rank DESC";
+
+/// How often the text being written is saved as a draft
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+/// How often to check for new notifications
+const NOTIFICATION_INTERVAL: Duration = Duration::from_secs(30);
+/// How often to check for posts, comments, and votes by other users
+const FEED_INTERVAL: Duration = Duration::from_secs(10);
+
 /// All the information needed to render a singe post
 #[derive(Debug, Clone)]
 pub struct Post {
+    pub post_id: i32,
     pub title: String,
     pub short: String,
     pub stats: String,
     pub author: String,
+    pub author_id: i32,
+    pub author_karma: i32,
+    pub community_id: Option<i32>,
+    pub community: Option<String>,
+    pub tags: Vec<String>,
     pub full: String,
     pub comments: Vec<Comment>,
 }
@@ -69,13 +137,37 @@ pub struct App {
     pub initial_frame: InitialFrame,
     pub login_frame: LoginFrame,
     pub register_frame: RegisterFrame,
+    pub drafts_frame: DraftsFrame,
+    pub communities_frame: CommunitiesFrame,
+    pub saved_frame: SavedFrame,
+    pub author_frame: AuthorFrame,
+    pub leaderboard_frame: LeaderboardFrame,
+    pub notifications_frame: NotificationsFrame,
+    pub messages_frame: MessagesFrame,
     pub quittable: bool,
+    /// Text to write in the user's own editor once the current key has been handled
+    pub compose: Option<Compose>,
+    /// When the drafts were last saved
+    last_autosave: Instant,
+    /// When the unread notifications were last counted
+    last_notification_check: Instant,
+    /// When the database was last checked for changes to the posts
+    last_feed_check: Instant,
+    /// The version of the posts that were last loaded
+    feed_version: Option<i64>,
+    /// The posts being loaded in the background by `check_feed`. Nothing is sent if they haven't
+    /// changed or couldn't be loaded
+    feed_loader: Option<Receiver<(i64, Vec<Post>)>>,
+    /// Whether the new posts should be listed as soon as they are loaded
+    show_loaded: bool,
 }
 
 impl App {
     /// Creates a new app using the default settings
     pub fn new() -> Self {
-        let posts = Self::load_posts();
+        let connection = st_read::establish_connection();
+        let (feed_version, posts) = Self::load_feed(&connection, None).ok().flatten().unzip();
+        let posts = posts.unwrap_or_default();
         App {
             page_title: PageTitle::new("Welcome to ST-Read"),
             posts_frame: PostsListFrame::with_items(posts),
@@ -86,15 +178,22 @@ impl App {
             initial_frame: InitialFrame::new(),
             login_frame: LoginFrame::new(),
             register_frame: RegisterFrame::new(),
+            drafts_frame: DraftsFrame::new(),
+            communities_frame: CommunitiesFrame::new(),
+            saved_frame: SavedFrame::new(),
+            author_frame: AuthorFrame::new(),
+            leaderboard_frame: LeaderboardFrame::new(),
+            notifications_frame: NotificationsFrame::new(),
+            messages_frame: MessagesFrame::new(),
             quittable: true,
-            profile_frame: UserProfileFrame {
-                selected: crate::profile::SelectedOption::None,
-                dark_mode: false,
-                user_id: 0,
-                email_notifications: false,
-                email: String::new(),
-                name: String::new(),
-            },
+            compose: None,
+            last_autosave: Instant::now(),
+            last_notification_check: Instant::now(),
+            last_feed_check: Instant::now(),
+            feed_version,
+            feed_loader: None,
+            show_loaded: false,
+            profile_frame: UserProfileFrame::new(),
         }
     }
 
@@ -106,41 +205,464 @@ impl App {
         Ok(())
     }
 
-    /// Submits a new post to the database and refreshes the list of posts to view
-    pub fn submit_post(&self, title: &str, text: &str) {
+    /// Submits a new post to the database along with its author, its tags, and the community it
+    /// was posted in, if any. Nothing is saved if any insert fails
+    pub fn submit_post(
+        &self,
+        title: &str,
+        text: &str,
+        community_id: Option<i32>,
+        tags: &[String],
+    ) -> QueryResult<()> {
         let connection = st_read::establish_connection();
+        use st_read::schema::communitypost::dsl as community_post_dsl;
         use st_read::schema::post::dsl as post_dsl;
         use st_read::schema::posts::dsl as posts_dsl;
+        use st_read::schema::posttag::dsl as post_tag_dsl;
 
-        let post = NewPost {
-            title: title.to_owned(),
-            text: text.to_owned(),
-        };
+        connection.transaction(|| {
+            let post = NewPost {
+                title: title.to_owned(),
+                text: text.to_owned(),
+            };
 
-        let post: DbPost = post
-            .insert_into(post_dsl::post)
-            .get_result(&connection)
-            .unwrap();
+            let post: DbPost = post.insert_into(post_dsl::post).get_result(&connection)?;
 
-        let author_id = self.profile_frame.user_id;
-        let posts = Posts {
-            user_id: author_id,
-            post_id: post.post_id,
-        };
-        posts
-            .insert_into(posts_dsl::posts)
+            let author_id = self.profile_frame.user_id;
+            let posts = Posts {
+                user_id: author_id,
+                post_id: post.post_id,
+            };
+            posts.insert_into(posts_dsl::posts).execute(&connection)?;
+
+            let tags: Vec<_> = tags
+                .iter()
+                .map(|tag| PostTag {
+                    post_id: post.post_id,
+                    tag: tag.clone(),
+                })
+                .collect();
+            diesel::insert_into(post_tag_dsl::posttag)
+                .values(&tags)
+                .execute(&connection)?;
+
+            if let Some(community_id) = community_id {
+                let community_post = CommunityPost {
+                    post_id: post.post_id,
+                    community_id,
+                };
+                community_post
+                    .insert_into(community_post_dsl::communitypost)
+                    .execute(&connection)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Saves the new title and text of a post that has been edited
+    pub fn update_post(&self, post_id: i32, title: &str, text: &str) -> QueryResult<()> {
+        let connection = st_read::establish_connection();
+        use st_read::schema::post::dsl as post_dsl;
+
+        diesel::update(post_dsl::post.find(post_id))
+            .set((post_dsl::title.eq(title), post_dsl::text.eq(text)))
             .execute(&connection)
-            .unwrap();
+            .map(|_| ())
+    }
+
+    /// Searches the titles, text, and comments of every post, returning the best matches first
+    pub fn search_posts(&self, query: &str) -> QueryResult<Vec<SearchResult>> {
+        use diesel::sql_types::Text;
+        let connection = st_read::establish_connection();
+
+        diesel::sql_query(SEARCH_POSTS)
+            .bind::<Text, _>(query)
+            .load(&connection)
+    }
+
+    /// Loads the ids of the communities the user is subscribed to
+    fn load_subscriptions(user_id: i32) -> Vec<i32> {
+        use st_read::schema::subscription::dsl;
+        let connection = st_read::establish_connection();
+
+        dsl::subscription
+            .filter(dsl::user_id.eq(user_id))
+            .select(dsl::community_id)
+            .load(&connection)
+            .unwrap_or_default()
+    }
+
+    /// Subscribes the user to a community, or unsubscribes them from it
+    pub fn set_subscribed(&mut self, community_id: i32, subscribed: bool) -> QueryResult<()> {
+        use st_read::schema::subscription::dsl;
+        let connection = st_read::establish_connection();
+        let user_id = self.profile_frame.user_id;
+
+        if subscribed {
+            diesel::insert_into(dsl::subscription)
+                .values((dsl::user_id.eq(user_id), dsl::community_id.eq(community_id)))
+                .on_conflict_do_nothing()
+                .execute(&connection)?;
+
+            if !self.profile_frame.subscriptions.contains(&community_id) {
+                self.profile_frame.subscriptions.push(community_id);
+            }
+        } else {
+            diesel::delete(dsl::subscription.find((user_id, community_id))).execute(&connection)?;
+
+            self.profile_frame
+                .subscriptions
+                .retain(|&id| id != community_id);
+        }
+
+        // Keep the home feed in step with the subscriptions
+        if matches!(self.posts_frame.filter(), FeedFilter::Home(_)) {
+            self.show_home_feed();
+        }
+
+        Ok(())
+    }
+
+    /// Saves a post for the user, or unsaves it if it was already saved
+    pub fn toggle_saved(&mut self, post_id: i32) {
+        let user_id = self.profile_frame.user_id;
+        let saved = self.profile_frame.saved_posts.contains(&post_id);
+
+        let result = if saved {
+            saved::unsave_post(user_id, post_id)
+        } else {
+            saved::save_post(user_id, post_id)
+        };
+
+        match result {
+            Ok(()) if saved => {
+                self.profile_frame.saved_posts.retain(|&id| id != post_id);
+                self.page_title
+                    .set_title("Removed the post from your saved posts");
+            }
+            Ok(()) => {
+                self.profile_frame.saved_posts.push(post_id);
+                self.page_title
+                    .set_title("Saved the post, press 'S' to see your saved posts");
+            }
+            Err(e) => {
+                self.page_title
+                    .set_title(format!("Couldn't change saved posts: {}", e));
+            }
+        }
+    }
+
+    /// Loads when the user last viewed each of the posts they have opened
+    fn load_viewed_posts(user_id: i32) -> HashMap<i32, SystemTime> {
+        use st_read::schema::postview::dsl;
+        let connection = st_read::establish_connection();
+
+        dsl::postview
+            .filter(dsl::user_id.eq(user_id))
+            .select((dsl::post_id, dsl::last_viewed))
+            .load(&connection)
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    }
+
+    /// Records that the user has just viewed the posts. Nothing is recorded before logging in
+    pub fn mark_read(&mut self, post_ids: &[i32]) -> QueryResult<()> {
+        use diesel::pg::upsert::excluded;
+        use st_read::schema::postview::dsl;
+        let connection = st_read::establish_connection();
+
+        let user_id = self.profile_frame.user_id;
+        if user_id == 0 || post_ids.is_empty() {
+            return Ok(());
+        }
+
+        // The server's clock is used, since that is what comments are dated with
+        let views: Vec<_> = post_ids
+            .iter()
+            .map(|&post_id| {
+                (
+                    dsl::user_id.eq(user_id),
+                    dsl::post_id.eq(post_id),
+                    dsl::last_viewed.eq(diesel::dsl::now),
+                )
+            })
+            .collect();
+
+        let viewed: Vec<(i32, SystemTime)> = diesel::insert_into(dsl::postview)
+            .values(&views)
+            .on_conflict((dsl::user_id, dsl::post_id))
+            .do_update()
+            .set(dsl::last_viewed.eq(excluded(dsl::last_viewed)))
+            .returning((dsl::post_id, dsl::last_viewed))
+            .get_results(&connection)?;
+
+        self.profile_frame.viewed_posts.extend(viewed);
+
+        Ok(())
     }
 
-    /// Reloads the posts from the database
+    /// Shows the posts in the communities the user is subscribed to
+    pub fn show_home_feed(&mut self) {
+        let subscriptions = self.profile_frame.subscriptions.clone();
+        self.posts_frame
+            .show_filter(FeedFilter::Home(subscriptions));
+    }
+
+    /// Reloads the posts from the database, still showing the same feed
     pub fn reload_posts(&mut self) {
-        let posts = Self::load_posts();
+        // A check that is still loading would bring back older posts
+        self.feed_loader = None;
+        self.show_loaded = false;
+
+        let connection = st_read::establish_connection();
+        let (version, posts) = match Self::load_feed(&connection, None) {
+            Ok(Some(feed)) => feed,
+            Ok(None) => return,
+            Err(e) => {
+                self.page_title
+                    .set_title(format!("Couldn't load the posts: {}", e));
+                return;
+            }
+        };
+        self.feed_version = Some(version);
+        let filter = self.posts_frame.filter().clone();
+        let tag = self.posts_frame.tag().cloned();
+
         self.posts_frame = PostsListFrame::with_items(posts);
+        self.posts_frame.show_filter(filter);
+        self.posts_frame.show_tag(tag);
+    }
+
+    /// Opens a post in the viewing frame, even if it isn't in the list being shown, along with any
+    /// draft of a reply to it. The post is marked as read, with the comments since the last visit
+    /// shown as new
+    pub fn view_post(&mut self, post_id: i32) {
+        // Keep the reply to the previous post before it is cleared
+        self.save_drafts();
+
+        let post = self.posts_frame.find_post(post_id);
+
+        if let Some(post) = post {
+            let last_visit = self.profile_frame.viewed_posts.get(&post_id).copied();
+            self.viewing_frame.view(post.clone(), last_visit);
+            self.viewing_frame.restore_draft(self.profile_frame.user_id);
+
+            if let Err(e) = self.mark_read(&[post_id]) {
+                self.page_title
+                    .set_title(format!("Couldn't mark the post as read: {}", e));
+            }
+        }
+    }
+
+    /// Opens a post from another view, scrolling to the comment if there is one
+    pub fn open_post_at(&mut self, post_id: i32, comment_id: Option<i32>) {
+        // The post may be newer than the feed
+        if self.posts_frame.find_post(post_id).is_none() {
+            self.reload_posts();
+        }
+
+        self.set_view(AppView::Homepage);
+        self.view_post(post_id);
+
+        if self.viewing_frame.post().map(|post| post.post_id) == Some(post_id) {
+            self.selected_frame = SelectedFrame::ViewPost;
+            if let Some(comment_id) = comment_id {
+                self.viewing_frame.focus_comment(comment_id);
+            }
+        } else {
+            self.page_title.set_title("That post couldn't be found");
+        }
+    }
+
+    /// Looks for posts, comments, and votes by other users, loading them in the background.
+    /// Once they are loaded, changes to the posts that are listed and to the open post show up in
+    /// place, while new posts are counted in the title of the list until the user refreshes it
+    pub fn check_feed(&mut self) {
+        self.last_feed_check = Instant::now();
+        if self.feed_loader.is_some() {
+            return;
+        }
+
+        let known = self.feed_version;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // Anything going wrong skips this check, and the next one tries again
+            let connection = match st_read::try_establish_connection() {
+                Ok(connection) => connection,
+                Err(_) => return,
+            };
+            if let Ok(Some(feed)) = Self::load_feed(&connection, known) {
+                let _ = sender.send(feed);
+            }
+        });
+        self.feed_loader = Some(receiver);
+    }
+
+    /// Checks for anything new right away, listing the new posts as soon as they are loaded
+    pub fn refresh_feed(&mut self) {
+        self.posts_frame.show_pending();
+        self.show_loaded = true;
+        self.check_feed();
+    }
+
+    /// Brings in the posts loaded by `check_feed`, once they are ready
+    fn receive_feed(&mut self) {
+        let received = match &self.feed_loader {
+            Some(loader) => loader.try_recv(),
+            None => return,
+        };
+
+        match received {
+            Err(TryRecvError::Empty) => return,
+            // The posts haven't changed, or couldn't be loaded
+            Err(TryRecvError::Disconnected) => {}
+            Ok((version, posts)) => {
+                self.feed_version = Some(version);
+
+                let open = self.viewing_frame.post().map(|post| post.post_id);
+                if let Some(post) = posts.iter().find(|post| Some(post.post_id) == open) {
+                    self.viewing_frame.refresh(post.clone());
+                }
+                self.posts_frame.update_posts(posts);
+            }
+        }
+
+        self.feed_loader = None;
+        if mem::take(&mut self.show_loaded) {
+            self.posts_frame.show_pending();
+        }
+    }
+
+    /// Loads the posts, unless their version is still the one that is `known`. Everything is read
+    /// from one snapshot, so that changes made part way through can't leave the posts half loaded
+    /// or out of step with their version
+    fn load_feed(
+        connection: &PgConnection,
+        known: Option<i64>,
+    ) -> QueryResult<Option<(i64, Vec<Post>)>> {
+        connection
+            .build_transaction()
+            .repeatable_read()
+            .read_only()
+            .run(|| {
+                let version = Self::feed_version(connection)?;
+                if known == Some(version) {
+                    return Ok(None);
+                }
+
+                let posts = Self::load_posts(connection)?;
+                Ok(Some((version, posts)))
+            })
+    }
+
+    /// Reads the version of the posts, which triggers in the database bump whenever anything
+    /// shown in the feed changes
+    fn feed_version(connection: &PgConnection) -> QueryResult<i64> {
+        use st_read::schema::feedversion::dsl;
+
+        dsl::feedversion.select(dsl::version).first(connection)
+    }
+
+    /// Counts the user's unread notifications and messages again, for the page title
+    pub fn refresh_unread(&mut self) {
+        let user_id = self.profile_frame.user_id;
+        if user_id == 0 {
+            self.page_title.unread = 0;
+            self.page_title.unread_messages = 0;
+        } else {
+            self.page_title.unread = notifications::count_unread(user_id);
+            self.page_title.unread_messages = messages::count_unread(user_id);
+        }
+        self.last_notification_check = Instant::now();
+    }
+
+    /// Shows the public profile of a user
+    pub fn show_author(&mut self, user_id: i32) {
+        self.author_frame.load(user_id, self.profile_frame.user_id);
+        self.set_view(AppView::Author);
+    }
+
+    /// Shows the user's messages, opening the conversation with another user if one is given
+    pub fn show_messages(&mut self, other_id: Option<i32>) {
+        let user_id = self.profile_frame.user_id;
+        self.messages_frame.load_names();
+        match other_id {
+            Some(other_id) => self.messages_frame.start_conversation(user_id, other_id),
+            None => self.messages_frame.load(user_id, None),
+        }
+        self.set_view(AppView::Messages);
+        self.refresh_unread();
+    }
+
+    /// Goes back to the first screen, forgetting everything about the user who was logged in
+    pub fn log_out(&mut self) {
+        self.profile_frame = UserProfileFrame::new();
+        self.create_frame = CreatePostFrame::new();
+        self.messages_frame = MessagesFrame::new();
+        self.viewing_frame.clear();
+        self.selected_frame = SelectedFrame::Posts;
+
+        self.reload_posts();
+        self.posts_frame.show_filter(FeedFilter::All);
+        self.set_view(AppView::Initial);
+        self.refresh_unread();
+    }
+
+    /// Leaves the post being viewed, saving the reply being written to it
+    pub fn close_post(&mut self) {
+        self.selected_frame = SelectedFrame::Posts;
+        self.save_drafts();
+        self.viewing_frame.clear();
+    }
+
+    /// Saves the post and reply being written as drafts. Nothing is saved before logging in
+    pub fn save_drafts(&mut self) {
+        let user_id = self.profile_frame.user_id;
+        if user_id == 0 {
+            return;
+        }
+
+        self.create_frame.save_draft(user_id);
+        self.viewing_frame.save_draft(user_id);
+        self.messages_frame.save_draft(user_id);
+        self.last_autosave = Instant::now();
+    }
+
+    /// Called every tick of the event loop
+    pub fn on_tick(&mut self) {
+        // Keep what is being written where the panic hook can save it
+        let copies = [
+            self.create_frame.draft_copy(),
+            self.viewing_frame.draft_copy(),
+            self.messages_frame.draft_copy(),
+        ];
+        drafts::keep_unsaved(
+            self.profile_frame.user_id,
+            copies.into_iter().flatten().collect(),
+        );
+
+        if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            self.save_drafts();
+        }
+        self.receive_feed();
+        if self.last_feed_check.elapsed() >= FEED_INTERVAL {
+            self.check_feed();
+        }
+        if self.last_notification_check.elapsed() >= NOTIFICATION_INTERVAL {
+            // New messages show up in the conversation that is open
+            if matches!(self.view, AppView::Messages) {
+                self.messages_frame.load(self.profile_frame.user_id, None);
+            }
+            self.refresh_unread();
+        }
     }
 
     /// Loads the current posts from the database
-    fn load_posts() -> Vec<Post> {
+    fn load_posts(connection: &PgConnection) -> QueryResult<Vec<Post>> {
+        use st_read::schema::community::dsl as community_dsl;
+        use st_read::schema::communitypost::dsl as community_post_dsl;
         use st_read::schema::post::dsl as post_dsl;
         use st_read::schema::postcomment::dsl as post_comment_dsl;
         use st_read::schema::postcomment::dsl::comment_id as post_comment_comment_id;
@@ -152,87 +674,109 @@ impl App {
         use st_read::schema::postreaction::dsl::post_id as post_reaction_post_id_dsl;
         use st_read::schema::posts::dsl as posts_dsl;
         use st_read::schema::posts::dsl::post_id as post_id_dsl;
+        use st_read::schema::posttag::dsl as post_tag_dsl;
         use st_read::schema::users::dsl as users_dsl;
         use st_read::schema::users::dsl::user_id as user_id_dsl;
 
-        let connection = st_read::establish_connection();
-        let posts = post_dsl::post.get_results::<DbPost>(&connection).unwrap();
+        let posts = post_dsl::post.get_results::<DbPost>(connection)?;
+        let karma = leaderboard::load_all_karma(connection)?;
 
         posts
             .into_iter()
             // Execute this closure for each post so that we can load more information about it
             .map(|base_post| {
-                let mut short = base_post.text[..16.min(base_post.text.len())].to_owned();
+                // The preview is taken from the rendered Markdown so that no formatting shows up
+                let mut short: String = markdown::plain_text(&base_post.text)
+                    .chars()
+                    .take(16)
+                    .collect();
                 short.push_str(" ...");
                 let author_id = posts_dsl::posts
                     .filter(post_id_dsl.eq(base_post.post_id))
-                    .first::<Posts>(&connection)
-                    .unwrap_or_else(|e| {
-                        panic!(
-                            "Failed to load author with post id {}: {}",
-                            base_post.post_id, e
-                        )
-                    });
+                    .first::<Posts>(connection)?;
 
                 let author: User = users_dsl::users
                     .filter(user_id_dsl.eq(author_id.user_id))
-                    .first(&connection)
-                    .unwrap_or_else(|e| {
-                        panic!("Failed to find user id {}: {}", author_id.user_id, e)
-                    });
+                    .first(connection)?;
 
                 let author = author.name.clone();
+                let author_karma = karma.get(&author_id.user_id).copied().unwrap_or(0);
+
+                let community: Option<(i32, String)> = community_post_dsl::communitypost
+                    .inner_join(community_dsl::community)
+                    .filter(community_post_dsl::post_id.eq(base_post.post_id))
+                    .select((community_dsl::community_id, community_dsl::name))
+                    .first(connection)
+                    .optional()?;
+                let (community_id, community) = community.unzip();
+
+                let tags: Vec<String> = post_tag_dsl::posttag
+                    .filter(post_tag_dsl::post_id.eq(base_post.post_id))
+                    .select(post_tag_dsl::tag)
+                    .order(post_tag_dsl::tag)
+                    .load(connection)?;
 
                 let root_comments: Vec<PostCommentOn> = post_comment_on_dsl::postcommenton
                     .filter(post_comment_on_id.eq(base_post.post_id))
-                    .get_results(&connection)
-                    .unwrap();
+                    .get_results(connection)?;
 
-                fn transform_comment(id: i32) -> Comment {
-                    let connection = st_read::establish_connection();
+                fn transform_comment(
+                    connection: &PgConnection,
+                    id: i32,
+                    karma: &HashMap<i32, i32>,
+                ) -> QueryResult<Comment> {
                     let post_comment: PostComment = post_comment_dsl::postcomment
                         .filter(post_comment_comment_id.eq(id))
-                        .first(&connection)
-                        .unwrap();
+                        .first(connection)?;
                     let comment: PostComments = post_comments_dsl::postcomments
                         .filter(post_comments_comment_id.eq(id))
-                        .first(&connection)
-                        .unwrap();
+                        .first(connection)?;
 
                     let comment_author: User = users_dsl::users
                         .filter(user_id_dsl.eq(comment.user_id))
-                        .first(&connection)
-                        .unwrap_or_else(|e| panic!("Failed to find user id {}: {}", id, e));
+                        .first(connection)?;
 
-                    Comment::new(post_comment.text, &comment_author.name, vec![])
+                    Ok(Comment::new(
+                        id,
+                        post_comment.text,
+                        comment_author.user_id,
+                        &comment_author.name,
+                        karma.get(&comment_author.user_id).copied().unwrap_or(0),
+                        post_comment.date_posted,
+                        vec![],
+                    ))
                 }
 
-                let comments: Vec<(_, _)> = root_comments
+                let comments = root_comments
                     .into_iter()
                     .map(|base_comment| {
-                        (
-                            transform_comment(base_comment.comment_id),
+                        Ok((
+                            transform_comment(connection, base_comment.comment_id, &karma)?,
                             base_comment.comment_id,
-                        )
+                        ))
                     })
-                    .collect();
+                    .collect::<QueryResult<Vec<(_, _)>>>()?;
 
-                fn load_recursive_comments(id: i32) -> Vec<Comment> {
-                    let connection = st_read::establish_connection();
+                fn load_recursive_comments(
+                    connection: &PgConnection,
+                    id: i32,
+                    karma: &HashMap<i32, i32>,
+                ) -> QueryResult<Vec<Comment>> {
                     use st_read::schema::replyto::dsl as reply_to_dsl;
                     use st_read::schema::replyto::parent_comment as reply_to_parent_comment_dsl;
 
                     let replies: Vec<ReplyTo> = reply_to_dsl::replyto
                         .filter(reply_to_parent_comment_dsl.eq(id))
-                        .get_results(&connection)
-                        .unwrap();
+                        .get_results(connection)?;
 
                     replies
                         .into_iter()
                         .map(|c| {
-                            let mut comment = transform_comment(c.child_comment);
-                            comment.children = load_recursive_comments(c.child_comment);
-                            comment
+                            let mut comment =
+                                transform_comment(connection, c.child_comment, karma)?;
+                            comment.children =
+                                load_recursive_comments(connection, c.child_comment, karma)?;
+                            Ok(comment)
                         })
                         .collect()
                 }
@@ -240,12 +784,12 @@ impl App {
                 let comments = comments
                     .into_iter()
                     .map(|(mut comment, id)| {
-                        let children = load_recursive_comments(id);
+                        let children = load_recursive_comments(connection, id, &karma)?;
                         comment.children = children;
 
-                        comment
+                        Ok(comment)
                     })
-                    .collect();
+                    .collect::<QueryResult<Vec<_>>>()?;
 
                 let mut comment_num = 0;
                 fn count_comments(comment: &Comment, comment_num: &mut usize) {
@@ -260,19 +804,24 @@ impl App {
 
                 let upvotes: Vec<PostReaction> = post_reaction_dsl::postreaction
                     .filter(post_reaction_post_id_dsl.eq(base_post.post_id))
-                    .get_results(&connection)
-                    .unwrap();
+                    .get_results(connection)?;
 
                 let stats = format!("{comment_num} comments, {} upvotes", upvotes.len());
 
-                Post {
+                Ok(Post {
+                    post_id: base_post.post_id,
                     title: base_post.title,
                     short,
                     stats,
                     author,
+                    author_id: author_id.user_id,
+                    author_karma,
+                    community_id,
+                    community,
+                    tags,
                     full: base_post.text,
                     comments,
-                }
+                })
             })
             .collect()
     }
@@ -305,6 +854,14 @@ impl App {
         self.profile_frame.name = user.name;
         self.profile_frame.email = user.email;
         self.profile_frame.email_notifications = user.email_notifications;
+        self.profile_frame.karma = leaderboard::load_karma(user.user_id);
+        self.profile_frame.subscriptions = Self::load_subscriptions(user.user_id);
+        self.profile_frame.saved_posts = saved::load_saved_ids(user.user_id);
+        self.profile_frame.viewed_posts = Self::load_viewed_posts(user.user_id);
+        self.refresh_unread();
+
+        // Start on the posts from the user's communities
+        self.show_home_feed();
     }
 
     /// Sets the App's view and changes the page title
@@ -320,6 +877,44 @@ impl App {
             }
             AppView::CreatePost => {
                 self.page_title.set_title("Creating Post");
+                self.create_frame.restore_draft(self.profile_frame.user_id);
+                // Posts are made in the community being shown, unless another one was picked
+                let community_id = self.posts_frame.community_id();
+                self.create_frame
+                    .load_communities(self.profile_frame.user_id, community_id);
+                self.create_frame.load_names();
+            }
+            AppView::Author => {
+                let title = match self.author_frame.name() {
+                    Some(name) => format!("{}'s Profile", name),
+                    None => String::from("Profile"),
+                };
+                self.page_title.set_title(title);
+            }
+            AppView::Leaderboard => {
+                self.page_title.set_title("Leaderboard");
+                self.leaderboard_frame.load();
+            }
+            AppView::Notifications => {
+                self.page_title.set_title("Notifications");
+                self.notifications_frame.load(self.profile_frame.user_id);
+            }
+            AppView::Messages => {
+                self.page_title.set_title("Messages");
+            }
+            AppView::Saved => {
+                self.page_title.set_title("Saved Posts");
+                self.saved_frame.load(self.profile_frame.user_id);
+            }
+            AppView::Communities => {
+                self.page_title.set_title("Communities");
+                self.communities_frame.load(self.profile_frame.user_id);
+            }
+            AppView::Drafts => {
+                self.page_title.set_title("Drafts");
+                // Save first so that the list shows what is being written now
+                self.save_drafts();
+                self.drafts_frame.load(self.profile_frame.user_id);
             }
             AppView::Initial => {
                 self.page_title.set_title("Welcome to ST-Read");
@@ -334,58 +929,105 @@ impl App {
     }
 }
 
-pub fn run_app<B: Backend>(
+pub fn run_app<B: Backend + Write>(
     terminal: &mut Terminal<B>,
-    mut app: App,
+    app: &mut App,
     tick_rate: Duration,
-) -> io::Result<App> {
+) -> io::Result<()> {
     let mut last_tick = Instant::now();
 
     loop {
-        terminal.draw(|f| ui(f, &mut app))?;
+        let frame = terminal.draw(|f| ui(f, app))?;
+
+        // Find the links that were drawn, and turn them into hyperlinks if the terminal can
+        let mut hyperlinks = Vec::new();
+        app.viewing_frame.links.find_hyperlinks(frame.buffer);
+        app.viewing_frame
+            .links
+            .write_hyperlinks(frame.buffer, &mut hyperlinks)?;
+        if !hyperlinks.is_empty() {
+            terminal.backend_mut().write_all(&hyperlinks)?;
+            Write::flush(terminal.backend_mut())?;
+        }
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+
+            // Clicking on a link opens it
+            if let Event::Mouse(mouse) = event {
+                if matches!(app.view, AppView::Homepage) {
+                    ViewingPostFrame::handle_mouse(app, mouse);
+                }
+            }
+
+            if let Event::Key(key) = event {
                 // If we are quittable and the user presses 'q', then exit the loop
                 if app.quittable && matches!(key.code, KeyCode::Char('q')) {
-                    return Ok(app);
+                    app.save_drafts();
+                    return Ok(());
                 }
 
                 // Pass the other keypresses to the assocated frames for handling
                 match app.view {
                     AppView::Homepage => match app.selected_frame {
                         SelectedFrame::Posts => {
-                            PostsListFrame::handle_key(&mut app, key);
+                            PostsListFrame::handle_key(app, key);
                         }
                         SelectedFrame::ViewPost => {
-                            ViewingPostFrame::handle_key(&mut app, key);
+                            ViewingPostFrame::handle_key(app, key);
                         }
                     },
                     AppView::UserProfile => {
-                        UserProfileFrame::handle_key(&mut app, key);
+                        UserProfileFrame::handle_key(app, key);
                     }
                     AppView::CreatePost => {
-                        CreatePostFrame::handle_key(&mut app, key);
+                        CreatePostFrame::handle_key(app, key);
                     }
                     AppView::Initial => {
-                        InitialFrame::handle_key(&mut app, key);
+                        InitialFrame::handle_key(app, key);
                     }
                     AppView::Login => {
-                        LoginFrame::handle_key(&mut app, key);
+                        LoginFrame::handle_key(app, key);
                     }
                     AppView::Register => {
-                        RegisterFrame::handle_key(&mut app, key);
+                        RegisterFrame::handle_key(app, key);
+                    }
+                    AppView::Drafts => {
+                        DraftsFrame::handle_key(app, key);
+                    }
+                    AppView::Communities => {
+                        CommunitiesFrame::handle_key(app, key);
+                    }
+                    AppView::Saved => {
+                        SavedFrame::handle_key(app, key);
                     }
+                    AppView::Author => {
+                        AuthorFrame::handle_key(app, key);
+                    }
+                    AppView::Leaderboard => {
+                        LeaderboardFrame::handle_key(app, key);
+                    }
+                    AppView::Notifications => {
+                        NotificationsFrame::handle_key(app, key);
+                    }
+                    AppView::Messages => {
+                        MessagesFrame::handle_key(app, key);
+                    }
+                }
+
+                // Hand the terminal over to the user's editor if the key asked for it
+                if let Some(compose) = app.compose.take() {
+                    compose.run(terminal, app)?;
                 }
             }
         }
 
         if last_tick.elapsed() >= tick_rate {
-            // app.on_tick();
+            app.on_tick();
             last_tick = Instant::now();
         }
     }
@@ -417,25 +1059,54 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
             f,
             horizontal[0],
             matches!(app.selected_frame, SelectedFrame::Posts),
+            &app.profile_frame.saved_posts,
+            &app.profile_frame.viewed_posts,
         );
 
         if app.viewing_frame.has_post() {
+            let saved = app
+                .viewing_frame
+                .post()
+                .is_some_and(|post| app.profile_frame.saved_posts.contains(&post.post_id));
+
             app.viewing_frame.render(
                 f,
                 horizontal[1],
                 matches!(app.selected_frame, SelectedFrame::ViewPost),
+                app.profile_frame.dark_mode,
+                saved,
             );
         }
     } else if matches!(app.view, AppView::UserProfile) {
         app.profile_frame.render(f, vertical[1]);
     } else if matches!(app.view, AppView::CreatePost) {
-        app.create_frame.render(f, vertical[1]);
+        app.create_frame.render(
+            f,
+            vertical[1],
+            &app.profile_frame.name,
+            app.profile_frame.dark_mode,
+        );
     } else if matches!(app.view, AppView::Initial) {
         app.initial_frame.render(f, vertical[1]);
     } else if matches!(app.view, AppView::Register) {
         app.register_frame.render(f, vertical[1]);
     } else if matches!(app.view, AppView::Login) {
         app.login_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Drafts) {
+        app.drafts_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Communities) {
+        app.communities_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Saved) {
+        app.saved_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Author) {
+        app.author_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Leaderboard) {
+        app.leaderboard_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Notifications) {
+        app.notifications_frame.render(f, vertical[1]);
+    } else if matches!(app.view, AppView::Messages) {
+        app.messages_frame
+            .render(f, vertical[1], app.profile_frame.dark_mode);
     }
 }
 
@@ -458,22 +1129,46 @@ pub fn get_border_style(selected: bool, locked: bool) -> Style {
 /// The App's main title that shows whether it is the Homepage, Create Post page, etc.
 pub struct PageTitle {
     pub title: String,
+    /// How many notifications the user hasn't read, shown in the corner
+    pub unread: i64,
+    /// How many messages the user hasn't read, shown next to the notifications
+    pub unread_messages: i64,
 }
 
 impl PageTitle {
     pub fn new(title: impl AsRef<str>) -> Self {
         Self {
             title: String::from(title.as_ref()),
+            unread: 0,
+            unread_messages: 0,
         }
     }
 
     pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::LightCyan));
+        let mut unread = Vec::new();
+        if self.unread > 0 {
+            let plural = if self.unread == 1 { "" } else { "s" };
+            unread.push(format!("{} unread notification{}", self.unread, plural));
+        }
+        if self.unread_messages > 0 {
+            let plural = if self.unread_messages == 1 { "" } else { "s" };
+            unread.push(format!("{} unread message{}", self.unread_messages, plural));
+        }
+        if !unread.is_empty() {
+            block = block.title(Span::styled(
+                format!(" {} ", unread.join(", ")),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
         let page_title_block = Paragraph::new(self.title.clone())
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::LightCyan)),
-            )
+            .block(block)
             .style(
                 Style::default()
                     .fg(Color::White)
@@ -0,0 +1,393 @@
+use std::time::SystemTime;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use st_read::models::{User, UserComment};
+use st_read::util::{format_date, time_ago};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView, SelectedFrame};
+use crate::leaderboard::load_karma;
+use crate::list::StatefulList;
+use crate::markdown;
+
+/// Every comment written by the user with the id `$1`, along with the post whose thread it is in.
+/// The newest comments come first
+const LOAD_COMMENTS: &str = "
+WITH RECURSIVE thread (comment_id, post_id) AS (
+    SELECT comment_id, post_id FROM PostCommentOn
+    UNION
+    SELECT r.child_comment, t.post_id
+    FROM ReplyTo r JOIN thread t ON r.parent_comment = t.comment_id
+)
+SELECT c.comment_id, p.post_id, p.title AS post_title, c.text, c.date_posted
+FROM PostComments pc
+    JOIN PostComment c ON c.comment_id = pc.comment_id
+    JOIN thread t ON t.comment_id = c.comment_id
+    JOIN Post p ON p.post_id = t.post_id
+WHERE pc.user_id = $1
+ORDER BY c.date_posted DESC";
+
+/// Loads every comment a user has written, newest first
+pub fn load_comments(user_id: i32) -> QueryResult<Vec<UserComment>> {
+    use diesel::sql_types::Integer;
+    let connection = st_read::establish_connection();
+
+    diesel::sql_query(LOAD_COMMENTS)
+        .bind::<Integer, _>(user_id)
+        .load(&connection)
+}
+
+/// Something the user has written, which opens the post it is in when picked
+enum Entry {
+    Post {
+        post_id: i32,
+        title: String,
+        date_posted: SystemTime,
+    },
+    Comment(UserComment),
+}
+
+impl Entry {
+    fn post_id(&self) -> i32 {
+        match self {
+            Entry::Post { post_id, .. } => *post_id,
+            Entry::Comment(comment) => comment.post_id,
+        }
+    }
+}
+
+/// The public profile of a user, with the posts and comments they have written
+pub struct AuthorFrame {
+    user: Option<User>,
+    /// Whether the profile belongs to the user who is logged in, who is the only one that sees the
+    /// email address
+    is_own: bool,
+    karma: i32,
+    /// The user's posts followed by their comments
+    entries: StatefulList<Entry>,
+    /// How many of the entries are posts
+    post_count: usize,
+    /// Why the profile couldn't be loaded
+    message: Option<String>,
+}
+
+impl AuthorFrame {
+    pub fn new() -> Self {
+        Self {
+            user: None,
+            is_own: false,
+            karma: 0,
+            entries: StatefulList::highlighted(Vec::new()),
+            post_count: 0,
+            message: None,
+        }
+    }
+
+    /// Loads the profile of the user with the id `user_id`, as seen by the user with the id
+    /// `viewer_id`
+    pub fn load(&mut self, user_id: i32, viewer_id: i32) {
+        use st_read::schema::post::dsl as post_dsl;
+        use st_read::schema::posts::dsl as posts_dsl;
+        use st_read::schema::users::dsl as users_dsl;
+        let connection = st_read::establish_connection();
+
+        *self = Self::new();
+
+        let user: User = match users_dsl::users.find(user_id).first(&connection) {
+            Ok(user) => user,
+            Err(e) => {
+                self.message = Some(format!("Couldn't load the profile: {}", e));
+                return;
+            }
+        };
+
+        let posts: Vec<(i32, String, SystemTime)> = posts_dsl::posts
+            .inner_join(post_dsl::post)
+            .filter(posts_dsl::user_id.eq(user_id))
+            .order(post_dsl::date_posted.desc())
+            .select((post_dsl::post_id, post_dsl::title, post_dsl::date_posted))
+            .load(&connection)
+            .unwrap_or_default();
+
+        let comments = load_comments(user_id).unwrap_or_default();
+
+        self.post_count = posts.len();
+
+        let mut entries: Vec<Entry> = posts
+            .into_iter()
+            .map(|(post_id, title, date_posted)| Entry::Post {
+                post_id,
+                title,
+                date_posted,
+            })
+            .collect();
+        entries.extend(comments.into_iter().map(Entry::Comment));
+
+        self.entries = StatefulList::highlighted(entries);
+        self.karma = load_karma(user_id);
+        self.is_own = user_id == viewer_id;
+        self.user = Some(user);
+    }
+
+    /// The name of the user whose profile is shown
+    pub fn name(&self) -> Option<&str> {
+        self.user.as_ref().map(|user| user.name.as_str())
+    }
+
+    /// Whether the user who is logged in can message the user whose profile is shown
+    fn can_message(&self) -> bool {
+        !self.is_own && self.user.as_ref().is_some_and(|user| !user.deleted)
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let entries = &mut app.author_frame.entries;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !entries.items.is_empty() => {
+                entries.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !entries.items.is_empty() => {
+                entries.previous();
+            }
+            KeyCode::Enter => {
+                if let Some(post_id) = entries.selected_item().map(Entry::post_id) {
+                    app.set_view(AppView::Homepage);
+                    app.view_post(post_id);
+                    app.selected_frame = SelectedFrame::ViewPost;
+                }
+            }
+            KeyCode::Char('m') if app.author_frame.can_message() => {
+                if let Some(user_id) = app.author_frame.user.as_ref().map(|user| user.user_id) {
+                    app.show_messages(Some(user_id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(6), Constraint::Min(0)].as_ref())
+            .split(area);
+
+        self.render_header(f, layout[0]);
+        self.render_entries(f, layout[1]);
+    }
+
+    /// Renders the name, join date, karma, and, on the user's own profile, the email address
+    fn render_header<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let mut text = Vec::new();
+
+        if let Some(user) = &self.user {
+            text.push(Spans::from(Span::styled(
+                user.name.clone(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            if self.is_own {
+                text.push(Spans::from(Span::styled(
+                    user.email.clone(),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            }
+            text.push(Spans::from(format!(
+                "Joined {}",
+                format_date(user.date_joined)
+            )));
+            text.push(Spans::from(format!("{} karma", self.karma)));
+        } else if let Some(message) = &self.message {
+            text.push(Spans::from(message.clone()));
+        }
+
+        let header = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(false, false))
+                .title(if self.can_message() {
+                    "Profile (m to message)"
+                } else {
+                    "Profile"
+                }),
+        );
+
+        f.render_widget(header, area);
+    }
+
+    /// Renders the user's posts, then their comments
+    fn render_entries<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let deselected_style = Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .fg(Color::Gray);
+        let heading_style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+
+        let comment_count = self.entries.items.len() - self.post_count;
+        // The row of the selected entry, which is kept on the screen
+        let mut selected_row = 0;
+        let mut text = vec![Spans::from(Span::styled(
+            format!("Posts ({})", self.post_count),
+            heading_style,
+        ))];
+
+        for (i, entry) in self.entries.items.iter().enumerate() {
+            if i == self.post_count {
+                text.push(Spans::from(Span::styled(
+                    format!("Comments ({})", comment_count),
+                    heading_style,
+                )));
+            }
+
+            let lines = match entry {
+                Entry::Post {
+                    title, date_posted, ..
+                } => vec![title.clone(), format!("posted {}", time_ago(*date_posted))],
+                Entry::Comment(comment) => {
+                    // Only the start of the comment is shown, without any formatting
+                    let mut short: String = markdown::plain_text(&comment.text)
+                        .chars()
+                        .take(60)
+                        .collect();
+                    short.push_str(" ...");
+
+                    vec![
+                        short,
+                        format!(
+                            "on {}, {}",
+                            comment.post_title,
+                            time_ago(comment.date_posted)
+                        ),
+                    ]
+                }
+            };
+
+            if self.entries.selected() == Some(i) {
+                selected_row = text.len() as u16;
+            }
+            self.entries
+                .push_rows(&mut text, i, lines, deselected_style);
+        }
+
+        if comment_count == 0 {
+            text.push(Spans::from(Span::styled("Comments (0)", heading_style)));
+        }
+
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let offset = (selected_row + 2).saturating_sub(inner.height);
+
+        let entries = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((offset, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(true, false))
+                    .title("Posts and Comments (Enter to open, Esc to go back)"),
+            );
+
+        f.render_widget(entries, area);
+    }
+}
+
+/// The authors of the open post and its comments, one of which can be picked to see their profile
+pub struct AuthorList {
+    authors: StatefulList<(i32, String)>,
+    /// A boolean for if the list is open and taking every key. Esc must be pressed to close it
+    pub locked: bool,
+}
+
+impl AuthorList {
+    pub fn new() -> Self {
+        Self {
+            authors: StatefulList::with_items(Vec::new()),
+            locked: false,
+        }
+    }
+
+    /// Opens the list with the authors given by their ids and names, leaving out repeats
+    pub fn open(&mut self, authors: Vec<(i32, String)>) {
+        let mut unique: Vec<(i32, String)> = Vec::new();
+        for author in authors {
+            if !unique.iter().any(|(id, _)| *id == author.0) {
+                unique.push(author);
+            }
+        }
+
+        self.authors = StatefulList::with_items(unique);
+        self.authors.select(Some(0));
+        self.locked = true;
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let authors = &mut app.viewing_frame.authors;
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('a') => {
+                authors.locked = false;
+                app.quittable = true;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                authors.authors.previous();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                authors.authors.next();
+            }
+            KeyCode::Enter => {
+                if let Some(&(user_id, _)) = authors.authors.selected_item() {
+                    authors.locked = false;
+                    app.quittable = true;
+                    app.show_author(user_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let lines: Vec<Spans> = self
+            .authors
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, (_, name))| {
+                let style = if self.authors.selected() == Some(i) {
+                    Style::default().bg(Color::DarkGray)
+                } else {
+                    Style::default()
+                };
+                // The author of the post comes first
+                let role = if i == 0 { " (post)" } else { "" };
+
+                Spans::from(Span::styled(format!("{}{}", name, role), style))
+            })
+            .collect();
+
+        // Keep the selected author on the screen
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let selected = self.authors.selected().unwrap_or(0) as u16;
+        let offset = (selected + 1).saturating_sub(inner.height);
+
+        let list = Paragraph::new(lines)
+            .scroll((offset, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(true, self.locked))
+                    .title("Authors (Enter to see their profile, Esc to close)"),
+            )
+            .style(Style::default().fg(Color::Gray));
+
+        f.render_widget(list, area);
+    }
+}
@@ -0,0 +1,355 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use st_read::models::{CommunityInfo, NewCommunity};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView, SelectedFrame};
+use crate::editor::Editor;
+use crate::list::StatefulList;
+use crate::posts_list::FeedFilter;
+
+/// The most characters a community's name can have
+pub const MAX_NAME_LENGTH: usize = 50;
+
+/// Every community with the name of its creator, how many subscribers and posts it has, and
+/// whether the user with the id `$1` is subscribed. The most subscribed communities come first
+const LOAD_COMMUNITIES: &str = "
+SELECT
+    c.community_id,
+    c.name,
+    c.description,
+    u.name AS creator,
+    (SELECT COUNT(*) FROM Subscription s WHERE s.community_id = c.community_id) AS subscribers,
+    (SELECT COUNT(*) FROM CommunityPost p WHERE p.community_id = c.community_id) AS posts,
+    EXISTS (
+        SELECT 1 FROM Subscription s WHERE s.community_id = c.community_id AND s.user_id = $1
+    ) AS subscribed
+FROM Community c JOIN Users u ON u.user_id = c.creator_id
+ORDER BY subscribers DESC, c.name";
+
+/// Loads every community, noting which ones the user is subscribed to
+pub fn load_communities(user_id: i32) -> QueryResult<Vec<CommunityInfo>> {
+    use diesel::sql_types::Integer;
+    let connection = st_read::establish_connection();
+
+    diesel::sql_query(LOAD_COMMUNITIES)
+        .bind::<Integer, _>(user_id)
+        .load(&connection)
+}
+
+/// Checks that a community name is short and only made of letters, numbers, '-' and '_', so
+/// that it can be typed easily. Returns the name trimmed of surrounding whitespace
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        Err(String::from("Give the community a name"))
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        Err(format!(
+            "The name can't be over {} characters",
+            MAX_NAME_LENGTH
+        ))
+    } else if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        Err(String::from(
+            "The name can only have letters, numbers, '-' and '_'",
+        ))
+    } else {
+        Ok(String::from(name))
+    }
+}
+
+/// Creates a new community, returning its id
+fn create_community(name: &str, description: &str, creator_id: i32) -> QueryResult<i32> {
+    use st_read::schema::community::dsl;
+    let connection = st_read::establish_connection();
+
+    let community = NewCommunity {
+        name: name.to_owned(),
+        description: description.to_owned(),
+        creator_id,
+    };
+
+    community
+        .insert_into(dsl::community)
+        .returning(dsl::community_id)
+        .get_result(&connection)
+}
+
+/// Lists every community so that the homepage can be narrowed down to one, and new communities
+/// can be created
+pub struct CommunitiesFrame {
+    communities: StatefulList<CommunityInfo>,
+    /// The form for creating a community, while it is open
+    form: Option<CommunityForm>,
+    /// The outcome of the last thing that was done, such as creating a community
+    message: Option<String>,
+}
+
+impl CommunitiesFrame {
+    pub fn new() -> Self {
+        Self {
+            communities: StatefulList::highlighted(Vec::new()),
+            form: None,
+            message: None,
+        }
+    }
+
+    /// Loads the communities again, keeping the selected one selected
+    pub fn load(&mut self, user_id: i32) {
+        let selected = self
+            .communities
+            .selected_item()
+            .map(|community| community.community_id);
+
+        self.communities = StatefulList::highlighted(load_communities(user_id).unwrap_or_default());
+        if let Some(community_id) = selected {
+            self.select(community_id);
+        }
+    }
+
+    fn select(&mut self, community_id: i32) {
+        let i = self
+            .communities
+            .items
+            .iter()
+            .position(|community| community.community_id == community_id);
+
+        if i.is_some() {
+            self.communities.select(i);
+        }
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        app.communities_frame.message = None;
+
+        if app.communities_frame.form.is_some() {
+            CommunityForm::handle_key(app, key);
+            return;
+        }
+
+        let communities = &mut app.communities_frame.communities;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !communities.items.is_empty() => {
+                communities.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !communities.items.is_empty() => {
+                communities.previous();
+            }
+            KeyCode::Enter => {
+                // Show only the posts in the selected community on the homepage
+                if let Some(community) = communities.selected_item() {
+                    let community = (community.community_id, community.name.clone());
+                    app.posts_frame
+                        .show_filter(FeedFilter::Community(community.0, community.1));
+                    app.selected_frame = SelectedFrame::Posts;
+                    app.set_view(AppView::Homepage);
+                }
+            }
+            KeyCode::Char('s') => {
+                let (community_id, name, subscribed) = match communities.selected_item() {
+                    Some(c) => (c.community_id, c.name.clone(), c.subscribed),
+                    None => return,
+                };
+
+                app.communities_frame.message =
+                    Some(match app.set_subscribed(community_id, !subscribed) {
+                        Ok(()) if subscribed => format!("Unsubscribed from {}", name),
+                        Ok(()) => format!("Subscribed to {}", name),
+                        Err(e) => format!("Couldn't change subscription: {}", e),
+                    });
+                app.communities_frame.load(app.profile_frame.user_id);
+            }
+            KeyCode::Char('n') => {
+                app.communities_frame.form = Some(CommunityForm::new());
+                app.quittable = false;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        // The form for creating a community sits under the list while it is open
+        let area = match &mut self.form {
+            Some(form) => {
+                let layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(8)].as_ref())
+                    .split(area);
+
+                form.render(f, layout[1]);
+                layout[0]
+            }
+            None => area,
+        };
+
+        let deselected_style = Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .fg(Color::Gray);
+
+        let mut text = Vec::new();
+
+        if self.communities.items.is_empty() {
+            text.push(Spans::from(Span::styled(
+                format!("{}No communities yet", self.communities.placeholder()),
+                deselected_style,
+            )));
+        }
+
+        for (i, community) in self.communities.items.iter().enumerate() {
+            let description = if community.description.trim().is_empty() {
+                String::from("No description")
+            } else {
+                community.description.clone()
+            };
+
+            let mut stats = format!(
+                "{} subscribers, {} posts, created by {}",
+                community.subscribers, community.posts, community.creator
+            );
+            if community.subscribed {
+                stats.push_str(" (subscribed)");
+            }
+
+            let lines = [community.name.clone(), description, stats];
+            self.communities
+                .push_rows(&mut text, i, lines, deselected_style);
+        }
+
+        let mut title = String::from(
+            "Communities (Enter to show posts, s to subscribe, n to create, Esc to go back)",
+        );
+        if let Some(message) = &self.message {
+            title = format!("{} - {}", title, message);
+        }
+
+        let communities = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(self.form.is_none(), false))
+                .title(title),
+        );
+
+        f.render_widget(communities, area);
+    }
+}
+
+/// The name and description of a community that is being created
+struct CommunityForm {
+    name: Editor,
+    description: Editor,
+    /// Whether the description is being typed instead of the name
+    on_description: bool,
+}
+
+impl CommunityForm {
+    fn new() -> Self {
+        Self {
+            name: Editor::single_line(),
+            description: Editor::single_line(),
+            on_description: false,
+        }
+    }
+
+    fn handle_key(app: &mut App, key: KeyEvent) {
+        let form = match &mut app.communities_frame.form {
+            Some(form) => form,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                app.communities_frame.form = None;
+                app.quittable = true;
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                form.on_description = !form.on_description;
+            }
+            KeyCode::Enter => {
+                let name = match validate_name(form.name.text()) {
+                    Ok(name) => name,
+                    Err(e) => {
+                        app.communities_frame.message = Some(e);
+                        return;
+                    }
+                };
+                let description = form.description.text().trim().to_owned();
+
+                match create_community(&name, &description, app.profile_frame.user_id) {
+                    Ok(community_id) => {
+                        app.communities_frame.form = None;
+                        app.quittable = true;
+                        app.communities_frame.load(app.profile_frame.user_id);
+                        app.communities_frame.select(community_id);
+                        app.communities_frame.message = Some(format!("Created {}", name));
+                    }
+                    Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                        app.communities_frame.message =
+                            Some(format!("There is already a community named {}", name));
+                    }
+                    Err(e) => {
+                        app.communities_frame.message =
+                            Some(format!("Couldn't create community: {}", e));
+                    }
+                }
+            }
+            _ => {
+                if form.on_description {
+                    form.description.handle_key(key);
+                } else {
+                    form.name.handle_key(key);
+                }
+            }
+        }
+    }
+
+    fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(true, true))
+            .title("New Community (Tab to switch, Enter to create, Esc to cancel)");
+
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Length(3)].as_ref())
+            .split(inner);
+
+        let input = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+
+        let name = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(!self.on_description, false))
+            .title("Name");
+        self.name
+            .render(f, layout[0], name, input, !self.on_description);
+
+        let description = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(self.on_description, false))
+            .title("Description");
+        self.description.render(
+            f,
+            layout[1],
+            description,
+            Style::default(),
+            self.on_description,
+        );
+    }
+}
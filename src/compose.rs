@@ -0,0 +1,214 @@
+use std::collections::hash_map::RandomState;
+use std::fs::OpenOptions;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::{env, fs, process};
+
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use tui::backend::Backend;
+use tui::Terminal;
+
+use crate::app::App;
+use crate::create_post::validate_post;
+
+/// Something to be written in the user's own editor instead of in the app. Since the terminal has
+/// to be handed over to the editor, it is opened by `run_app` once the key asking for it has been
+/// handled
+#[derive(Debug, Clone, Copy)]
+pub enum Compose {
+    /// The title and text of the post in `CreatePostFrame`
+    NewPost,
+    /// The reply to the post being viewed
+    Reply,
+    /// The message being written in the open conversation
+    Message,
+    /// The title and text of the post being viewed, which is saved once the editor is closed
+    EditPost,
+}
+
+impl Compose {
+    /// Opens the editor with the text written so far, and loads what was written back into the
+    /// app. Nothing is changed if the editor exits with an error
+    pub fn run<B: Backend + Write>(
+        self,
+        terminal: &mut Terminal<B>,
+        app: &mut App,
+    ) -> io::Result<()> {
+        let text = match self {
+            Compose::NewPost => {
+                let (title, text) = app.create_frame.text();
+                join_post(title, text)
+            }
+            Compose::Reply => String::from(app.viewing_frame.reply_text()),
+            Compose::Message => String::from(app.messages_frame.message_text()),
+            Compose::EditPost => match app.viewing_frame.post() {
+                Some(post) => join_post(&post.title, &post.full),
+                None => return Ok(()),
+            },
+        };
+
+        let edited = match edit(terminal, &text) {
+            Ok(Some(edited)) => edited,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                app.page_title
+                    .set_title(format!("Failed to open {}: {}", editor(), e));
+                return Ok(());
+            }
+        };
+
+        match self {
+            Compose::NewPost => {
+                let (title, text) = split_post(&edited);
+                app.create_frame.set_text(&title, &text);
+            }
+            Compose::Reply => {
+                app.viewing_frame.set_reply_text(edited.trim_end());
+            }
+            Compose::Message => {
+                app.messages_frame.set_message_text(edited.trim_end());
+            }
+            Compose::EditPost => {
+                let (title, text) = split_post(&edited);
+                let (title, text) = match validate_post(&title, &text) {
+                    Ok(post) => post,
+                    Err(e) => {
+                        app.page_title.set_title(format!("Post not saved: {}", e));
+                        return Ok(());
+                    }
+                };
+
+                if let Some(post_id) = app.viewing_frame.post().map(|post| post.post_id) {
+                    match app.update_post(post_id, &title, &text) {
+                        Ok(()) => {
+                            app.reload_posts();
+                            app.view_post(post_id);
+                        }
+                        Err(e) => {
+                            app.page_title
+                                .set_title(format!("Failed to save post: {}", e));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How many random names are tried for the temporary file before giving up
+const TEMP_FILE_ATTEMPTS: usize = 16;
+
+/// The command to edit text with, from `$VISUAL` or `$EDITOR`
+fn editor() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+/// Writes the text to a temporary file and opens it in the editor, with the app suspended until
+/// the editor is closed. Returns the edited text, or `None` if the editor failed
+fn edit<B: Backend + Write>(terminal: &mut Terminal<B>, text: &str) -> io::Result<Option<String>> {
+    let path = create_temp_file(text)?;
+
+    suspend(terminal)?;
+    let status = run_editor(&path);
+    resume(terminal)?;
+
+    let edited = match status {
+        Ok(status) if status.success() => fs::read_to_string(&path).map(Some),
+        Ok(_) => Ok(None),
+        Err(e) => Err(e),
+    };
+    let _ = fs::remove_file(&path);
+
+    edited
+}
+
+/// Writes the text to a new file in the temporary directory. The name is random and the file must
+/// not exist yet, so that another user can't have it written somewhere else through a symlink
+fn create_temp_file(text: &str) -> io::Result<PathBuf> {
+    for _ in 0..TEMP_FILE_ATTEMPTS {
+        // Every `RandomState` is seeded differently, which makes its hashes random enough here
+        let suffix = RandomState::new().build_hasher().finish();
+        let path = env::temp_dir().join(format!("st-read-{}-{:016x}.md", process::id(), suffix));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        match options.open(&path) {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(text.as_bytes()) {
+                    let _ = fs::remove_file(&path);
+                    return Err(e);
+                }
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "couldn't find an unused name for a temporary file",
+    ))
+}
+
+fn run_editor(path: &Path) -> io::Result<ExitStatus> {
+    // The editor may be given with arguments, such as "code --wait"
+    let editor = editor();
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    Command::new(program).args(words).arg(path).status()
+}
+
+/// Gives the terminal back to the shell, the same way it is left when the app exits
+fn suspend<B: Backend + Write>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()
+}
+
+/// Takes the terminal back from the editor, and redraws everything on the next frame
+fn resume<B: Backend + Write>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.hide_cursor()?;
+    terminal.clear()
+}
+
+/// Puts a post into a file with its title on the first line, followed by a blank line and the text
+fn join_post(title: &str, text: &str) -> String {
+    format!("{}\n\n{}", title, text)
+}
+
+/// Reads a post back out of a file written by `join_post`
+fn split_post(edited: &str) -> (String, String) {
+    let (title, text) = edited.split_once('\n').unwrap_or((edited, ""));
+
+    (
+        String::from(title.trim()),
+        String::from(text.trim_start_matches(['\r', '\n']).trim_end()),
+    )
+}
@@ -0,0 +1,133 @@
+use crossterm::event::KeyEvent;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Style};
+use tui::widgets::Block;
+use tui::Frame;
+
+use crate::drafts::{self, Autosave, DraftCopy, DraftOf};
+use crate::editor::Editor;
+use crate::mentions::Mentions;
+
+/// The box a reply or a message is written in. What is written is kept saved as a draft, names
+/// are suggested while a mention is typed, and the text can be handed to the user's own editor
+/// through `Compose`. The boxes using it handle the keys for moving around and sending
+pub struct ComposeBox {
+    editor: Editor,
+    pub locked: bool,
+    draft: Autosave,
+    /// Suggests names while a mention is typed
+    mentions: Mentions,
+}
+
+impl ComposeBox {
+    pub fn new() -> Self {
+        Self {
+            editor: Editor::multi_line(),
+            locked: false,
+            draft: Autosave::new(),
+            mentions: Mentions::new(),
+        }
+    }
+
+    /// Clears the text, leaving its draft saved
+    pub fn clear(&mut self) {
+        self.editor.clear();
+        self.draft = Autosave::new();
+    }
+
+    /// Loads the names that can be mentioned again, to pick up new users
+    pub fn load_names(&mut self) {
+        self.mentions.load();
+    }
+
+    /// Everyone who can be mentioned, for highlighting their mentions
+    pub fn names(&self) -> &[String] {
+        self.mentions.names()
+    }
+
+    pub fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.editor.set_text(text);
+    }
+
+    /// Handles the editing keys while the box is locked, returning whether the key was used
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if !self.locked {
+            return false;
+        }
+
+        // Picking a name takes the keys it needs before the editor gets them
+        if self.mentions.handle_key(&mut self.editor, key) {
+            return true;
+        }
+        if self.editor.handle_key(key) {
+            self.mentions.update(&self.editor);
+            return true;
+        }
+
+        false
+    }
+
+    /// Saves the text as a draft, or deletes the draft once the text is cleared
+    pub fn save_draft(&mut self, user_id: i32, of: DraftOf) {
+        let _ = if self.editor.text().trim().is_empty() {
+            self.draft.discard()
+        } else {
+            self.draft.save(user_id, of, "", self.editor.text())
+        };
+    }
+
+    /// Copies the text for saving as a draft later, unless nothing is written
+    pub fn draft_copy(&self, of: DraftOf) -> Option<DraftCopy> {
+        if self.editor.text().trim().is_empty() {
+            return None;
+        }
+
+        Some(self.draft.copy(of, "", self.editor.text()))
+    }
+
+    /// Brings back the latest draft, unless something is already written
+    pub fn restore_draft(&mut self, user_id: i32, of: DraftOf) {
+        if !self.editor.text().trim().is_empty() {
+            return;
+        }
+
+        if let Ok(Some(draft)) = drafts::latest_draft(user_id, of) {
+            self.editor.set_text(&draft.text);
+            self.draft.resume(&draft);
+        }
+    }
+
+    /// Clears the text if it was being written to a draft that has been discarded
+    pub fn forget_draft(&mut self, draft_id: i32) {
+        if self.draft.is(draft_id) {
+            self.clear();
+        }
+    }
+
+    /// Clears the text and deletes its draft, once what was written has been sent
+    pub fn discard(&mut self) {
+        self.editor.clear();
+        let _ = self.draft.discard();
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        block: Block,
+        is_selected: bool,
+    ) {
+        let writing = self.locked && is_selected;
+        self.editor
+            .render(f, area, block, Style::default().fg(Color::Gray), writing);
+
+        if writing {
+            self.mentions.render(f, area);
+        }
+    }
+}
@@ -7,22 +7,89 @@ use tui::Frame;
 use tui::{backend::Backend, widgets::Paragraph};
 
 use crate::app::{get_border_style, App, AppView};
+use crate::communities;
+use crate::compose::Compose;
+use crate::drafts::{self, Autosave, DraftCopy, DraftOf};
+use crate::editor::Editor;
+use crate::markdown::TextOptions;
+use crate::mentions::Mentions;
+use crate::scroll::{wrapped_height, ScrollState};
+use crate::tags::{self, TagInput, MAX_TAGS};
+use crate::viewing_post;
+use st_read::models::Draft;
 
 /// Enum for which box is selected on the create post screen
 #[derive(Debug, Copy, Clone)]
 enum SelectedBox {
     Title,
+    Community,
+    Tags,
     Text,
     PostButton,
 }
 
+/// The most characters a post's title can have
+pub const MAX_TITLE_LENGTH: usize = 300;
+/// The most characters a post's text can have
+pub const MAX_TEXT_LENGTH: usize = 40_000;
+
+/// Checks that a post has a title and text that aren't too long, returning them trimmed of
+/// surrounding whitespace, or a message explaining what needs to be fixed
+pub fn validate_post(title: &str, text: &str) -> Result<(String, String), String> {
+    let title = title.trim();
+    let text = text.trim();
+
+    if title.is_empty() || title == "New Post" {
+        Err(String::from("Give the post a title"))
+    } else if title.chars().count() > MAX_TITLE_LENGTH {
+        Err(format!(
+            "The title can't be over {} characters",
+            MAX_TITLE_LENGTH
+        ))
+    } else if text.is_empty() {
+        Err(String::from("The post can't be empty"))
+    } else if text.chars().count() > MAX_TEXT_LENGTH {
+        Err(format!(
+            "The post can't be over {} characters",
+            MAX_TEXT_LENGTH
+        ))
+    } else {
+        Ok((String::from(title), String::from(text)))
+    }
+}
+
+/// Which of the boxes for writing the post and its rendered preview are shown
+#[derive(Debug, Copy, Clone)]
+enum PreviewMode {
+    Edit,
+    SideBySide,
+    Preview,
+}
+
+impl PreviewMode {
+    fn next(self) -> Self {
+        match self {
+            PreviewMode::Edit => PreviewMode::SideBySide,
+            PreviewMode::SideBySide => PreviewMode::Preview,
+            PreviewMode::Preview => PreviewMode::Edit,
+        }
+    }
+}
+
 /// Represents the view where the user can create a new post
 pub struct CreatePostFrame {
     selected: SelectedBox,
     title_box: TitleBox,
+    community_box: CommunityBox,
+    tags_box: TagsBox,
     text_box: TextBox,
     post_button: PostButton,
     exitable: bool,
+    draft: Autosave,
+    preview_mode: PreviewMode,
+    preview_scroll: ScrollState,
+    /// Asks whether to leave, after Esc is pressed with something written
+    confirm_leave: bool,
 }
 
 impl CreatePostFrame {
@@ -30,20 +97,141 @@ impl CreatePostFrame {
         Self {
             selected: SelectedBox::Title,
             title_box: TitleBox::new(),
+            community_box: CommunityBox::new(),
+            tags_box: TagsBox::new(),
             text_box: TextBox::new(),
             post_button: PostButton::new(),
             exitable: true,
+            draft: Autosave::new(),
+            preview_mode: PreviewMode::Edit,
+            preview_scroll: ScrollState::new(),
+            confirm_leave: false,
+        }
+    }
+
+    /// The title and text written so far
+    pub fn text(&self) -> (&str, &str) {
+        (self.title_box.editor.text(), self.text_box.editor.text())
+    }
+
+    pub fn set_text(&mut self, title: &str, text: &str) {
+        self.title_box.editor.set_text(title);
+        self.text_box.editor.set_text(text);
+    }
+
+    /// Loads the communities that the post can be made in. `community_id` is picked if no other
+    /// community has been picked yet
+    pub fn load_communities(&mut self, user_id: i32, community_id: Option<i32>) {
+        let communities = communities::load_communities(user_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|community| (community.community_id, community.name))
+            .collect();
+
+        let picked = self.community_box.picked().or(community_id);
+        self.community_box.communities = communities;
+        self.community_box.pick(picked);
+    }
+
+    /// Loads the names of the users that can be mentioned in the post
+    pub fn load_names(&mut self) {
+        self.text_box.mentions.load();
+    }
+
+    /// Returns true if nothing has been written besides the default title
+    fn is_empty(&self) -> bool {
+        let (title, text) = self.text();
+        (title.trim().is_empty() || title == "New Post") && text.trim().is_empty()
+    }
+
+    /// Saves what has been written as a draft, or deletes the draft once everything is cleared
+    pub fn save_draft(&mut self, user_id: i32) {
+        let _ = if self.is_empty() {
+            self.draft.discard()
+        } else {
+            let (title, text) = (self.title_box.editor.text(), self.text_box.editor.text());
+            self.draft.save(user_id, DraftOf::NewPost, title, text)
+        };
+    }
+
+    /// Copies what has been written for saving as a draft later, unless nothing has
+    pub fn draft_copy(&self) -> Option<DraftCopy> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (title, text) = (self.title_box.editor.text(), self.text_box.editor.text());
+        Some(self.draft.copy(DraftOf::NewPost, title, text))
+    }
+
+    /// Brings back the latest draft of a new post, unless something is already being written
+    pub fn restore_draft(&mut self, user_id: i32) {
+        if !self.is_empty() {
+            return;
+        }
+
+        if let Ok(Some(draft)) = drafts::latest_draft(user_id, DraftOf::NewPost) {
+            self.resume_draft(&draft);
+        }
+    }
+
+    /// Continues writing a draft picked from the drafts list
+    pub fn resume_draft(&mut self, draft: &Draft) {
+        self.set_text(&draft.title, &draft.text);
+        self.draft.resume(draft);
+    }
+
+    /// Clears the post if it was being written to a draft that has been discarded
+    pub fn forget_draft(&mut self, draft_id: i32) {
+        if self.draft.is(draft_id) {
+            self.draft = Autosave::new();
+            self.title_box.clear();
+            self.tags_box.clear();
+            self.text_box.clear();
         }
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
-        // If we aren't locked and the user presses escape, then we return to the homepage
+        // Any key dismisses the reason the post couldn't be made
+        app.create_frame.post_button.error = None;
+
+        if app.create_frame.confirm_leave {
+            Self::handle_confirm_key(app, key);
+            return;
+        }
+
+        // If we aren't locked and the user presses escape, then we return to the homepage, after
+        // checking with the user if something has been written
         if app.create_frame.exitable && matches!(key.code, KeyCode::Esc) {
-            app.set_view(AppView::Homepage);
+            if app.create_frame.is_empty() {
+                app.set_view(AppView::Homepage);
+            } else {
+                app.create_frame.confirm_leave = true;
+            }
+        } else if app.create_frame.exitable && matches!(key.code, KeyCode::Char('e')) {
+            // Write the post in the user's own editor instead
+            app.compose = Some(Compose::NewPost);
+        } else if app.create_frame.exitable && matches!(key.code, KeyCode::Char('d')) {
+            app.set_view(AppView::Drafts);
+        } else if app.create_frame.exitable && matches!(key.code, KeyCode::Char('v')) {
+            // Switch between writing, previewing, and doing both side by side
+            app.create_frame.preview_mode = app.create_frame.preview_mode.next();
+        } else if matches!(app.create_frame.preview_mode, PreviewMode::Preview) {
+            // Only the preview is shown, so every scrolling key moves through it
+            app.create_frame.preview_scroll.handle_key(key.code);
+        } else if app.create_frame.exitable
+            && matches!(app.create_frame.preview_mode, PreviewMode::SideBySide)
+            && matches!(key.code, KeyCode::PageUp | KeyCode::PageDown)
+        {
+            app.create_frame.preview_scroll.handle_key(key.code);
         } else {
             // We distribute the correct key events to the selected item
             if matches!(app.create_frame.selected, SelectedBox::Title) {
                 TitleBox::handle_key(app, key);
+            } else if matches!(app.create_frame.selected, SelectedBox::Community) {
+                CommunityBox::handle_key(app, key);
+            } else if matches!(app.create_frame.selected, SelectedBox::Tags) {
+                TagsBox::handle_key(app, key);
             } else if matches!(app.create_frame.selected, SelectedBox::Text) {
                 TextBox::handle_key(app, key);
             } else if matches!(app.create_frame.selected, SelectedBox::PostButton) {
@@ -52,14 +240,83 @@ impl CreatePostFrame {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
-        // Creates a new vertical layout where the content takes up 86% of the view
+    /// Handles the answer to whether to leave with something written
+    fn handle_confirm_key(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                // The post is kept as a draft to come back to
+                app.create_frame.confirm_leave = false;
+                app.save_drafts();
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Char('x') => {
+                app.create_frame.confirm_leave = false;
+                let _ = app.create_frame.draft.discard();
+                app.create_frame.title_box.clear();
+                app.create_frame.tags_box.clear();
+                app.create_frame.text_box.clear();
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.create_frame.confirm_leave = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Renders the boxes for writing the post, the preview of it, or both. The preview is shown
+    /// with `author` as the author of the post
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        author: &str,
+        dark_mode: bool,
+    ) {
+        let (edit_area, preview_area) = match self.preview_mode {
+            PreviewMode::Edit => (Some(area), None),
+            PreviewMode::Preview => (None, Some(area)),
+            PreviewMode::SideBySide => {
+                let layout = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                    .split(area);
+
+                (Some(layout[0]), Some(layout[1]))
+            }
+        };
+
+        if let Some(edit_area) = edit_area {
+            self.render_boxes(f, edit_area);
+        }
+
+        if let Some(preview_area) = preview_area {
+            // Without the post button, the question of whether to leave goes under the preview
+            let preview_area = if self.confirm_leave && edit_area.is_none() {
+                let layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+                    .split(preview_area);
+
+                Self::render_confirm(f, layout[1]);
+                layout[0]
+            } else {
+                preview_area
+            };
+
+            self.render_preview(f, preview_area, author, dark_mode);
+        }
+    }
+
+    fn render_boxes<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        // Creates a new vertical layout where the content takes up 79% of the view
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
                     Constraint::Percentage(7),
-                    Constraint::Percentage(86),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(79),
                     Constraint::Percentage(7),
                 ]
                 .as_ref(),
@@ -67,139 +324,443 @@ impl CreatePostFrame {
             .split(area);
 
         let title_area = layout[0];
-        let text_area = layout[1];
-        let button_area = layout[2];
+        let tags_area = layout[1];
+        let text_area = layout[2];
+        let button_area = layout[3];
+
+        // The community picker sits next to the title
+        let title_row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+            .split(title_area);
 
         // Render the title box
         self.title_box
-            .render(f, title_area, matches!(self.selected, SelectedBox::Title));
+            .render(f, title_row[0], matches!(self.selected, SelectedBox::Title));
+
+        // Render the community picker
+        self.community_box.render(
+            f,
+            title_row[1],
+            matches!(self.selected, SelectedBox::Community),
+        );
+
+        // Render the tags box
+        self.tags_box
+            .render(f, tags_area, matches!(self.selected, SelectedBox::Tags));
 
         // Render the content box
         self.text_box
             .render(f, text_area, matches!(self.selected, SelectedBox::Text));
 
-        // Render the post button
-        self.post_button.render(
-            f,
-            button_area,
-            matches!(self.selected, SelectedBox::PostButton),
-        );
+        // Render the post button, or the question of whether to leave in its place
+        if self.confirm_leave {
+            Self::render_confirm(f, button_area);
+        } else {
+            self.post_button.render(
+                f,
+                button_area,
+                matches!(self.selected, SelectedBox::PostButton),
+            );
+        }
+    }
+
+    /// Asks whether to leave with something written
+    fn render_confirm<B: Backend>(f: &mut Frame<B>, area: Rect) {
+        let question = Paragraph::new(Spans::from(Span::styled(
+            "Leave this post? y: keep it as a draft, x: discard it, n: keep writing",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .alignment(tui::layout::Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(true, true))
+                .border_type(tui::widgets::BorderType::Double),
+        )
+        .wrap(Wrap { trim: false });
+
+        f.render_widget(question, area);
+    }
+
+    /// Renders the post the way it will look once it is posted
+    fn render_preview<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        author: &str,
+        dark_mode: bool,
+    ) {
+        let options = TextOptions {
+            raw: false,
+            dark_mode,
+            names: self.text_box.mentions.names(),
+        };
+        let (title, body) = self.text();
+        let tags = &self.tags_box.tags;
+        let text = viewing_post::post_text(title, author, tags, body, options, &mut Vec::new());
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(
+                matches!(self.preview_mode, PreviewMode::Preview),
+                false,
+            ));
+
+        let inner = block.inner(area);
+        self.preview_scroll
+            .update(wrapped_height(&text, inner.width), inner.height);
+
+        let preview = Paragraph::new(text)
+            .block(block.title(self.preview_scroll.title("Preview (v to switch layout)")))
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: false })
+            .scroll((self.preview_scroll.offset(), 0));
+
+        f.render_widget(preview, area);
     }
 }
 
 struct TitleBox {
-    text: String,
+    editor: Editor,
     locked: bool,
 }
 
 impl TitleBox {
     pub fn new() -> Self {
+        let mut editor = Editor::single_line();
+        editor.set_text("New Post");
+
         Self {
-            text: String::from("New Post"),
+            editor,
             locked: false,
         }
     }
 
     pub fn clear(&mut self) {
-        self.text = String::from("New Post");
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
+        self.editor.set_text("New Post");
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // While locked, the editing keys change the title
+        if app.create_frame.title_box.locked && app.create_frame.title_box.editor.handle_key(key) {
+            return;
+        }
+
         match key.code {
             KeyCode::Down => {
                 if !app.create_frame.title_box.locked {
-                    app.create_frame.selected = SelectedBox::Text;
+                    app.create_frame.selected = SelectedBox::Tags;
                 }
             }
+            KeyCode::Right if !app.create_frame.title_box.locked => {
+                app.create_frame.selected = SelectedBox::Community;
+            }
             KeyCode::Enter => {
                 app.create_frame.title_box.locked = true;
                 app.create_frame.exitable = false;
+                app.quittable = false;
             }
             KeyCode::Esc => {
                 app.create_frame.title_box.locked = false;
                 app.create_frame.exitable = true;
-            }
-            KeyCode::Backspace => {
-                if app.create_frame.title_box.locked {
-                    app.create_frame.title_box.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.create_frame.title_box.locked {
-                    app.create_frame.title_box.add_char(c);
-                }
+                app.quittable = true;
             }
             _ => {}
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
         let title_style = get_border_style(is_selected, self.locked);
 
-        let mut text = vec![Span::styled(
-            self.text.clone(),
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(title_style)
+            .title(counter_title("Title", self.editor.text(), MAX_TITLE_LENGTH));
+
+        self.editor.render(
+            f,
+            area,
+            block,
             Style::default()
                 .fg(Color::White)
                 .add_modifier(Modifier::BOLD),
-        )];
+            self.locked,
+        );
+    }
+}
 
-        if self.locked {
-            let cursor = Span::styled("█", Style::default().fg(Color::White));
+/// Picks which community the post is made in, if any
+struct CommunityBox {
+    /// The id and name of every community
+    communities: Vec<(i32, String)>,
+    /// The index of the picked community, or `None` to not post in a community
+    picked: Option<usize>,
+    locked: bool,
+}
 
-            text.push(cursor);
+impl CommunityBox {
+    pub fn new() -> Self {
+        Self {
+            communities: Vec::new(),
+            picked: None,
+            locked: false,
         }
+    }
 
-        let title = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(title_style)
-                    .title("Title"),
-            )
-            .wrap(Wrap { trim: false });
+    /// The id of the picked community
+    pub fn picked(&self) -> Option<i32> {
+        self.communities.get(self.picked?).map(|(id, _)| *id)
+    }
+
+    /// Picks the community with the given id, or no community if it can't be found
+    pub fn pick(&mut self, community_id: Option<i32>) {
+        self.picked = community_id.and_then(|community_id| {
+            self.communities
+                .iter()
+                .position(|(id, _)| *id == community_id)
+        });
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let community_box = &mut app.create_frame.community_box;
+        let len = community_box.communities.len();
+
+        match key.code {
+            // While locked, the arrow keys go through the communities, starting from no community
+            KeyCode::Down | KeyCode::Char('j') if community_box.locked => {
+                community_box.picked = match community_box.picked {
+                    None if len > 0 => Some(0),
+                    Some(i) if i + 1 < len => Some(i + 1),
+                    picked => picked,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') if community_box.locked => {
+                community_box.picked = match community_box.picked {
+                    Some(0) | None => None,
+                    Some(i) => Some(i - 1),
+                };
+            }
+            KeyCode::Enter | KeyCode::Esc if community_box.locked => {
+                community_box.locked = false;
+                app.create_frame.exitable = true;
+                app.quittable = true;
+            }
+            KeyCode::Enter => {
+                community_box.locked = true;
+                app.create_frame.exitable = false;
+                app.quittable = false;
+            }
+            KeyCode::Left => {
+                app.create_frame.selected = SelectedBox::Title;
+            }
+            KeyCode::Down => {
+                app.create_frame.selected = SelectedBox::Tags;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.locked);
+
+        let name = match self.picked.and_then(|i| self.communities.get(i)) {
+            Some((_, name)) => Span::styled(
+                name.clone(),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None => Span::styled("None", Style::default().fg(Color::Gray)),
+        };
+
+        let mut text = vec![name];
+        if self.locked {
+            text.push(Span::styled(" (Up/Down)", Style::default().fg(Color::Gray)));
+        }
+
+        let community = Paragraph::new(Spans::from(text)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title("Community"),
+        );
 
-        f.render_widget(title, area);
+        f.render_widget(community, area);
     }
 }
 
-struct TextBox {
-    text: String,
+/// The tags that the post will be made with
+struct TagsBox {
+    input: TagInput,
+    tags: Vec<String>,
     locked: bool,
+    /// Why the last tag couldn't be added
+    error: Option<String>,
 }
 
-impl TextBox {
+impl TagsBox {
     pub fn new() -> Self {
         Self {
-            text: String::new(),
+            input: TagInput::new(),
+            tags: Vec::new(),
             locked: false,
+            error: None,
         }
     }
 
     pub fn clear(&mut self) {
-        self.text.clear();
+        self.input.editor.clear();
+        self.tags.clear();
+        self.error = None;
     }
 
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
+    /// Adds the tag that has been typed
+    fn add_tag(&mut self) {
+        let tag = match tags::normalize_tag(self.input.editor.text()) {
+            Ok(tag) => tag,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+
+        if self.tags.contains(&tag) {
+            self.error = Some(format!("The post is already tagged {}", tag));
+        } else if self.tags.len() >= MAX_TAGS {
+            self.error = Some(format!("Posts can't have over {} tags", MAX_TAGS));
+        } else {
+            self.tags.push(tag);
+            self.input.editor.clear();
+        }
     }
 
-    pub fn del_char(&mut self) {
-        self.text.pop();
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let tags_box = &mut app.create_frame.tags_box;
+        tags_box.error = None;
+
+        if tags_box.locked {
+            match key.code {
+                // Enter adds the tag, or finishes once nothing is typed
+                KeyCode::Enter if !tags_box.input.editor.text().trim().is_empty() => {
+                    tags_box.add_tag();
+                }
+                KeyCode::Enter | KeyCode::Esc => {
+                    tags_box.input.editor.clear();
+                    tags_box.locked = false;
+                    app.create_frame.exitable = true;
+                    app.quittable = true;
+                }
+                // Backspace with nothing typed removes the last tag
+                KeyCode::Backspace if tags_box.input.editor.text().is_empty() => {
+                    tags_box.tags.pop();
+                }
+                _ => {
+                    tags_box.input.handle_key(key);
+                }
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Enter => {
+                tags_box.input.load();
+                tags_box.locked = true;
+                app.create_frame.exitable = false;
+                app.quittable = false;
+            }
+            KeyCode::Up => {
+                app.create_frame.selected = SelectedBox::Title;
+            }
+            KeyCode::Down => {
+                app.create_frame.selected = SelectedBox::Text;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.locked);
+
+        let mut text = tags::chips(&self.tags);
+        if !text.is_empty() {
+            text.push(Span::raw(" "));
+        }
+        text.extend(
+            self.input.line(
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                self.locked,
+            ),
+        );
+
+        let title = match &self.error {
+            Some(error) => Spans::from(vec![
+                Span::raw("Tags "),
+                Span::styled(
+                    error.clone(),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            None if self.locked => Spans::from(format!(
+                "Tags ({}/{}, Enter to add, Backspace to remove)",
+                self.tags.len(),
+                MAX_TAGS
+            )),
+            None => Spans::from(format!("Tags ({}/{})", self.tags.len(), MAX_TAGS)),
+        };
+
+        let tags = Paragraph::new(Spans::from(text)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(title),
+        );
+
+        f.render_widget(tags, area);
+    }
+}
+
+struct TextBox {
+    editor: Editor,
+    locked: bool,
+    /// Suggests names while a mention is typed
+    mentions: Mentions,
+}
+
+impl TextBox {
+    pub fn new() -> Self {
+        Self {
+            editor: Editor::multi_line(),
+            locked: false,
+            mentions: Mentions::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.editor.clear();
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // While locked, the editing keys change the text, unless a name is being picked
+        let text_box = &mut app.create_frame.text_box;
+        if text_box.locked {
+            if text_box.mentions.handle_key(&mut text_box.editor, key) {
+                return;
+            }
+            if text_box.editor.handle_key(key) {
+                text_box.mentions.update(&text_box.editor);
+                return;
+            }
+        }
+
         match key.code {
             KeyCode::Up => {
                 if !app.create_frame.text_box.locked {
-                    app.create_frame.selected = SelectedBox::Title;
+                    app.create_frame.selected = SelectedBox::Tags;
                 }
             }
             KeyCode::Down => {
@@ -208,71 +769,64 @@ impl TextBox {
                 }
             }
             KeyCode::Enter => {
-                if !app.create_frame.text_box.locked {
-                    app.create_frame.text_box.locked = true;
-                    app.create_frame.exitable = false;
-                } else {
-                    app.create_frame.text_box.add_char('\n');
-                }
+                app.create_frame.text_box.locked = true;
+                app.create_frame.exitable = false;
+                app.quittable = false;
             }
             KeyCode::Esc => {
                 app.create_frame.text_box.locked = false;
                 app.create_frame.exitable = true;
-            }
-            KeyCode::Backspace => {
-                if app.create_frame.text_box.locked {
-                    app.create_frame.text_box.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.create_frame.text_box.locked {
-                    app.create_frame.text_box.add_char(c);
-                }
+                app.quittable = true;
             }
             _ => {}
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
         let text_style = get_border_style(is_selected, self.locked);
 
-        let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-        let mut spans = Vec::new();
-
-        if !self.text.is_empty() {
-            for line in self.text.split('\n') {
-                spans.push(Spans::from(Span::raw(String::from(line))));
-            }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(text_style)
+            .title(counter_title(
+                "Contents",
+                self.editor.text(),
+                MAX_TEXT_LENGTH,
+            ));
 
-            let mut last = spans.pop().unwrap();
-            last.0.push(cursor);
+        self.editor
+            .render(f, area, block, Style::default(), self.locked && is_selected);
 
-            spans.push(last);
-        } else {
-            if self.locked && is_selected {
-                spans.push(Spans::from(cursor));
-            }
+        if self.locked && is_selected {
+            self.mentions.render(f, area);
         }
-
-        let text = Paragraph::new(spans)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(text_style)
-                    .title("Contents"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(text, area);
     }
 }
 
-struct PostButton {}
+/// A box title followed by how many characters have been written out of the most allowed, which
+/// turns red once there are too many
+fn counter_title(title: &str, text: &str, max: usize) -> Spans<'static> {
+    let count = text.trim().chars().count();
+    let style = if count > max {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+
+    Spans::from(vec![
+        Span::raw(format!("{} ", title)),
+        Span::styled(format!("({}/{})", count, max), style),
+    ])
+}
+
+struct PostButton {
+    /// Why the post couldn't be made, shown until the next key is pressed
+    error: Option<String>,
+}
 
 impl PostButton {
     pub fn new() -> Self {
-        Self {}
+        Self { error: None }
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
@@ -281,14 +835,30 @@ impl PostButton {
                 app.create_frame.selected = SelectedBox::Text;
             }
             KeyCode::Enter => {
+                let (title, text) = match validate_post(
+                    app.create_frame.title_box.editor.text(),
+                    app.create_frame.text_box.editor.text(),
+                ) {
+                    Ok(post) => post,
+                    Err(e) => {
+                        app.create_frame.post_button.error = Some(e);
+                        return;
+                    }
+                };
+
+                let community_id = app.create_frame.community_box.picked();
+                let tags = &app.create_frame.tags_box.tags;
+                if let Err(e) = app.submit_post(&title, &text, community_id, tags) {
+                    app.create_frame.post_button.error = Some(format!("Couldn't make post: {}", e));
+                    return;
+                }
+
                 app.create_frame.selected = SelectedBox::Title;
-                app.submit_post(
-                    &app.create_frame.title_box.text,
-                    &app.create_frame.text_box.text,
-                );
+                let _ = app.create_frame.draft.discard();
                 app.reload_posts();
                 app.set_view(AppView::Homepage);
                 app.create_frame.title_box.clear();
+                app.create_frame.tags_box.clear();
                 app.create_frame.text_box.clear();
             }
             _ => {}
@@ -298,7 +868,15 @@ impl PostButton {
     pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
         let border_style = get_border_style(is_selected, false);
 
-        let button = Paragraph::new(Spans::from(Span::raw("Make Post")))
+        let label = match &self.error {
+            Some(error) => Span::styled(
+                error.clone(),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            None => Span::raw("Make Post"),
+        };
+
+        let button = Paragraph::new(Spans::from(label))
             .alignment(tui::layout::Alignment::Center)
             .block(
                 Block::default()
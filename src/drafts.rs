@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, TryLockError};
+use std::time::SystemTime;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use st_read::models::{Draft, NewDraft};
+use st_read::util::time_ago;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView, SelectedFrame};
+use crate::list::StatefulList;
+
+/// What a draft is being written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DraftOf {
+    NewPost,
+    /// A reply to the post with this id
+    Reply(i32),
+    /// A message to the user with this id
+    Message(i32),
+}
+
+impl DraftOf {
+    pub fn of(draft: &Draft) -> Self {
+        match (draft.post_id, draft.recipient_id) {
+            (Some(post_id), _) => DraftOf::Reply(post_id),
+            (None, Some(recipient_id)) => DraftOf::Message(recipient_id),
+            (None, None) => DraftOf::NewPost,
+        }
+    }
+
+    fn post_id(self) -> Option<i32> {
+        match self {
+            DraftOf::Reply(post_id) => Some(post_id),
+            _ => None,
+        }
+    }
+
+    fn recipient_id(self) -> Option<i32> {
+        match self {
+            DraftOf::Message(recipient_id) => Some(recipient_id),
+            _ => None,
+        }
+    }
+}
+
+/// Copies of the text being written by the user with this id, as of the last tick. The panic
+/// hook saves these rather than reaching into the app, which the UI thread may still be using
+static UNSAVED: Mutex<Option<(i32, Vec<DraftCopy>)>> = Mutex::new(None);
+
+/// Keeps copies of the text being written, for `save_unsaved` to save if the app panics
+pub fn keep_unsaved(user_id: i32, copies: Vec<DraftCopy>) {
+    let unsaved = (user_id != 0).then_some((user_id, copies));
+    match UNSAVED.lock() {
+        Ok(mut kept) => *kept = unsaved,
+        Err(poisoned) => *poisoned.into_inner() = unsaved,
+    }
+}
+
+/// Saves the copies kept by `keep_unsaved` as drafts. This is called from the panic hook, so it
+/// gives up rather than waiting if the copies are being replaced
+pub fn save_unsaved() {
+    let kept = match UNSAVED.try_lock() {
+        Ok(mut kept) => kept.take(),
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().take(),
+        Err(TryLockError::WouldBlock) => None,
+    };
+
+    if let Some((user_id, copies)) = kept {
+        for copy in copies {
+            let _ = copy.save(user_id);
+        }
+    }
+}
+
+/// A copy of what is written in a compose box, which can be saved without the box
+#[derive(Clone)]
+pub struct DraftCopy {
+    draft: Autosave,
+    of: DraftOf,
+    title: String,
+    text: String,
+}
+
+impl DraftCopy {
+    fn save(mut self, user_id: i32) -> QueryResult<()> {
+        self.draft.save(user_id, self.of, &self.title, &self.text)
+    }
+}
+
+/// Keeps the text being written in a compose box saved as a draft, so that it isn't lost if the
+/// app quits or panics before it is posted
+#[derive(Clone)]
+pub struct Autosave {
+    /// The draft being written to, or `None` until the text is first saved
+    draft_id: Option<i32>,
+    /// The title and text as they were last saved, so that unchanged text isn't saved again
+    saved: (String, String),
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Self {
+            draft_id: None,
+            saved: (String::new(), String::new()),
+        }
+    }
+
+    /// Continues writing to a draft that was saved before
+    pub fn resume(&mut self, draft: &Draft) {
+        self.draft_id = Some(draft.draft_id);
+        self.saved = (draft.title.clone(), draft.text.clone());
+    }
+
+    /// Copies the title and text, to save later into the same draft
+    pub fn copy(&self, of: DraftOf, title: &str, text: &str) -> DraftCopy {
+        DraftCopy {
+            draft: self.clone(),
+            of,
+            title: title.to_owned(),
+            text: text.to_owned(),
+        }
+    }
+
+    /// Returns true if this is writing to the given draft
+    pub fn is(&self, draft_id: i32) -> bool {
+        self.draft_id == Some(draft_id)
+    }
+
+    /// Saves the title and text if they have changed since they were last saved
+    pub fn save(&mut self, user_id: i32, of: DraftOf, title: &str, text: &str) -> QueryResult<()> {
+        use st_read::schema::draft::dsl;
+
+        if self.saved.0 == title && self.saved.1 == text {
+            return Ok(());
+        }
+
+        let connection = st_read::establish_connection();
+
+        // The draft may have been discarded from the drafts list since it was last saved
+        let updated = match self.draft_id {
+            Some(draft_id) => diesel::update(dsl::draft.find(draft_id))
+                .set((
+                    dsl::title.eq(title),
+                    dsl::text.eq(text),
+                    dsl::last_saved.eq(SystemTime::now()),
+                ))
+                .execute(&connection)?,
+            None => 0,
+        };
+
+        if updated == 0 {
+            let draft = NewDraft {
+                user_id,
+                post_id: of.post_id(),
+                recipient_id: of.recipient_id(),
+                title: title.to_owned(),
+                text: text.to_owned(),
+            };
+
+            self.draft_id = Some(
+                draft
+                    .insert_into(dsl::draft)
+                    .returning(dsl::draft_id)
+                    .get_result(&connection)?,
+            );
+        }
+
+        self.saved = (title.to_owned(), text.to_owned());
+
+        Ok(())
+    }
+
+    /// Deletes the draft, once it has been posted or its text has been cleared
+    pub fn discard(&mut self) -> QueryResult<()> {
+        let draft_id = self.draft_id.take();
+        self.saved = (String::new(), String::new());
+
+        match draft_id {
+            Some(draft_id) => delete_draft(draft_id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Loads the latest draft of a new post, a reply, or a message
+pub fn latest_draft(user_id: i32, of: DraftOf) -> QueryResult<Option<Draft>> {
+    use st_read::schema::draft::dsl;
+    let connection = st_read::establish_connection();
+
+    let query = dsl::draft
+        .filter(dsl::user_id.eq(user_id))
+        .order(dsl::last_saved.desc())
+        .into_boxed();
+
+    let query = match of {
+        DraftOf::NewPost => query
+            .filter(dsl::post_id.is_null())
+            .filter(dsl::recipient_id.is_null()),
+        DraftOf::Reply(post_id) => query.filter(dsl::post_id.eq(post_id)),
+        DraftOf::Message(recipient_id) => query.filter(dsl::recipient_id.eq(recipient_id)),
+    };
+
+    query.first(&connection).optional()
+}
+
+pub fn delete_draft(draft_id: i32) -> QueryResult<()> {
+    use st_read::schema::draft::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::delete(dsl::draft.find(draft_id))
+        .execute(&connection)
+        .map(|_| ())
+}
+
+/// A saved draft, along with the title of the post it replies to or the name of who the message
+/// is to
+struct DraftItem {
+    draft: Draft,
+    reply_to: Option<String>,
+    message_to: Option<String>,
+}
+
+/// Lists the user's saved drafts so that they can be resumed or discarded
+pub struct DraftsFrame {
+    drafts: StatefulList<DraftItem>,
+}
+
+impl DraftsFrame {
+    pub fn new() -> Self {
+        Self {
+            drafts: StatefulList::highlighted(Vec::new()),
+        }
+    }
+
+    /// Loads the user's drafts, most recently saved first
+    pub fn load(&mut self, user_id: i32) {
+        use st_read::schema::draft::dsl as draft_dsl;
+        use st_read::schema::post::dsl as post_dsl;
+        use st_read::schema::users::dsl as users_dsl;
+        let connection = st_read::establish_connection();
+
+        let drafts: Vec<(Draft, Option<String>)> = draft_dsl::draft
+            .left_join(post_dsl::post)
+            .filter(draft_dsl::user_id.eq(user_id))
+            .order(draft_dsl::last_saved.desc())
+            .select((draft_dsl::draft::all_columns(), post_dsl::title.nullable()))
+            .load(&connection)
+            .unwrap_or_default();
+
+        let recipient_ids: Vec<i32> = drafts
+            .iter()
+            .filter_map(|(draft, _)| draft.recipient_id)
+            .collect();
+        let names: HashMap<i32, String> = users_dsl::users
+            .filter(users_dsl::user_id.eq_any(&recipient_ids))
+            .select((users_dsl::user_id, users_dsl::name))
+            .load(&connection)
+            .map(|names: Vec<(i32, String)>| names.into_iter().collect())
+            .unwrap_or_default();
+
+        let drafts = drafts
+            .into_iter()
+            .map(|(draft, reply_to)| {
+                let message_to = draft.recipient_id.and_then(|id| names.get(&id).cloned());
+                DraftItem {
+                    draft,
+                    reply_to,
+                    message_to,
+                }
+            })
+            .collect();
+
+        self.drafts = StatefulList::highlighted(drafts);
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !app.drafts_frame.drafts.items.is_empty() => {
+                app.drafts_frame.drafts.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !app.drafts_frame.drafts.items.is_empty() => {
+                app.drafts_frame.drafts.previous();
+            }
+            KeyCode::Enter => {
+                let item = match app.drafts_frame.drafts.selected_item() {
+                    Some(item) => item,
+                    None => return,
+                };
+
+                match DraftOf::of(&item.draft) {
+                    DraftOf::Reply(post_id) => {
+                        app.set_view(AppView::Homepage);
+                        app.view_post(post_id);
+                        app.selected_frame = SelectedFrame::ViewPost;
+                    }
+                    DraftOf::Message(recipient_id) => {
+                        app.show_messages(Some(recipient_id));
+                    }
+                    DraftOf::NewPost => {
+                        app.create_frame.resume_draft(&item.draft);
+                        app.set_view(AppView::CreatePost);
+                    }
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                let i = match app.drafts_frame.drafts.selected() {
+                    Some(i) if i < app.drafts_frame.drafts.items.len() => i,
+                    _ => return,
+                };
+
+                let draft_id = app.drafts_frame.drafts.items[i].draft.draft_id;
+                if delete_draft(draft_id).is_err() {
+                    return;
+                }
+
+                // Don't let the text that is still open save the draft again
+                app.create_frame.forget_draft(draft_id);
+                app.viewing_frame.forget_draft(draft_id);
+                app.messages_frame.forget_draft(draft_id);
+
+                let drafts = &mut app.drafts_frame.drafts;
+                drafts.items.remove(i);
+                if drafts.items.is_empty() {
+                    drafts.unselect();
+                } else if i >= drafts.items.len() {
+                    drafts.select(Some(drafts.items.len() - 1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let deselected_style = Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .fg(Color::Gray);
+
+        let mut text = Vec::new();
+
+        if self.drafts.items.is_empty() {
+            text.push(Spans::from(Span::styled(
+                format!("{}No drafts", self.drafts.placeholder()),
+                deselected_style,
+            )));
+        }
+
+        for (i, item) in self.drafts.items.iter().enumerate() {
+            let heading = match (&item.reply_to, &item.message_to) {
+                (Some(title), _) => format!("Reply to \"{}\"", title),
+                (None, Some(name)) => format!("Message to {}", name),
+                _ if item.draft.title.trim().is_empty() => String::from("Untitled post"),
+                _ => item.draft.title.clone(),
+            };
+
+            let mut preview: String = item
+                .draft
+                .text
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("")
+                .chars()
+                .take(40)
+                .collect();
+            preview.push_str(" ...");
+
+            let lines = [
+                heading,
+                preview,
+                format!("saved {}", time_ago(item.draft.last_saved)),
+            ];
+            self.drafts.push_rows(&mut text, i, lines, deselected_style);
+        }
+
+        let drafts = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(true, false))
+                .title("Drafts (Enter to resume, x to discard, Esc to go back)"),
+        );
+
+        f.render_widget(drafts, area);
+    }
+}
@@ -0,0 +1,587 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Paragraph, Wrap};
+use tui::Frame;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::scroll::{wrapped_height, ScrollState};
+
+/// The most edits that can be undone
+const MAX_UNDO: usize = 1000;
+
+/// The kind of change made by an edit. Edits of the same kind in a row are undone together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+/// The text and cursor before an edit, so that it can be undone
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+}
+
+/// The text editing shared by every box that can be typed into.
+///
+/// The cursor is kept as a byte index into the text, and always moves over whole graphemes so
+/// that multi-byte characters are never split
+#[derive(Debug, Clone)]
+pub struct Editor {
+    text: String,
+    cursor: usize,
+    multi_line: bool,
+    /// Shows this character in place of every character of the text, for passwords
+    mask: Option<char>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+    /// The column to go back to when moving up or down through lines of different lengths
+    goal_column: Option<usize>,
+    scroll: ScrollState,
+}
+
+impl Editor {
+    /// An editor for a single line of text, where Enter is left to the box it is in
+    pub fn single_line() -> Self {
+        Self::new(false, None)
+    }
+
+    /// An editor where Enter starts a new line
+    pub fn multi_line() -> Self {
+        Self::new(true, None)
+    }
+
+    /// A single line editor that hides what is typed. Nothing is kept for undo, so that no copies
+    /// of the password are left in memory
+    pub fn password() -> Self {
+        Self::new(false, Some('*'))
+    }
+
+    fn new(multi_line: bool, mask: Option<char>) -> Self {
+        Self {
+            text: String::new(),
+            cursor: 0,
+            multi_line,
+            mask,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            goal_column: None,
+            scroll: ScrollState::new(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, moving the cursor to the end and forgetting the undo history
+    pub fn set_text(&mut self, text: impl AsRef<str>) {
+        self.clear();
+        self.text.push_str(text.as_ref());
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        // Overwritten rather than just cleared, in case it was a password
+        zeroize::Zeroize::zeroize(&mut self.text);
+        self.cursor = 0;
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+        self.goal_column = None;
+        self.scroll.reset();
+    }
+
+    /// Where the cursor is, in bytes from the start of the text
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The text from the start of the line up to the cursor
+    pub fn before_cursor(&self) -> &str {
+        &self.text[self.line_start(self.cursor)..self.cursor]
+    }
+
+    /// Replaces the `len` bytes before the cursor with `text`, leaving the cursor after it. This
+    /// is undone on its own
+    pub fn replace_before_cursor(&mut self, len: usize, text: &str) {
+        let start = self.cursor.saturating_sub(len);
+
+        self.checkpoint(EditKind::Other);
+        self.text.replace_range(start..self.cursor, text);
+        self.move_to(start + text.len());
+    }
+
+    /// Takes the text out of the editor, leaving it empty
+    pub fn take(&mut self) -> String {
+        let text = std::mem::take(&mut self.text);
+        self.clear();
+        text
+    }
+
+    /// Saves the text as it is before an edit. Edits of the same kind in a row share one snapshot
+    fn checkpoint(&mut self, kind: EditKind) {
+        self.goal_column = None;
+
+        if self.mask.is_some() || (self.last_edit == Some(kind) && kind != EditKind::Other) {
+            return;
+        }
+
+        self.last_edit = Some(kind);
+        self.redo.clear();
+        self.undo.push(Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor,
+        });
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Ends the current run of edits, so that the next edit is undone on its own
+    fn moved(&mut self) {
+        self.last_edit = None;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if c == '\n' && !self.multi_line {
+            return;
+        }
+
+        // Each word typed is undone on its own
+        if c.is_whitespace() {
+            self.moved();
+        }
+
+        self.checkpoint(EditKind::Insert);
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Deletes the character before the cursor
+    pub fn backspace(&mut self) {
+        if let Some(start) = self.prev_boundary(self.cursor) {
+            self.checkpoint(EditKind::Delete);
+            self.delete_range(start, self.cursor);
+        }
+    }
+
+    /// Deletes the character under the cursor
+    pub fn delete(&mut self) {
+        if let Some(end) = self.next_boundary(self.cursor) {
+            self.checkpoint(EditKind::Delete);
+            self.delete_range(self.cursor, end);
+        }
+    }
+
+    /// Deletes back to the start of the word before the cursor
+    pub fn delete_word(&mut self) {
+        let start = self.word_start(self.cursor);
+
+        if start < self.cursor {
+            self.moved();
+            self.checkpoint(EditKind::Other);
+            self.delete_range(start, self.cursor);
+        }
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) {
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            self.redo.push(Snapshot {
+                text: std::mem::replace(&mut self.text, snapshot.text),
+                cursor: self.cursor,
+            });
+            self.cursor = snapshot.cursor;
+            self.moved();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            self.undo.push(Snapshot {
+                text: std::mem::replace(&mut self.text, snapshot.text),
+                cursor: self.cursor,
+            });
+            self.cursor = snapshot.cursor;
+            self.moved();
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.move_to(self.prev_boundary(self.cursor).unwrap_or(self.cursor));
+    }
+
+    pub fn right(&mut self) {
+        self.move_to(self.next_boundary(self.cursor).unwrap_or(self.cursor));
+    }
+
+    pub fn word_left(&mut self) {
+        self.move_to(self.word_start(self.cursor));
+    }
+
+    pub fn word_right(&mut self) {
+        // Skip to the end of the next word
+        let rest = &self.text[self.cursor..];
+        let end = rest
+            .unicode_word_indices()
+            .next()
+            .map(|(i, word)| self.cursor + i + word.len())
+            .unwrap_or(self.text.len());
+
+        self.move_to(end);
+    }
+
+    /// Moves to the start of the line
+    pub fn home(&mut self) {
+        self.move_to(self.line_start(self.cursor));
+    }
+
+    /// Moves to the end of the line
+    pub fn end(&mut self) {
+        self.move_to(self.line_end(self.cursor));
+    }
+
+    pub fn up(&mut self) {
+        let start = self.line_start(self.cursor);
+
+        if start > 0 {
+            let column = self.goal_column.unwrap_or_else(|| self.column());
+            let previous = self.line_start(start - 1);
+
+            self.move_to(self.at_column(previous, column));
+            self.goal_column = Some(column);
+        }
+    }
+
+    pub fn down(&mut self) {
+        let end = self.line_end(self.cursor);
+
+        if end < self.text.len() {
+            let column = self.goal_column.unwrap_or_else(|| self.column());
+
+            self.move_to(self.at_column(end + 1, column));
+            self.goal_column = Some(column);
+        }
+    }
+
+    fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor;
+        self.goal_column = None;
+        self.moved();
+    }
+
+    fn prev_boundary(&self, i: usize) -> Option<usize> {
+        self.text[..i]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(start, _)| start)
+    }
+
+    fn next_boundary(&self, i: usize) -> Option<usize> {
+        self.text[i..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| i + grapheme.len())
+    }
+
+    fn word_start(&self, i: usize) -> usize {
+        self.text[..i]
+            .unicode_word_indices()
+            .next_back()
+            .map(|(start, _)| start)
+            .unwrap_or(0)
+    }
+
+    fn line_start(&self, i: usize) -> usize {
+        self.text[..i].rfind('\n').map(|n| n + 1).unwrap_or(0)
+    }
+
+    fn line_end(&self, i: usize) -> usize {
+        self.text[i..]
+            .find('\n')
+            .map(|n| i + n)
+            .unwrap_or(self.text.len())
+    }
+
+    /// The number of characters between the start of the line and the cursor
+    fn column(&self) -> usize {
+        self.text[self.line_start(self.cursor)..self.cursor]
+            .graphemes(true)
+            .count()
+    }
+
+    /// The index of a column in the line starting at `start`, or the end of the line if it is
+    /// shorter than that
+    fn at_column(&self, start: usize, column: usize) -> usize {
+        let line = &self.text[start..self.line_end(start)];
+
+        line.grapheme_indices(true)
+            .nth(column)
+            .map(|(i, _)| start + i)
+            .unwrap_or(start + line.len())
+    }
+
+    /// Edits the text in response to a key. Returns false if the key isn't used for editing, so
+    /// that the box can handle it instead
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Char('z') if ctrl => self.undo(),
+            KeyCode::Char('y') if ctrl => self.redo(),
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Enter if self.multi_line => self.insert_char('\n'),
+            KeyCode::Backspace if ctrl || alt => self.delete_word(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left if ctrl || alt => self.word_left(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right if ctrl || alt => self.word_right(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home if ctrl => self.move_to(0),
+            KeyCode::Home => self.home(),
+            KeyCode::End if ctrl => self.move_to(self.text.len()),
+            KeyCode::End => self.end(),
+            KeyCode::Up if self.multi_line => self.up(),
+            KeyCode::Down if self.multi_line => self.down(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// The lines of text styled for drawing, with the cursor shown if `show_cursor` is set. The
+    /// character under the cursor is reversed, or a block is drawn past the end of the line.
+    ///
+    /// Also returns the line the cursor is on, and the text of that line up to and including the
+    /// cursor
+    fn styled_lines(
+        &self,
+        style: Style,
+        show_cursor: bool,
+    ) -> (Vec<Spans<'static>>, usize, String) {
+        let mut lines = Vec::new();
+        let mut cursor_line = 0;
+        let mut cursor_prefix = String::new();
+        let mut start = 0;
+
+        for (i, line) in self.text.split('\n').enumerate() {
+            let end = start + line.len();
+            let display = |s: &str| match self.mask {
+                Some(mask) => s.graphemes(true).map(|_| mask).collect(),
+                None => String::from(s),
+            };
+
+            if (start..=end).contains(&self.cursor) {
+                let before = display(&line[..self.cursor - start]);
+                let after = &line[self.cursor - start..];
+
+                cursor_line = i;
+                let mut spans = vec![Span::styled(before.clone(), style)];
+
+                if show_cursor {
+                    match after.graphemes(true).next() {
+                        Some(under) => {
+                            let rest = display(&after[under.len()..]);
+                            let under = display(under);
+
+                            cursor_prefix = format!("{}{}", before, under);
+                            spans.push(Span::styled(under, style.add_modifier(Modifier::REVERSED)));
+                            spans.push(Span::styled(rest, style));
+                        }
+                        None => {
+                            cursor_prefix = format!("{}█", before);
+                            spans.push(Span::styled("█", Style::default().fg(Color::White)));
+                        }
+                    }
+                } else {
+                    cursor_prefix = before;
+                    spans.push(Span::styled(display(after), style));
+                }
+
+                lines.push(Spans::from(spans));
+            } else {
+                lines.push(Spans::from(Span::styled(display(line), style)));
+            }
+
+            start = end + 1;
+        }
+
+        (lines, cursor_line, cursor_prefix)
+    }
+
+    /// The lines of text styled for drawing in a custom widget
+    pub fn lines(&self, style: Style, show_cursor: bool) -> Vec<Spans<'static>> {
+        self.styled_lines(style, show_cursor).0
+    }
+
+    /// Draws the text inside of a block, wrapping it and scrolling to keep the cursor in view
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        block: Block,
+        style: Style,
+        show_cursor: bool,
+    ) {
+        let (lines, cursor_line, cursor_prefix) = self.styled_lines(style, show_cursor);
+
+        let inner = block.inner(area);
+        self.scroll
+            .update(wrapped_height(&lines, inner.width), inner.height);
+
+        // The row the cursor is on, which may be partway through a wrapped line
+        let row = wrapped_height(&lines[..cursor_line], inner.width)
+            + wrapped_height(&[Spans::from(cursor_prefix)], inner.width).saturating_sub(1);
+        self.scroll.show_line(row);
+
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll.offset(), 0));
+
+        f.render_widget(paragraph, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(editor: &mut Editor, text: &str) {
+        for c in text.chars() {
+            editor.insert_char(c);
+        }
+    }
+
+    #[test]
+    fn moves_and_deletes_whole_graphemes() {
+        // An "e" with a combining accent, and a thumbs up with a skin tone
+        let mut editor = Editor::single_line();
+        editor.set_text("e\u{301}\u{1F44D}\u{1F3FD}x");
+
+        editor.left();
+        assert_eq!(editor.before_cursor(), "e\u{301}\u{1F44D}\u{1F3FD}");
+        editor.left();
+        assert_eq!(editor.before_cursor(), "e\u{301}");
+        editor.left();
+        assert_eq!(editor.cursor(), 0);
+        editor.left();
+        assert_eq!(editor.cursor(), 0);
+
+        editor.right();
+        editor.delete();
+        assert_eq!(editor.text(), "e\u{301}x");
+        editor.backspace();
+        assert_eq!(editor.text(), "x");
+    }
+
+    #[test]
+    fn undo_and_redo_stop_at_either_end() {
+        let mut editor = Editor::single_line();
+        type_text(&mut editor, "hello world");
+
+        editor.undo();
+        assert_eq!(editor.text(), "hello");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+        editor.undo();
+        assert_eq!(editor.text(), "");
+
+        editor.redo();
+        editor.redo();
+        assert_eq!(editor.text(), "hello world");
+        assert_eq!(editor.cursor(), "hello world".len());
+        editor.redo();
+        assert_eq!(editor.text(), "hello world");
+
+        // A new edit can't be followed by redoing an old one
+        editor.undo();
+        type_text(&mut editor, "!");
+        editor.redo();
+        assert_eq!(editor.text(), "hello!");
+    }
+
+    #[test]
+    fn only_the_latest_edits_are_kept_for_undo() {
+        let mut editor = Editor::single_line();
+        // Every space starts an edit of its own
+        type_text(&mut editor, &" ".repeat(MAX_UNDO + 5));
+
+        for _ in 0..MAX_UNDO + 5 {
+            editor.undo();
+        }
+        assert_eq!(editor.text(), " ".repeat(5));
+    }
+
+    #[test]
+    fn passwords_keep_nothing_to_undo() {
+        let mut editor = Editor::password();
+        type_text(&mut editor, "secret words");
+
+        editor.undo();
+        assert_eq!(editor.text(), "secret words");
+    }
+
+    #[test]
+    fn single_line_editors_ignore_new_lines() {
+        let mut editor = Editor::single_line();
+        type_text(&mut editor, "a\nb");
+        assert_eq!(editor.text(), "ab");
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column_across_shorter_lines() {
+        let mut editor = Editor::multi_line();
+        editor.set_text("abcdef\nab\nabcdef");
+
+        editor.up();
+        assert_eq!(editor.cursor(), "abcdef\nab".len());
+        editor.up();
+        assert_eq!(editor.cursor(), "abcdef".len());
+        editor.down();
+        editor.down();
+        assert_eq!(editor.cursor(), "abcdef\nab\nabcdef".len());
+
+        // Moving sideways picks a new column
+        editor.left();
+        editor.left();
+        editor.up();
+        editor.up();
+        assert_eq!(editor.cursor(), "abcd".len());
+
+        // The top and bottom lines are as far as it goes
+        editor.up();
+        assert_eq!(editor.cursor(), "abcd".len());
+    }
+
+    #[test]
+    fn columns_are_counted_in_graphemes() {
+        let mut editor = Editor::multi_line();
+        editor.set_text("\u{1F44D}\u{1F3FD}e\u{301}x\nabcd");
+
+        editor.up();
+        assert_eq!(editor.cursor(), "\u{1F44D}\u{1F3FD}e\u{301}x".len());
+        editor.home();
+        editor.right();
+        editor.down();
+        assert_eq!(editor.cursor(), "\u{1F44D}\u{1F3FD}e\u{301}x\na".len());
+    }
+}
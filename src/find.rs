@@ -0,0 +1,314 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use regex::{Regex, RegexBuilder};
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App};
+use crate::editor::Editor;
+use crate::scroll::{wrapped_height, wrapped_row};
+
+/// A vim-like "/pattern" search through the text of the open post and its comments.
+///
+/// Each pane that is rendered calls `search` on its lines in order, which highlights the matches
+/// and numbers them so that 'n' and 'N' can step through all of them across every pane
+pub struct FindBar {
+    editor: Editor,
+    pub locked: bool,
+    pattern: Option<Regex>,
+    /// The index of the current match, counting through every pane in the order they are rendered
+    current: usize,
+    /// How many matches have been found by the panes rendered so far this frame
+    counted: usize,
+    /// How many matches there were in total during the last frame
+    total: usize,
+    /// Set when the pane with the current match should scroll to it on the next render
+    jump: bool,
+}
+
+impl FindBar {
+    pub fn new() -> Self {
+        Self {
+            editor: Editor::single_line(),
+            locked: false,
+            pattern: None,
+            current: 0,
+            counted: 0,
+            total: 0,
+            jump: false,
+        }
+    }
+
+    /// Removes the pattern and all of its highlighting
+    pub fn clear(&mut self) {
+        self.editor.clear();
+        self.locked = false;
+        self.pattern = None;
+        self.current = 0;
+        self.total = 0;
+    }
+
+    /// Returns true if a pattern is being typed or highlighted
+    pub fn is_active(&self) -> bool {
+        self.locked || self.pattern.is_some()
+    }
+
+    /// Compiles the typed text as a case insensitive regex. Text that isn't a valid regex is
+    /// searched for literally instead
+    fn submit(&mut self) {
+        self.locked = false;
+        self.current = 0;
+        self.jump = true;
+
+        let text = self.editor.text();
+        self.pattern = if text.is_empty() {
+            None
+        } else {
+            RegexBuilder::new(text)
+                .case_insensitive(true)
+                .build()
+                .or_else(|_| {
+                    RegexBuilder::new(&regex::escape(text))
+                        .case_insensitive(true)
+                        .build()
+                })
+                .ok()
+        };
+    }
+
+    /// Moves to the next match, wrapping around to the first one
+    pub fn next(&mut self) {
+        if self.total > 0 {
+            self.current = (self.current + 1) % self.total;
+            self.jump = true;
+        }
+    }
+
+    /// Moves to the previous match, wrapping around to the last one
+    pub fn previous(&mut self) {
+        if self.total > 0 {
+            self.current = (self.current + self.total - 1) % self.total;
+            self.jump = true;
+        }
+    }
+
+    /// Starts counting matches for a new frame. Must be called before any pane is searched
+    pub fn begin(&mut self) {
+        self.counted = 0;
+    }
+
+    /// Finishes a frame once every pane has been searched
+    pub fn end(&mut self) {
+        self.total = self.counted;
+        self.jump = false;
+
+        if self.current >= self.total {
+            self.current = 0;
+        }
+    }
+
+    /// Highlights the matches in the lines of one pane.
+    ///
+    /// If the pane holds the current match and the view should jump to it, this returns the
+    /// wrapped row it is on so that the pane can scroll there
+    pub fn search(&mut self, lines: &mut [Spans], width: u16) -> Option<u16> {
+        let pattern = self.pattern.as_ref()?;
+
+        let match_style = Style::default().bg(Color::DarkGray).fg(Color::White);
+        let current_style = Style::default()
+            .bg(Color::Yellow)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD);
+
+        let mut row = None;
+
+        for i in 0..lines.len() {
+            let plain: String = lines[i].0.iter().map(|s| s.content.as_ref()).collect();
+
+            let mut ranges = Vec::new();
+            for m in pattern.find_iter(&plain).filter(|m| !m.as_str().is_empty()) {
+                let style = if self.counted == self.current {
+                    if self.jump {
+                        // The row the match ends on, which may be partway through a wrapped line
+                        row = Some(
+                            wrapped_height(&lines[..i], width)
+                                + wrapped_row(&lines[i], width, m.end() - 1),
+                        );
+                    }
+
+                    current_style
+                } else {
+                    match_style
+                };
+
+                ranges.push((m.start(), m.end(), style));
+                self.counted += 1;
+            }
+
+            if !ranges.is_empty() {
+                lines[i] = highlight(&lines[i], &ranges);
+            }
+        }
+
+        row
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let find = &mut app.viewing_frame.find;
+
+        match key.code {
+            KeyCode::Esc => {
+                find.clear();
+                app.quittable = true;
+            }
+            KeyCode::Enter => {
+                find.submit();
+                app.quittable = true;
+            }
+            _ => {
+                find.editor.handle_key(key);
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let border_style = get_border_style(self.locked, self.locked);
+
+        let input = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let mut text = vec![Span::styled("/", Style::default().fg(Color::Gray))];
+        for line in self.editor.lines(input, self.locked) {
+            text.extend(line.0);
+        }
+
+        // The number of matches is shown once the pattern has been searched for
+        if self.locked {
+        } else if self.total == 0 {
+            text.push(Span::styled(
+                "   no matches",
+                Style::default().fg(Color::Red),
+            ));
+        } else {
+            text.push(Span::styled(
+                format!("   [{}/{}]", self.current + 1, self.total),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+
+        let title = if self.locked {
+            "Find (Enter to search, Esc to cancel)"
+        } else {
+            "Find (n/N for next/previous, Esc to clear)"
+        };
+
+        let find = Paragraph::new(Spans::from(text))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(title),
+            )
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(find, area);
+    }
+}
+
+/// Rebuilds a line so that the byte ranges given have their style patched on top of the style
+/// already there. The ranges must be in order and not overlap
+fn highlight(line: &Spans, ranges: &[(usize, usize, Style)]) -> Spans<'static> {
+    let mut spans = Vec::new();
+    let mut span_start = 0;
+
+    for span in &line.0 {
+        let content = span.content.as_ref();
+        let span_end = span_start + content.len();
+        let mut cut = span_start;
+
+        for &(start, end, style) in ranges {
+            let start = start.max(span_start);
+            let end = end.min(span_end);
+            if start >= end {
+                continue;
+            }
+
+            if start > cut {
+                spans.push(Span::styled(
+                    String::from(&content[cut - span_start..start - span_start]),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                String::from(&content[start - span_start..end - span_start]),
+                span.style.patch(style),
+            ));
+            cut = end;
+        }
+
+        if cut < span_end {
+            spans.push(Span::styled(
+                String::from(&content[cut - span_start..]),
+                span.style,
+            ));
+        }
+
+        span_start = span_end;
+    }
+
+    Spans::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(text: &str) -> FindBar {
+        let mut find_bar = FindBar::new();
+        find_bar.editor.set_text(text);
+        find_bar.submit();
+        find_bar
+    }
+
+    #[test]
+    fn invalid_regex_is_searched_literally() {
+        let pattern = find("a(b").pattern.unwrap();
+        assert!(pattern.is_match("xa(by"));
+        assert!(!pattern.is_match("ab"));
+
+        let pattern = find("[x").pattern.unwrap();
+        assert!(pattern.is_match("A[X"));
+    }
+
+    #[test]
+    fn valid_regex_is_case_insensitive() {
+        let pattern = find("fo+ ba.").pattern.unwrap();
+        assert!(pattern.is_match("FOOO BAR"));
+        assert!(find("").pattern.is_none());
+    }
+
+    #[test]
+    fn jumps_to_the_row_inside_a_wrapped_line() {
+        let mut find_bar = find("fox");
+        let mut lines = vec![
+            Spans::from("first"),
+            Spans::from("the quick brown fox jumps over the lazy dog"),
+            Spans::from("another fox"),
+        ];
+
+        // "the quick", "brown fox", ... so the first fox is on the second row of its line
+        find_bar.begin();
+        assert_eq!(find_bar.search(&mut lines, 10), Some(2));
+        find_bar.end();
+        assert_eq!(find_bar.total, 2);
+
+        // The second line takes five rows, and "another fox" wraps before "fox" too
+        find_bar.next();
+        find_bar.begin();
+        assert_eq!(find_bar.search(&mut lines, 10), Some(7));
+        find_bar.end();
+    }
+}
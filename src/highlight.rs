@@ -0,0 +1,547 @@
+use tui::style::{Color, Modifier, Style};
+use tui::text::Span;
+
+/// The different kinds of text that are colored when highlighting code
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Plain,
+    Keyword,
+    Literal,
+    String,
+    Number,
+    Comment,
+    Function,
+    Type,
+    /// Keys in JSON objects and TOML tables
+    Key,
+    /// TOML table headers
+    Header,
+    /// Shell variables
+    Variable,
+}
+
+/// The colors used for each kind of token, chosen to be readable in either dark or light mode
+pub struct Palette {
+    dark_mode: bool,
+}
+
+impl Palette {
+    pub fn new(dark_mode: bool) -> Self {
+        Self { dark_mode }
+    }
+
+    fn style(&self, token: Token) -> Style {
+        let color = if self.dark_mode {
+            match token {
+                Token::Plain => Color::White,
+                Token::Keyword | Token::Header => Color::LightMagenta,
+                Token::Literal | Token::Number | Token::Variable => Color::LightCyan,
+                Token::String => Color::LightGreen,
+                Token::Comment => Color::DarkGray,
+                Token::Function | Token::Key => Color::LightBlue,
+                Token::Type => Color::LightYellow,
+            }
+        } else {
+            match token {
+                Token::Plain => Color::Black,
+                Token::Keyword | Token::Header => Color::Magenta,
+                Token::Literal | Token::Number | Token::Variable => Color::Cyan,
+                Token::String => Color::Green,
+                Token::Comment => Color::Gray,
+                Token::Function | Token::Key => Color::Blue,
+                Token::Type => Color::Red,
+            }
+        };
+
+        let style = Style::default().fg(color);
+        match token {
+            Token::Keyword | Token::Header => style.add_modifier(Modifier::BOLD),
+            Token::Comment => style.add_modifier(Modifier::ITALIC),
+            _ => style,
+        }
+    }
+
+    /// The style used for code in a language that can't be highlighted
+    pub fn code(&self) -> Style {
+        if self.dark_mode {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Red)
+        }
+    }
+}
+
+/// The languages that have rules of their own beyond keywords, comments, and strings
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Syntax {
+    Rust,
+    Python,
+    Sql,
+    Shell,
+    Json,
+    Toml,
+}
+
+/// The rules for picking out the tokens of a language
+struct Language {
+    syntax: Syntax,
+    keywords: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, with the longer ones first so that `"""` is found before `"`
+    strings: &'static [&'static str],
+    case_sensitive: bool,
+}
+
+const RUST: Language = Language {
+    syntax: Syntax::Rust,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    literals: &["true", "false", "None", "Some", "Ok", "Err"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    strings: &["\""],
+    case_sensitive: true,
+};
+
+const PYTHON: Language = Language {
+    syntax: Syntax::Python,
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield",
+    ],
+    literals: &["True", "False", "None", "self"],
+    line_comments: &["#"],
+    block_comment: None,
+    strings: &["\"\"\"", "'''", "\"", "'"],
+    case_sensitive: true,
+};
+
+const SQL: Language = Language {
+    syntax: Syntax::Sql,
+    keywords: &[
+        "add",
+        "all",
+        "alter",
+        "and",
+        "as",
+        "asc",
+        "between",
+        "by",
+        "case",
+        "check",
+        "column",
+        "constraint",
+        "create",
+        "cross",
+        "default",
+        "delete",
+        "desc",
+        "distinct",
+        "drop",
+        "else",
+        "end",
+        "exists",
+        "foreign",
+        "from",
+        "full",
+        "group",
+        "having",
+        "if",
+        "in",
+        "index",
+        "inner",
+        "insert",
+        "into",
+        "is",
+        "join",
+        "key",
+        "left",
+        "like",
+        "limit",
+        "not",
+        "offset",
+        "on",
+        "or",
+        "order",
+        "outer",
+        "primary",
+        "recursive",
+        "references",
+        "returning",
+        "right",
+        "select",
+        "set",
+        "table",
+        "then",
+        "truncate",
+        "union",
+        "unique",
+        "update",
+        "using",
+        "values",
+        "when",
+        "where",
+        "with",
+    ],
+    literals: &["true", "false", "null"],
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    strings: &["'", "\""],
+    case_sensitive: false,
+};
+
+const SHELL: Language = Language {
+    syntax: Syntax::Shell,
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "select", "then", "until", "while",
+    ],
+    literals: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    strings: &["\"", "'"],
+    case_sensitive: true,
+};
+
+const JSON: Language = Language {
+    syntax: Syntax::Json,
+    keywords: &[],
+    literals: &["true", "false", "null"],
+    line_comments: &[],
+    block_comment: None,
+    strings: &["\""],
+    case_sensitive: true,
+};
+
+const TOML: Language = Language {
+    syntax: Syntax::Toml,
+    keywords: &[],
+    literals: &["true", "false"],
+    line_comments: &["#"],
+    block_comment: None,
+    strings: &["\"\"\"", "'''", "\"", "'"],
+    case_sensitive: true,
+};
+
+/// Finds the rules for the language named at the start of a fenced code block
+fn language(name: &str) -> Option<&'static Language> {
+    match name.trim().to_lowercase().as_str() {
+        "rust" | "rs" => Some(&RUST),
+        "python" | "py" => Some(&PYTHON),
+        "sql" | "postgres" | "postgresql" => Some(&SQL),
+        "sh" | "bash" | "shell" | "zsh" | "console" => Some(&SHELL),
+        "json" => Some(&JSON),
+        "toml" => Some(&TOML),
+        _ => None,
+    }
+}
+
+/// Highlights a whole code block, returning the spans for each of its lines.
+///
+/// The whole block is highlighted at once so that comments and strings that span multiple lines
+/// are colored correctly. Languages that aren't known get a single color
+pub fn highlight(lang: &str, code: &str, palette: &Palette) -> Vec<Vec<Span<'static>>> {
+    let tokens = match language(lang) {
+        Some(language) => tokenize(language, code),
+        None => return plain(code, palette.code()),
+    };
+
+    let mut lines = vec![Vec::new()];
+
+    for (text, token) in tokens {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            if !part.is_empty() {
+                let line = lines.last_mut().unwrap();
+                line.push(Span::styled(String::from(part), palette.style(token)));
+            }
+        }
+    }
+
+    lines
+}
+
+fn plain(code: &str, style: Style) -> Vec<Vec<Span<'static>>> {
+    code.split('\n')
+        .map(|line| vec![Span::styled(String::from(line), style)])
+        .collect()
+}
+
+/// Splits the code into runs of text and the kind of token each run is
+fn tokenize<'a>(language: &Language, code: &'a str) -> Vec<(&'a str, Token)> {
+    let mut tokens: Vec<(&str, Token)> = Vec::new();
+    let mut i = 0;
+    // Whether there is only whitespace between the start of the line and `i`
+    let mut at_line_start = true;
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    while i < code.len() {
+        let rest = &code[i..];
+        let c = rest.chars().next().unwrap();
+
+        let (len, token) = if let Some(len) = language
+            .line_comments
+            .iter()
+            .find(|start| rest.starts_with(**start))
+            .map(|_| rest.find('\n').unwrap_or(rest.len()))
+        {
+            (len, Token::Comment)
+        } else if let Some((start, end)) = language
+            .block_comment
+            .filter(|(start, _)| rest.starts_with(start))
+        {
+            let len = rest[start.len()..]
+                .find(end)
+                .map(|pos| start.len() + pos + end.len())
+                .unwrap_or(rest.len());
+            (len, Token::Comment)
+        } else if let Some(quote) = language.strings.iter().find(|q| rest.starts_with(**q)) {
+            let len = string_len(rest, quote);
+
+            // Strings that are followed by a colon are keys in JSON
+            let after = rest[len..].trim_start_matches([' ', '\t']);
+            if language.syntax == Syntax::Json && after.starts_with(':') {
+                (len, Token::Key)
+            } else {
+                (len, Token::String)
+            }
+        } else if language.syntax == Syntax::Rust && c == '\'' {
+            rust_quote(rest)
+        } else if language.syntax == Syntax::Toml && c == '[' && at_line_start {
+            (rest.find('\n').unwrap_or(rest.len()), Token::Header)
+        } else if language.syntax == Syntax::Shell && c == '$' {
+            let len = if rest[1..].starts_with('{') {
+                rest.find('}').map(|end| end + 1).unwrap_or(rest.len())
+            } else {
+                1 + rest[1..].find(|c| !is_word(c)).unwrap_or(rest.len() - 1)
+            };
+            (len, Token::Variable)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(is_word(c) || c == '.'))
+                .unwrap_or(rest.len());
+            (len, Token::Number)
+        } else if is_word(c) {
+            let len = rest
+                .find(|c: char| !(is_word(c) || (c == '-' && language.syntax == Syntax::Toml)))
+                .unwrap_or(rest.len());
+            (
+                len,
+                classify(language, &rest[..len], &rest[len..], at_line_start),
+            )
+        } else {
+            (c.len_utf8(), Token::Plain)
+        };
+
+        // Merge runs of the same kind so that there are fewer spans
+        match tokens.last_mut() {
+            Some((text, last)) if *last == token && token == Token::Plain => {
+                *text = &code[i - text.len()..i + len];
+            }
+            _ => tokens.push((&rest[..len], token)),
+        }
+
+        let text = &rest[..len];
+        at_line_start = match text.rfind('\n') {
+            Some(n) => text[n + 1..].trim().is_empty(),
+            None => at_line_start && text.trim().is_empty(),
+        };
+        i += len;
+    }
+
+    tokens
+}
+
+/// Decides what kind of token a word is from the word itself and the text after it
+fn classify(language: &Language, word: &str, after: &str, at_line_start: bool) -> Token {
+    let matches = |list: &[&str]| {
+        if language.case_sensitive {
+            list.contains(&word)
+        } else {
+            list.iter().any(|w| w.eq_ignore_ascii_case(word))
+        }
+    };
+    let next = after.trim_start_matches([' ', '\t']);
+
+    if matches(language.keywords) {
+        Token::Keyword
+    } else if matches(language.literals) {
+        Token::Literal
+    } else if language.syntax == Syntax::Toml && at_line_start && next.starts_with('=') {
+        Token::Key
+    } else if next.starts_with('(') || (language.syntax == Syntax::Rust && after.starts_with('!')) {
+        Token::Function
+    } else if language.syntax == Syntax::Rust && word.starts_with(char::is_uppercase) {
+        Token::Type
+    } else {
+        Token::Plain
+    }
+}
+
+/// Finds how long a string starting with `quote` is, skipping over escaped characters.
+/// Strings that aren't closed run to the end of the line, or to the end of the code for
+/// multi-line delimiters
+fn string_len(rest: &str, quote: &str) -> usize {
+    let multi_line = quote.len() == 3;
+    let mut chars = rest.char_indices().skip(quote.chars().count()).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if rest[i..].starts_with(quote) {
+            return i + quote.len();
+        } else if c == '\n' && !multi_line {
+            return i;
+        }
+    }
+
+    rest.len()
+}
+
+/// In Rust a single quote either starts a character like `'a'` or a lifetime like `'a`
+fn rust_quote(rest: &str) -> (usize, Token) {
+    let mut chars = rest.char_indices().skip(1);
+
+    match (chars.next(), chars.next()) {
+        (Some((_, '\\')), _) => (string_len(rest, "'"), Token::String),
+        (Some(_), Some((i, '\''))) => (i + 1, Token::String),
+        _ => {
+            let len = rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map(|end| end + 1)
+                .unwrap_or(rest.len());
+            (len, Token::Type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The tokens that aren't plain text, to leave out the spaces and punctuation between them
+    fn colored(lang: &str, code: &str) -> Vec<(String, Token)> {
+        tokenize(language(lang).unwrap(), code)
+            .into_iter()
+            .filter(|(_, token)| *token != Token::Plain)
+            .map(|(text, token)| (String::from(text), token))
+            .collect()
+    }
+
+    fn token(text: &str, token: Token) -> (String, Token) {
+        (String::from(text), token)
+    }
+
+    #[test]
+    fn strings_skip_escaped_quotes() {
+        assert_eq!(
+            colored("rust", r#"let s = "a \"b\" c\\";"#),
+            [
+                token("let", Token::Keyword),
+                token(r#""a \"b\" c\\""#, Token::String)
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_strings_end_with_the_line() {
+        assert_eq!(
+            colored("python", "x = 'open\ny = 1"),
+            [token("'open", Token::String), token("1", Token::Number)]
+        );
+        assert_eq!(
+            colored("python", "\"\"\"doc\nstring\"\"\""),
+            [token("\"\"\"doc\nstring\"\"\"", Token::String)]
+        );
+    }
+
+    #[test]
+    fn comments_run_to_the_end_of_the_line_or_block() {
+        assert_eq!(
+            colored("sql", "SELECT 1 -- one\n/* a\nb */ from t"),
+            [
+                token("SELECT", Token::Keyword),
+                token("1", Token::Number),
+                token("-- one", Token::Comment),
+                token("/* a\nb */", Token::Comment),
+                token("from", Token::Keyword),
+            ]
+        );
+        // A comment marker inside a string is part of the string
+        assert_eq!(
+            colored("sh", "echo \"# not\" # yes"),
+            [
+                token("\"# not\"", Token::String),
+                token("# yes", Token::Comment)
+            ]
+        );
+    }
+
+    #[test]
+    fn toml_tables_and_keys_start_lines() {
+        assert_eq!(
+            colored(
+                "toml",
+                "[package]\nname = \"st-read\"\nlist = [1, 2]\n  [dependencies.tui]"
+            ),
+            [
+                token("[package]", Token::Header),
+                token("name", Token::Key),
+                token("\"st-read\"", Token::String),
+                token("list", Token::Key),
+                token("1", Token::Number),
+                token("2", Token::Number),
+                token("[dependencies.tui]", Token::Header),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_keys_are_strings_before_a_colon() {
+        assert_eq!(
+            colored("json", r#"{"a": "b", "c" : null}"#),
+            [
+                token("\"a\"", Token::Key),
+                token("\"b\"", Token::String),
+                token("\"c\"", Token::Key),
+                token("null", Token::Literal),
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_quotes_are_characters_or_lifetimes() {
+        assert_eq!(
+            colored("rust", r"fn f<'a>(c: &'a str) -> char { '\n' }"),
+            [
+                token("fn", Token::Keyword),
+                token("'a", Token::Type),
+                token("'a", Token::Type),
+                token(r"'\n'", Token::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_are_split_into_lines() {
+        let lines = highlight("rust", "/* a\nb */ x", &Palette::new(true));
+        let text: Vec<Vec<String>> = lines
+            .iter()
+            .map(|line| line.iter().map(|span| span.content.to_string()).collect())
+            .collect();
+
+        assert_eq!(text, [vec!["/* a"], vec!["b */", " x"]]);
+        assert_eq!(highlight("cobol", "A\nB", &Palette::new(true)).len(), 2);
+    }
+}
@@ -7,6 +7,7 @@ use tui::Frame;
 use tui::{backend::Backend, widgets::Block};
 
 use crate::app::{get_border_style, App, AppView};
+use crate::editor::Editor;
 
 pub struct InitialFrame {
     login_button: InitialButtonBox,
@@ -137,6 +138,66 @@ impl InitialButtonBox {
     }
 }
 
+/// A box on the login and register pages that is typed in after pressing Enter, until Esc
+pub struct TextField {
+    pub editor: Editor,
+    locked: bool,
+    title: &'static str,
+}
+
+impl TextField {
+    pub fn new(title: &'static str, editor: Editor) -> Self {
+        Self {
+            editor,
+            locked: false,
+            title,
+        }
+    }
+
+    /// Handles a key, returning false if it is Up or Down while the field isn't being typed in,
+    /// so that the page can move to the next box
+    pub fn handle_key(&mut self, key: KeyEvent, quittable: &mut bool) -> bool {
+        // While locked, the editing keys change the text
+        if self.locked && self.editor.handle_key(key) {
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Down => return self.locked,
+            KeyCode::Enter => {
+                self.locked = true;
+                *quittable = false;
+            }
+            KeyCode::Esc => {
+                self.locked = false;
+                *quittable = true;
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.locked);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(self.title);
+
+        self.editor.render(
+            f,
+            area,
+            block,
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            self.locked,
+        );
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum LoginSelectedBox {
     Username,
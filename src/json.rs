@@ -0,0 +1,164 @@
+use std::fmt::{self, Display, Write};
+
+/// A JSON value, which is written out indented by two spaces
+#[derive(Debug, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    /// The keys and values of an object, kept in the order they were added
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Starts an object, which fields can be added to with `field`
+    pub fn object() -> Self {
+        Json::Object(Vec::new())
+    }
+
+    /// Adds a field to an object. Does nothing to other values
+    pub fn field(mut self, key: &str, value: impl Into<Json>) -> Self {
+        if let Json::Object(fields) = &mut self {
+            fields.push((String::from(key), value.into()));
+        }
+
+        self
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        let pad = "  ".repeat(indent + 1);
+
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) if items.is_empty() => f.write_str("[]"),
+            Json::Array(items) => {
+                f.write_str("[\n")?;
+                for (i, item) in items.iter().enumerate() {
+                    f.write_str(&pad)?;
+                    item.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{}]", "  ".repeat(indent))
+            }
+            Json::Object(fields) if fields.is_empty() => f.write_str("{}"),
+            Json::Object(fields) => {
+                f.write_str("{\n")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    f.write_str(&pad)?;
+                    write_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < fields.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{}}}", "  ".repeat(indent))
+            }
+        }
+    }
+}
+
+/// Writes a string in quotes, escaping the characters JSON doesn't allow in strings
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<i32> for Json {
+    fn from(n: i32) -> Self {
+        Json::Number(n.into())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(String::from(s))
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        let json = Json::from("say \"hi\"\\\n\r\t");
+        assert_eq!(json.to_string(), r#""say \"hi\"\\\n\r\t""#);
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        let json = Json::from("a\u{0}b\u{1b}c\u{1f}");
+        assert_eq!(json.to_string(), r#""a\u0000b\u001bc\u001f""#);
+    }
+
+    #[test]
+    fn other_characters_are_kept() {
+        // DEL and everything past ASCII are allowed in JSON strings as they are
+        let json = Json::from("é 日本 😀 \u{7f}");
+        assert_eq!(json.to_string(), "\"é 日本 😀 \u{7f}\"");
+    }
+
+    #[test]
+    fn keys_are_escaped() {
+        let json = Json::object().field("a\"b", 1);
+        assert_eq!(json.to_string(), "{\n  \"a\\\"b\": 1\n}");
+    }
+
+    #[test]
+    fn nested_values_are_indented() {
+        let json = Json::object()
+            .field("name", "Ana")
+            .field("tags", vec!["x", "y"])
+            .field("empty", Vec::<i32>::new())
+            .field("missing", None::<i32>);
+        assert_eq!(
+            json.to_string(),
+            "{\n  \"name\": \"Ana\",\n  \"tags\": [\n    \"x\",\n    \"y\"\n  ],\n  \"empty\": [],\n  \"missing\": null\n}"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView};
+use crate::list::StatefulList;
+
+/// How many users are shown on the leaderboard
+const LEADERBOARD_SIZE: i64 = 50;
+
+/// Loads the sum of the votes on everything a user has written, which the database keeps up to
+/// date as votes are cast
+pub fn load_karma(user_id: i32) -> i32 {
+    use st_read::schema::userkarma::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::userkarma
+        .find(user_id)
+        .select(dsl::karma)
+        .first(&connection)
+        .unwrap_or(0)
+}
+
+/// Loads everyone's karma by their id, for showing it next to many posts and comments at once
+pub fn load_all_karma(connection: &PgConnection) -> QueryResult<HashMap<i32, i32>> {
+    use st_read::schema::userkarma::dsl;
+
+    dsl::userkarma
+        .select((dsl::user_id, dsl::karma))
+        .load(connection)
+        .map(|karma: Vec<(i32, i32)>| karma.into_iter().collect())
+}
+
+/// Loads the ids, names, and karma of the users with the most karma
+fn load_leaderboard() -> QueryResult<Vec<(i32, String, i32)>> {
+    use st_read::schema::userkarma::dsl as karma_dsl;
+    use st_read::schema::users::dsl as users_dsl;
+    let connection = st_read::establish_connection();
+
+    karma_dsl::userkarma
+        .inner_join(users_dsl::users)
+        .filter(users_dsl::deleted.eq(false))
+        .order((karma_dsl::karma.desc(), users_dsl::name))
+        .select((users_dsl::user_id, users_dsl::name, karma_dsl::karma))
+        .limit(LEADERBOARD_SIZE)
+        .load(&connection)
+}
+
+/// Ranks the users by their karma
+pub struct LeaderboardFrame {
+    users: StatefulList<(i32, String, i32)>,
+}
+
+impl LeaderboardFrame {
+    pub fn new() -> Self {
+        Self {
+            users: StatefulList::with_items(Vec::new()),
+        }
+    }
+
+    pub fn load(&mut self) {
+        self.users = StatefulList::highlighted(load_leaderboard().unwrap_or_default());
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let users = &mut app.leaderboard_frame.users;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !users.items.is_empty() => {
+                users.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !users.items.is_empty() => {
+                users.previous();
+            }
+            KeyCode::Enter => {
+                if let Some(&(user_id, ..)) = users.selected_item() {
+                    app.show_author(user_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let deselected_style = Style::default().fg(Color::Gray);
+
+        let lines: Vec<Spans> = self
+            .users
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, (_, name, karma))| {
+                let style = if self.users.selected() == Some(i) {
+                    self.users.highlight_style()
+                } else {
+                    deselected_style
+                };
+
+                Spans::from(vec![
+                    Span::styled(self.users.prefix(i), style),
+                    Span::styled(format!("{:>3}. ", i + 1), style),
+                    Span::styled(format!("{:<40}", name), style),
+                    Span::styled(format!("{:>6} karma", karma), style),
+                ])
+            })
+            .collect();
+
+        // Keep the selected user on the screen
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let selected = self.users.selected().unwrap_or(0) as u16;
+        let offset = (selected + 1).saturating_sub(inner.height);
+
+        let leaderboard = Paragraph::new(lines).scroll((offset, 0)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(true, false))
+                .title("Leaderboard (Enter to see a profile, Esc to go back)"),
+        );
+
+        f.render_widget(leaderboard, area);
+    }
+}
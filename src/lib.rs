@@ -11,6 +11,14 @@ pub fn establish_connection() -> PgConnection {
     PgConnection::establish(&database_url).expect(&format!("Error connecting to {}", database_url))
 }
 
+/// Connects to the database like `establish_connection`, but returns the error instead of
+/// panicking, for work done in the background
+pub fn try_establish_connection() -> ConnectionResult<PgConnection> {
+    let database_url =
+        env::var("DATABASE_URL").map_err(|e| ConnectionError::BadConnection(e.to_string()))?;
+    PgConnection::establish(&database_url)
+}
+
 pub mod models;
 pub mod schema;
 pub mod util;
@@ -0,0 +1,590 @@
+use std::borrow::Cow;
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::{cursor::MoveTo, queue, style};
+use tui::backend::Backend;
+use tui::buffer::{Buffer, Cell};
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph};
+use tui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::{get_border_style, App};
+use crate::list::StatefulList;
+use crate::markdown::{link_style, marker_style};
+use crate::scroll::ScrollState;
+
+/// The kinds of links that can be opened. Anything else, like `file:`, `javascript:`, or a
+/// scheme some other program handles, could do more than show a page
+const OPENABLE_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// A part of the screen that a link was drawn on, which can be clicked or turned into a terminal
+/// hyperlink. Links that wrap onto several rows have one of these per row
+#[derive(Debug, Clone)]
+pub struct Hyperlink {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub url: String,
+}
+
+impl Hyperlink {
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        y == self.y && x >= self.x && x < self.x + self.width
+    }
+}
+
+/// The numbered list of every link in the open post and its comments, which can be opened in a
+/// browser or copied
+pub struct LinkList {
+    urls: StatefulList<String>,
+    /// A boolean for if the list is open and taking every key. Esc must be pressed to close it
+    pub locked: bool,
+    scroll: ScrollState,
+    /// The result of the last link that was opened or copied
+    message: Option<String>,
+    /// Whether the selected link was clicked on, and is waiting for the user to open it
+    clicked: bool,
+    /// The inside of each pane that links are drawn in, which are searched for them once drawn
+    panes: Vec<Rect>,
+    /// The links drawn in the list itself, whose places are already known
+    rows: Vec<Hyperlink>,
+    /// Every link on the screen as of the last frame
+    hyperlinks: Vec<Hyperlink>,
+    /// Whether the terminal can show OSC 8 hyperlinks
+    supported: bool,
+}
+
+impl LinkList {
+    pub fn new() -> Self {
+        Self {
+            urls: StatefulList::with_items(Vec::new()),
+            locked: false,
+            scroll: ScrollState::new(),
+            message: None,
+            clicked: false,
+            panes: Vec::new(),
+            rows: Vec::new(),
+            hyperlinks: Vec::new(),
+            supported: supports_hyperlinks(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.urls = StatefulList::with_items(Vec::new());
+        self.locked = false;
+        self.scroll.reset();
+        self.message = None;
+        self.clicked = false;
+        self.panes.clear();
+        self.rows.clear();
+        self.hyperlinks.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.items.is_empty()
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Replaces the links with the ones found while rendering this frame, and forgets where the
+    /// links of the last frame were drawn
+    pub fn begin(&mut self, urls: Vec<String>) {
+        let selected = self
+            .urls
+            .selected()
+            .filter(|i| *i < urls.len())
+            .or(if urls.is_empty() { None } else { Some(0) });
+
+        self.urls.items = urls;
+        self.urls.select(selected);
+        self.panes.clear();
+        self.rows.clear();
+    }
+
+    /// Adds a pane whose links should be found once the frame is drawn
+    pub fn add_pane(&mut self, area: Rect) {
+        self.panes.push(area);
+    }
+
+    /// Opens the link in the browser given by `$BROWSER`, or the system's default browser
+    pub fn open(&mut self, url: &str) {
+        if !is_openable(url) {
+            self.message = Some(format!(
+                "Won't open {}: only http, https, and mailto links can be opened",
+                url
+            ));
+            return;
+        }
+
+        self.message = Some(match open_url(url) {
+            Ok(()) => format!("Opened {}", url),
+            Err(e) => format!("Failed to open {}: {}", url, e),
+        });
+    }
+
+    /// Opens the list with a link that was clicked on selected, so that it is shown to the user
+    /// before it is opened
+    pub fn ask_open(&mut self, url: &str) {
+        if let Some(i) = self.urls.items.iter().position(|item| item == url) {
+            self.urls.select(Some(i));
+            self.locked = true;
+            self.clicked = true;
+        }
+    }
+
+    /// Copies the link to the clipboard
+    pub fn copy(&mut self, url: &str) {
+        self.message = Some(match copy_text(url) {
+            Ok(()) => format!("Copied {}", url),
+            Err(e) => format!("Failed to copy {}: {}", url, e),
+        });
+    }
+
+    /// Returns the link drawn at a place on the screen during the last frame
+    pub fn link_at(&self, x: u16, y: u16) -> Option<&str> {
+        self.hyperlinks
+            .iter()
+            .find(|link| link.contains(x, y))
+            .map(|link| link.url.as_str())
+    }
+
+    /// Finds where every link was drawn once the frame is finished. If the links weren't
+    /// rendered this frame, there are none on the screen.
+    ///
+    /// In the panes, the text of a link is followed by its "[n]" marker, so the underlined text
+    /// just before each marker belongs to the link it numbers
+    pub fn find_hyperlinks(&mut self, buffer: &Buffer) {
+        let mut hyperlinks = std::mem::take(&mut self.rows);
+
+        for pane in std::mem::take(&mut self.panes) {
+            // The rows of link text that haven't been matched to a marker yet
+            let mut pending: Vec<(u16, u16, u16)> = Vec::new();
+
+            for y in pane.top()..pane.bottom() {
+                let mut x = pane.left();
+
+                while x < pane.right() {
+                    let cell = buffer.get(x, y);
+
+                    if let Some((number, width)) = parse_marker(buffer, x, y, pane.right()) {
+                        let url = number.checked_sub(1).and_then(|i| self.urls.items.get(i));
+                        if let Some(url) = url {
+                            pending.push((x, y, width));
+                            hyperlinks.extend(pending.drain(..).map(|(x, y, width)| Hyperlink {
+                                x,
+                                y,
+                                width,
+                                url: url.clone(),
+                            }));
+                        }
+                        pending.clear();
+                        x += width;
+                        continue;
+                    }
+
+                    if is_link(cell) {
+                        match pending.last_mut() {
+                            Some((start, row, width)) if *row == y && *start + *width == x => {
+                                *width += 1
+                            }
+                            _ => pending.push((x, y, 1)),
+                        }
+                    } else if !cell.symbol.trim().is_empty() {
+                        // Something other than a marker came after the link text
+                        pending.clear();
+                    }
+
+                    x += 1;
+                }
+            }
+        }
+
+        self.hyperlinks = hyperlinks;
+    }
+
+    /// Redraws the links found in the last frame wrapped in OSC 8 escape codes, which lets
+    /// terminals that support them open the links with a click.
+    ///
+    /// The cells are drawn exactly as they already are, so this must be written to the terminal
+    /// right after the frame
+    pub fn write_hyperlinks(&self, buffer: &Buffer, out: &mut impl Write) -> io::Result<()> {
+        if !self.supported {
+            return Ok(());
+        }
+
+        for link in &self.hyperlinks {
+            queue!(out, MoveTo(link.x, link.y))?;
+            write!(out, "\x1b]8;;{}\x1b\\", escape_controls(&link.url))?;
+
+            let mut last = None;
+            for x in link.x..link.x + link.width {
+                let cell = buffer.get(x, link.y);
+
+                // The style only needs to be set when it changes
+                if last != Some(cell.style()) {
+                    last = Some(cell.style());
+                    queue!(
+                        out,
+                        style::SetAttribute(style::Attribute::Reset),
+                        style::SetForegroundColor(to_crossterm(cell.fg)),
+                        style::SetBackgroundColor(to_crossterm(cell.bg)),
+                    )?;
+                    if cell.modifier.contains(Modifier::BOLD) {
+                        queue!(out, style::SetAttribute(style::Attribute::Bold))?;
+                    }
+                    if cell.modifier.contains(Modifier::ITALIC) {
+                        queue!(out, style::SetAttribute(style::Attribute::Italic))?;
+                    }
+                    if cell.modifier.contains(Modifier::UNDERLINED) {
+                        queue!(out, style::SetAttribute(style::Attribute::Underlined))?;
+                    }
+                }
+                write!(out, "{}", cell.symbol)?;
+            }
+
+            write!(out, "\x1b]8;;\x1b\\")?;
+            queue!(
+                out,
+                style::SetAttribute(style::Attribute::Reset),
+                style::ResetColor
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let links = &mut app.viewing_frame.links;
+        links.clicked = false;
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('l') => {
+                links.locked = false;
+                app.quittable = true;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                links.urls.previous();
+            }
+            KeyCode::Down | KeyCode::Char('j') if !links.is_empty() => {
+                links.urls.next();
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let i = c as usize - '1' as usize;
+                if i < links.urls.items.len() {
+                    links.urls.select(Some(i));
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('o') => {
+                if let Some(url) = links.urls.selected_item().cloned() {
+                    links.open(&url);
+                }
+            }
+            KeyCode::Char('y') => {
+                if let Some(url) = links.urls.selected_item().cloned() {
+                    links.copy(&url);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let border_style = get_border_style(true, self.locked);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(match (&self.message, self.urls.selected_item()) {
+                (_, Some(url)) if self.clicked => {
+                    format!("Links - Open {}? (Enter to open, Esc to close)", url)
+                }
+                (Some(message), _) => format!("Links - {}", message),
+                _ => String::from("Links (Enter to open, y to copy, Esc to close)"),
+            });
+        let inner = block.inner(area);
+
+        self.scroll
+            .update(self.urls.items.len() as u16, inner.height);
+        if let Some(selected) = self.urls.selected() {
+            self.scroll.show_line(selected as u16);
+        }
+
+        let mut lines = Vec::new();
+
+        for (i, url) in self.urls.items.iter().enumerate() {
+            let marker = format!("[{}] ", i + 1);
+            let (marker_style, link_style) = if self.urls.selected() == Some(i) {
+                let selected = Style::default().bg(Color::DarkGray);
+                (marker_style().patch(selected), link_style().patch(selected))
+            } else {
+                (marker_style(), link_style())
+            };
+
+            let row = i as u16;
+            if row >= self.scroll.offset() && row - self.scroll.offset() < inner.height {
+                let width = (marker.width() + url.width()) as u16;
+
+                self.rows.push(Hyperlink {
+                    x: inner.x,
+                    y: inner.y + row - self.scroll.offset(),
+                    width: width.min(inner.width),
+                    url: url.clone(),
+                });
+            }
+
+            lines.push(Spans::from(vec![
+                Span::styled(marker, marker_style),
+                Span::styled(url.clone(), link_style),
+            ]));
+        }
+
+        let list = Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(Color::Gray))
+            .scroll((self.scroll.offset(), 0));
+
+        f.render_widget(list, area);
+    }
+}
+
+/// Returns true if the terminal is known to support OSC 8 hyperlinks. `FORCE_HYPERLINK` can be
+/// set to 1 or 0 to turn them on or off regardless
+fn supports_hyperlinks() -> bool {
+    if let Ok(force) = env::var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+
+    let var = |name: &str| env::var(name).unwrap_or_default();
+
+    if matches!(
+        var("TERM_PROGRAM").as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+    ) {
+        return true;
+    }
+
+    let term = var("TERM");
+    if ["kitty", "alacritty", "foot", "wezterm", "ghostty"]
+        .iter()
+        .any(|name| term.contains(name))
+    {
+        return true;
+    }
+
+    // VTE based terminals, such as GNOME Terminal, have supported them since 0.50
+    var("VTE_VERSION").parse::<u32>().unwrap_or(0) >= 5000
+        || !var("WT_SESSION").is_empty()
+        || !var("KONSOLE_VERSION").is_empty()
+}
+
+fn is_link(cell: &Cell) -> bool {
+    let style = link_style();
+
+    Some(cell.fg) == style.fg && cell.modifier.contains(Modifier::UNDERLINED)
+}
+
+/// Reads a "[n]" link marker starting at a cell, returning its number and width
+fn parse_marker(buffer: &Buffer, x: u16, y: u16, right: u16) -> Option<(usize, u16)> {
+    let is_marker = |cell: &Cell| Some(cell.fg) == marker_style().fg && cell.modifier.is_empty();
+
+    let start = buffer.get(x, y);
+    if start.symbol != "[" || !is_marker(start) {
+        return None;
+    }
+
+    let mut digits = String::new();
+    for end in x + 1..right {
+        let cell = buffer.get(end, y);
+        if !is_marker(cell) {
+            return None;
+        }
+
+        match cell.symbol.as_str() {
+            "]" if !digits.is_empty() => {
+                return Some((digits.parse().ok()?, end - x + 1));
+            }
+            digit if digit.len() == 1 && digit.as_bytes()[0].is_ascii_digit() => {
+                digits.push_str(digit)
+            }
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Percent-encodes the control characters in a URL, so that it can't end the escape code it is
+/// written in and start one of its own. Links from Markdown can contain anything
+fn escape_controls(url: &str) -> Cow<'_, str> {
+    if !url.chars().any(char::is_control) {
+        return Cow::Borrowed(url);
+    }
+
+    let mut escaped = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_control() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Starts a program without letting it draw over the app. It is waited on from a thread of its
+/// own so that it doesn't linger as a zombie once it exits
+fn spawn(program: &str, args: &[&str]) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+/// Whether the link has one of the `OPENABLE_SCHEMES`
+fn is_openable(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        OPENABLE_SCHEMES
+            .iter()
+            .any(|openable| openable.eq_ignore_ascii_case(scheme))
+    })
+}
+
+fn open_url(url: &str) -> io::Result<()> {
+    // `$BROWSER` is a list of commands separated by ':', where "%s" is replaced with the URL
+    if let Ok(browsers) = env::var("BROWSER") {
+        for browser in browsers.split(':').filter(|b| !b.trim().is_empty()) {
+            let mut words: Vec<String> = browser.split_whitespace().map(String::from).collect();
+            if words.iter().any(|word| word.contains("%s")) {
+                for word in words.iter_mut() {
+                    *word = word.replace("%s", url);
+                }
+            } else {
+                words.push(String::from(url));
+            }
+
+            let args: Vec<&str> = words[1..].iter().map(String::as_str).collect();
+            if spawn(&words[0], &args).is_ok() {
+                return Ok(());
+            }
+        }
+    }
+
+    if cfg!(target_os = "macos") {
+        spawn("open", &[url])
+    } else if cfg!(windows) {
+        // Explorer is given the link itself, where `cmd /C start` would run anything after a `&`
+        spawn("explorer", &[url])
+    } else {
+        spawn("xdg-open", &[url])
+    }
+}
+
+/// Copies text using the system's clipboard tool. If there isn't one, the text is sent to the
+/// terminal in an OSC 52 escape code instead, which many terminals will put on the clipboard
+fn copy_text(text: &str) -> io::Result<()> {
+    let var = |name: &str| env::var_os(name).is_some();
+
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if var("WAYLAND_DISPLAY") {
+        &[("wl-copy", &[])]
+    } else if var("DISPLAY") {
+        &[
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    } else {
+        &[]
+    };
+
+    for (program, args) in tools {
+        let child = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        if let Ok(mut child) = child {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(text.as_bytes())?;
+            }
+            if child.wait()?.success() {
+                return Ok(());
+            }
+        }
+    }
+
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64::encode(text))?;
+    stdout.flush()
+}
+
+fn to_crossterm(color: Color) -> style::Color {
+    match color {
+        Color::Reset => style::Color::Reset,
+        Color::Black => style::Color::Black,
+        Color::Red => style::Color::DarkRed,
+        Color::Green => style::Color::DarkGreen,
+        Color::Yellow => style::Color::DarkYellow,
+        Color::Blue => style::Color::DarkBlue,
+        Color::Magenta => style::Color::DarkMagenta,
+        Color::Cyan => style::Color::DarkCyan,
+        Color::Gray => style::Color::Grey,
+        Color::DarkGray => style::Color::DarkGrey,
+        Color::LightRed => style::Color::Red,
+        Color::LightGreen => style::Color::Green,
+        Color::LightBlue => style::Color::Blue,
+        Color::LightYellow => style::Color::Yellow,
+        Color::LightMagenta => style::Color::Magenta,
+        Color::LightCyan => style::Color::Cyan,
+        Color::White => style::Color::White,
+        Color::Indexed(i) => style::Color::AnsiValue(i),
+        Color::Rgb(r, g, b) => style::Color::Rgb { r, g, b },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_only_web_and_mail_links() {
+        assert!(is_openable("https://example.com/a?b=c&d"));
+        assert!(is_openable("HTTP://example.com"));
+        assert!(is_openable("mailto:someone@example.com"));
+
+        assert!(!is_openable("file:///etc/passwd"));
+        assert!(!is_openable("javascript:alert(1)"));
+        assert!(!is_openable("foo://bar"));
+        assert!(!is_openable("example.com"));
+    }
+
+    #[test]
+    fn control_characters_are_percent_encoded() {
+        assert_eq!(escape_controls("https://a.com/"), "https://a.com/");
+        assert_eq!(
+            escape_controls("https://a.com/\u{1b}\\\u{85}"),
+            "https://a.com/%1B\\%C2%85"
+        );
+    }
+}
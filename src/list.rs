@@ -1,4 +1,5 @@
-use tui::style::Style;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
 
 pub struct StatefulList<T> {
     pub items: Vec<T>,
@@ -17,6 +18,22 @@ impl<T> StatefulList<T> {
         }
     }
 
+    /// Makes a list highlighted like the others in the app, with the first item selected
+    pub fn highlighted(items: Vec<T>) -> Self {
+        let mut list = Self::with_items(items);
+        list.with_highlight_style(
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        );
+        list.with_highlight_symbol(">> ");
+        if !list.items.is_empty() {
+            list.next();
+        }
+
+        list
+    }
+
     pub fn with_highlight_symbol(&mut self, symbol: impl AsRef<str>) -> &mut Self {
         self.highlight_symbol = String::from(symbol.as_ref());
 
@@ -37,6 +54,50 @@ impl<T> StatefulList<T> {
         self.highlight_style
     }
 
+    /// The spaces used instead of the highlighting symbol to keep everything indented
+    pub fn placeholder(&self) -> String {
+        " ".repeat(self.highlight_symbol.len())
+    }
+
+    /// The highlighting symbol if the `i`th item is selected, otherwise the placeholder
+    pub fn prefix(&self, i: usize) -> String {
+        if self.selected == Some(i) {
+            self.highlight_symbol.clone()
+        } else {
+            self.placeholder()
+        }
+    }
+
+    /// Adds the lines showing the `i`th item to `text`, followed by a blank line. Only the first
+    /// line gets the highlighting symbol, and the rest are indented to match
+    pub fn push_rows(
+        &self,
+        text: &mut Vec<Spans<'static>>,
+        i: usize,
+        lines: impl IntoIterator<Item = String>,
+        deselected_style: Style,
+    ) {
+        let style = if self.selected == Some(i) {
+            self.highlight_style
+        } else {
+            deselected_style
+        };
+
+        for (j, line) in lines.into_iter().enumerate() {
+            let prefix = if j == 0 {
+                self.prefix(i)
+            } else {
+                self.placeholder()
+            };
+
+            text.push(Spans::from(vec![
+                Span::styled(prefix, style),
+                Span::styled(line, style),
+            ]));
+        }
+        text.push(Spans::default());
+    }
+
     pub fn next(&mut self) {
         let i = match self.selected {
             Some(i) => {
@@ -7,11 +7,12 @@ use tui::Frame;
 use tui::{backend::Backend, widgets::Block};
 
 use crate::app::{get_border_style, App, AppView};
-use crate::initial::{InitialButtonBox, LoginSelectedBox};
+use crate::editor::Editor;
+use crate::initial::{InitialButtonBox, LoginSelectedBox, TextField};
 
 pub struct LoginFrame {
-    username: UsernameBox,
-    password: PasswordBox,
+    username: TextField,
+    password: TextField,
     login_button: LoginButtonBox,
     back_button: InitialButtonBox,
     selected: LoginSelectedBox,
@@ -20,8 +21,8 @@ pub struct LoginFrame {
 impl LoginFrame {
     pub fn new() -> Self {
         Self {
-            username: UsernameBox::new(),
-            password: PasswordBox::new(),
+            username: TextField::new("Email", Editor::single_line()),
+            password: TextField::new("Password", Editor::password()),
             login_button: LoginButtonBox::new(),
             back_button: InitialButtonBox::new("Back", false),
             selected: LoginSelectedBox::Username,
@@ -29,16 +30,29 @@ impl LoginFrame {
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let frame = &mut app.login_frame;
+        let field = match frame.selected {
+            LoginSelectedBox::Username => Some(&mut frame.username),
+            LoginSelectedBox::Password => Some(&mut frame.password),
+            _ => None,
+        };
+        if let Some(field) = field {
+            if !field.handle_key(key, &mut app.quittable) {
+                frame.selected = match (frame.selected, key.code) {
+                    (LoginSelectedBox::Username, KeyCode::Down) => LoginSelectedBox::Password,
+                    (LoginSelectedBox::Password, KeyCode::Up) => LoginSelectedBox::Username,
+                    (LoginSelectedBox::Password, KeyCode::Down) => LoginSelectedBox::ActionButton,
+                    (selected, _) => selected,
+                };
+            }
+            return;
+        }
+
         match app.login_frame.selected {
-            LoginSelectedBox::Username => {
-                UsernameBox::handle_key(app, key);
-            }
+            LoginSelectedBox::Username | LoginSelectedBox::Password => {}
             LoginSelectedBox::Name => {
                 app.login_frame.selected = LoginSelectedBox::Username;
             }
-            LoginSelectedBox::Password => {
-                PasswordBox::handle_key(app, key);
-            }
             LoginSelectedBox::ActionButton => {
                 LoginButtonBox::handle_key(app, key);
             }
@@ -55,7 +69,7 @@ impl LoginFrame {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let main = Block::default()
             .border_style(
                 Style::default()
@@ -124,165 +138,6 @@ impl LoginFrame {
     }
 }
 
-pub struct UsernameBox {
-    text: String,
-    locked: bool,
-}
-
-impl UsernameBox {
-    pub fn new() -> Self {
-        Self {
-            text: String::new(),
-            locked: false,
-        }
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match key.code {
-            KeyCode::Down => {
-                if !app.login_frame.username.locked {
-                    app.login_frame.selected = LoginSelectedBox::Password;
-                }
-            }
-            KeyCode::Enter => {
-                app.login_frame.username.locked = true;
-            }
-            KeyCode::Esc => {
-                app.login_frame.username.locked = false;
-            }
-            KeyCode::Backspace => {
-                if app.login_frame.username.locked {
-                    app.login_frame.username.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.login_frame.username.locked {
-                    app.login_frame.username.add_char(c);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let mut text = vec![Span::styled(
-            self.text.clone(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )];
-
-        if self.locked {
-            let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-            text.push(cursor);
-        }
-
-        let username = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Email"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(username, area);
-    }
-}
-
-pub struct PasswordBox {
-    text: String,
-    locked: bool,
-}
-
-impl PasswordBox {
-    pub fn new() -> Self {
-        Self {
-            text: String::new(),
-            locked: false,
-        }
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match key.code {
-            KeyCode::Down => {
-                if !app.login_frame.password.locked {
-                    app.login_frame.selected = LoginSelectedBox::ActionButton;
-                }
-            }
-            KeyCode::Up => {
-                if !app.login_frame.password.locked {
-                    app.login_frame.selected = LoginSelectedBox::Username;
-                }
-            }
-            KeyCode::Enter => {
-                app.login_frame.password.locked = true;
-            }
-            KeyCode::Esc => {
-                app.login_frame.password.locked = false;
-            }
-            KeyCode::Backspace => {
-                if app.login_frame.password.locked {
-                    app.login_frame.password.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.login_frame.password.locked {
-                    app.login_frame.password.add_char(c);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let mut stars = String::new();
-
-        for _ in self.text.chars() {
-            stars.push('*');
-        }
-
-        let text = vec![Span::styled(
-            stars,
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )];
-
-        let password = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Password"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(password, area);
-    }
-}
-
 pub struct LoginButtonBox {}
 
 impl LoginButtonBox {
@@ -293,8 +148,8 @@ impl LoginButtonBox {
     pub fn handle_key(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Enter => {
-                let email = std::mem::take(&mut app.login_frame.username.text);
-                let password = std::mem::take(&mut app.login_frame.password.text);
+                let email = app.login_frame.username.editor.take();
+                let password = app.login_frame.password.editor.take();
                 let res = app.login(&email, password);
 
                 match res {
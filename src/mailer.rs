@@ -0,0 +1,409 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use diesel::prelude::*;
+
+/// How long the queue is left alone after everything in it has been sent
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// How many times sending an email is tried before giving up on it
+const MAX_ATTEMPTS: i32 = 5;
+/// How many emails are taken from the queue at once
+const BATCH_SIZE: usize = 20;
+/// How long to wait for the mail server before giving up on it
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Added to the end of every email, since they're only sent to people who asked for them
+const FOOTER: &str = "You're getting this because email notifications are turned on. \
+You can turn them off from your profile in st-read.";
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Something that can deliver emails
+pub trait Mailer: Send {
+    fn send(&mut self, email: &Email) -> Result<(), String>;
+}
+
+/// Picks the mailer from the `MAILER` environment variable, which is one of:
+/// - `smtp://host[:port]` to send through a mail server. The connection isn't encrypted, so this
+///   should be a relay on the same machine or network, and logging in to it isn't supported
+/// - `file:path` to add the emails to the end of a file, for trying things out locally
+///
+/// `MAIL_FROM` is the address they are sent from. Without `MAILER`, emails are queued but
+/// never sent
+pub fn from_env() -> Result<Option<Box<dyn Mailer>>, String> {
+    let config = match env::var("MAILER") {
+        Ok(config) => config,
+        Err(_) => return Ok(None),
+    };
+    let from = env::var("MAIL_FROM").unwrap_or_else(|_| String::from("st-read@localhost"));
+
+    let mailer: Box<dyn Mailer> = if let Some(server) = config.strip_prefix("smtp://") {
+        Box::new(SmtpMailer::parse(server, from)?)
+    } else if let Some(path) = config.strip_prefix("file:") {
+        Box::new(FileMailer::file(Path::new(path), from)?)
+    } else {
+        // Printing them would draw over the app, so there is no stdout or stderr mailer
+        return Err(format!(
+            "MAILER should be smtp://host or file:path, not {}",
+            config
+        ));
+    };
+
+    Ok(Some(mailer))
+}
+
+/// Sends the queued emails from a thread of their own, for as long as the app is open
+pub fn start_queue(mut mailer: Box<dyn Mailer>) {
+    thread::spawn(move || loop {
+        // Keep going while there are emails left, in case there are more than a batch. If the
+        // database can't be reached, try again after a while
+        match send_queued(mailer.as_mut()) {
+            Ok(0) | Err(_) => thread::sleep(POLL_INTERVAL),
+            Ok(_) => {}
+        }
+    });
+}
+
+/// Sends a batch of the emails waiting in the queue, returning how many were tried. Emails that
+/// can't be sent are tried again later, up to `MAX_ATTEMPTS` times
+fn send_queued(mailer: &mut dyn Mailer) -> Result<usize, String> {
+    let connection = st_read::try_establish_connection().map_err(|e| e.to_string())?;
+
+    let mut tried = 0;
+    while tried < BATCH_SIZE
+        && connection
+            .transaction(|| send_next(&connection, mailer))
+            .map_err(|e| e.to_string())?
+    {
+        tried += 1;
+    }
+
+    Ok(tried)
+}
+
+/// Sends the oldest email in the queue, returning false if there wasn't one. This should be run
+/// in a transaction: the email's row stays locked until it is marked as sent, and other copies of
+/// the app skip locked rows, so that no email is sent twice
+fn send_next(connection: &PgConnection, mailer: &mut dyn Mailer) -> QueryResult<bool> {
+    use st_read::schema::emailqueue::dsl as queue_dsl;
+    use st_read::schema::users::dsl as users_dsl;
+
+    // Only the queue is locked, so the users table is checked with a subquery rather than a join
+    let active_users = users_dsl::users
+        .filter(users_dsl::deleted.eq(false))
+        .select(users_dsl::user_id);
+    let queued: Option<(i32, i32, String, String)> = queue_dsl::emailqueue
+        .filter(queue_dsl::sent_at.is_null())
+        .filter(queue_dsl::attempts.lt(MAX_ATTEMPTS))
+        .filter(queue_dsl::user_id.eq_any(active_users))
+        .order(queue_dsl::queued_at)
+        .select((
+            queue_dsl::email_id,
+            queue_dsl::user_id,
+            queue_dsl::subject,
+            queue_dsl::body,
+        ))
+        .for_update()
+        .skip_locked()
+        .first(connection)
+        .optional()?;
+
+    let (email_id, user_id, subject, body) = match queued {
+        Some(queued) => queued,
+        None => return Ok(false),
+    };
+
+    let email = Email {
+        to: users_dsl::users
+            .find(user_id)
+            .select(users_dsl::email)
+            .first(connection)?,
+        subject,
+        body: format!("{}\n\n--\n{}", body, FOOTER),
+    };
+    let row = queue_dsl::emailqueue.find(email_id);
+
+    match mailer.send(&email) {
+        Ok(()) => {
+            diesel::update(row)
+                .set(queue_dsl::sent_at.eq(diesel::dsl::now))
+                .execute(connection)?;
+        }
+        Err(e) => {
+            diesel::update(row)
+                .set((
+                    queue_dsl::attempts.eq(queue_dsl::attempts + 1),
+                    queue_dsl::last_error.eq(e),
+                ))
+                .execute(connection)?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Writes the whole email, headers and all, with `\r\n` line endings
+fn format_message(from: &str, email: &Email) -> String {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        from,
+        email.to,
+        encode_header(&email.subject)
+    );
+    for line in email.body.lines() {
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+
+    message
+}
+
+/// Prepares a message to be sent after DATA, ending it with a line with just a ".". Lines that
+/// start with a "." get another one, so that they aren't taken as the end
+fn dot_stuff(message: &str) -> String {
+    let mut stuffed = String::new();
+    for line in message.lines() {
+        if line.starts_with('.') {
+            stuffed.push('.');
+        }
+        stuffed.push_str(line);
+        stuffed.push_str("\r\n");
+    }
+    stuffed.push('.');
+
+    stuffed
+}
+
+/// Headers have to be ASCII, so anything else is base64 encoded
+fn encode_header(value: &str) -> String {
+    // Line breaks would start a new header
+    let value = value.replace(['\r', '\n'], " ");
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?utf-8?B?{}?=", base64::encode(value))
+    }
+}
+
+/// Writes emails to a file instead of sending them, for trying things out locally
+pub struct FileMailer {
+    output: Box<dyn Write + Send>,
+    from: String,
+}
+
+impl FileMailer {
+    /// Adds the emails to the end of the file, creating it if it doesn't exist
+    pub fn file(path: &Path, from: String) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+
+        Ok(Self {
+            output: Box::new(file),
+            from,
+        })
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&mut self, email: &Email) -> Result<(), String> {
+        let message = format_message(&self.from, email).replace("\r\n", "\n");
+
+        writeln!(self.output, "{}", message)
+            .and_then(|_| self.output.flush())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Sends emails through a mail server. This speaks plain SMTP without TLS, so the server should be
+/// a relay that can be trusted with the connection, like one on the same machine
+pub struct SmtpMailer {
+    host: String,
+    port: u16,
+    from: String,
+}
+
+impl SmtpMailer {
+    /// Reads `host[:port]`, where an IPv6 host is written in brackets, like `[::1]:25`
+    fn parse(server: &str, from: String) -> Result<Self, String> {
+        // Logging in would send the password unencrypted
+        if server.contains('@') {
+            return Err(String::from(
+                "st-read can't encrypt the connection to the mail server, so it won't send a \
+                 password over it. Use a relay that doesn't need one, like one on this machine",
+            ));
+        }
+
+        let address = server.trim_end_matches('/');
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !port.ends_with(']') => {
+                let port = port
+                    .parse()
+                    .map_err(|_| format!("{} isn't a valid SMTP port", port))?;
+                (host, port)
+            }
+            _ => (address, 25),
+        };
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+
+        if host.is_empty() {
+            return Err(String::from("The SMTP server needs a host"));
+        }
+
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            from,
+        })
+    }
+
+    /// Connects to the first of the server's addresses that answers within `SMTP_TIMEOUT`
+    fn connect(host: &str, port: u16) -> Result<TcpStream, String> {
+        let mut error = format!("{} has no addresses", host);
+        for address in (host, port).to_socket_addrs().map_err(|e| e.to_string())? {
+            match TcpStream::connect_timeout(&address, SMTP_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => error = e.to_string(),
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Sends one command and checks the reply is the one expected
+    fn command(
+        stream: &mut TcpStream,
+        reader: &mut impl BufRead,
+        command: &str,
+        expected: u16,
+    ) -> Result<(), String> {
+        stream
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        Self::expect(reader, expected)
+    }
+
+    /// Reads a reply, which may go over several lines, and checks its code
+    fn expect(reader: &mut impl BufRead, expected: u16) -> Result<(), String> {
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) => return Err(String::from("The mail server hung up")),
+                Ok(_) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+
+            let code: u16 = line
+                .get(..3)
+                .and_then(|code| code.parse().ok())
+                .ok_or_else(|| format!("The mail server replied with {}", line.trim_end()))?;
+
+            if code != expected {
+                return Err(format!("The mail server replied with {}", line.trim_end()));
+            }
+            // Replies continue with "250-" and end with "250 "
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&mut self, email: &Email) -> Result<(), String> {
+        // The email's row is locked until this returns, so nothing may wait on the server forever
+        let mut stream = Self::connect(&self.host, self.port)?;
+        stream
+            .set_read_timeout(Some(SMTP_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(SMTP_TIMEOUT)))
+            .map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+
+        Self::expect(&mut reader, 220)?;
+        Self::command(&mut stream, &mut reader, "EHLO localhost", 250)?;
+
+        let from = format!("MAIL FROM:<{}>", self.from);
+        Self::command(&mut stream, &mut reader, &from, 250)?;
+        let to = format!("RCPT TO:<{}>", email.to);
+        Self::command(&mut stream, &mut reader, &to, 250)?;
+        Self::command(&mut stream, &mut reader, "DATA", 354)?;
+
+        let message = dot_stuff(&format_message(&self.from, email));
+        Self::command(&mut stream, &mut reader, &message, 250)?;
+
+        Self::command(&mut stream, &mut reader, "QUIT", 221)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(server: &str) -> Result<(String, u16), String> {
+        SmtpMailer::parse(server, String::from("st-read@localhost"))
+            .map(|mailer| (mailer.host, mailer.port))
+    }
+
+    #[test]
+    fn parse_reads_the_host_and_port() {
+        assert_eq!(parse("localhost"), Ok((String::from("localhost"), 25)));
+        assert_eq!(
+            parse("mail.example.com:2525/"),
+            Ok((String::from("mail.example.com"), 2525))
+        );
+        assert_eq!(parse("[::1]:587"), Ok((String::from("::1"), 587)));
+        assert_eq!(parse("[::1]"), Ok((String::from("::1"), 25)));
+    }
+
+    #[test]
+    fn parse_rejects_bad_addresses() {
+        assert!(parse("localhost:smtp").is_err());
+        assert!(parse("localhost:70000").is_err());
+        assert!(parse(":25").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn parse_refuses_logins() {
+        // The password would be sent without encryption
+        assert!(parse("user:pass@mail.example.com:587").is_err());
+        assert!(parse("user:p@ss:word@localhost").is_err());
+        assert!(parse("user@localhost").is_err());
+    }
+
+    #[test]
+    fn dot_stuff_ends_the_message() {
+        assert_eq!(dot_stuff("Hello\r\nthere\r\n"), "Hello\r\nthere\r\n.");
+        assert_eq!(dot_stuff(""), ".");
+    }
+
+    #[test]
+    fn dot_stuff_doubles_leading_dots() {
+        assert_eq!(
+            dot_stuff(".\r\n..two\r\nmid.dle\r\n. end"),
+            "..\r\n...two\r\nmid.dle\r\n.. end\r\n."
+        );
+    }
+
+    #[test]
+    fn headers_are_kept_to_one_ascii_line() {
+        assert_eq!(encode_header("New reply\r\nBcc: x"), "New reply  Bcc: x");
+        assert_eq!(encode_header("Réponse"), "=?utf-8?B?UsOpcG9uc2U=?=");
+    }
+}
@@ -1,75 +1,103 @@
 use app::{run_app, App};
 use crossterm::{
+    cursor::Show,
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::{error::Error, panic::PanicInfo, sync::atomic::AtomicPtr, time::Duration};
-use std::{
-    io::{self, Stdout},
-    sync::atomic::Ordering,
-};
+use std::{error::Error, panic::PanicInfo, sync::atomic::AtomicBool, thread, time::Duration};
+use std::{io, sync::atomic::Ordering};
 use tui::{backend::CrosstermBackend, widgets::ListState, Terminal};
 
+mod account;
 mod app;
+mod author;
+mod communities;
+mod compose;
+mod compose_box;
 mod create_post;
+mod drafts;
+mod editor;
+mod find;
+mod highlight;
 mod initial;
+mod json;
+mod leaderboard;
+mod links;
 mod list;
 mod login;
+mod mailer;
+mod markdown;
+mod mentions;
+mod messages;
+mod notifications;
 mod posts_list;
 mod profile;
 mod register;
+mod saved;
+mod scroll;
+mod tags;
 mod viewing_post;
 
 //use diesel::prelude::*;
 
-static TERMINAL: AtomicPtr<Terminal<CrosstermBackend<Stdout>>> =
-    AtomicPtr::new(std::ptr::null_mut());
+/// Whether the terminal has been set up for the app, and needs fixing if we panic
+static TERMINAL: AtomicBool = AtomicBool::new(false);
 
 fn main() -> Result<(), Box<dyn Error>> {
     // set vars from the `.env` file
     dotenv::dotenv().ok();
 
-    // setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    TERMINAL.store(&mut terminal, Ordering::SeqCst);
-
     // If we panic (basically throw an exception), reset the terminal to avoid issues
     std::panic::set_hook(Box::new(|i| {
-        let ptr = TERMINAL.load(Ordering::SeqCst);
-        if ptr.is_null() {
-            println!("{}", i);
+        // A background thread that panics just stops, unless panicking aborts the whole app
+        if cfg!(panic = "unwind") && thread::current().name() != Some("main") {
             return;
         }
-        // hope for the best from rustc...
-        // woo everyone loves undefined behavior!
-        let term = unsafe { &mut *ptr };
-
-        //Fix terminal!
-        disable_raw_mode().unwrap();
-        execute!(
-            term.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )
-        .unwrap();
-        term.show_cursor().unwrap();
+
+        if TERMINAL.swap(false, Ordering::SeqCst) {
+            //Fix terminal! It is written to directly, since the UI thread owns the `Terminal`
+            let _ = disable_raw_mode();
+            let _ = execute!(
+                io::stdout(),
+                LeaveAlternateScreen,
+                DisableMouseCapture,
+                Show
+            );
+        }
         println!("{}", i);
 
+        // Don't lose what the user was writing. If saving panics too, the process aborts, but the
+        // terminal has already been fixed
+        drafts::save_unsaved();
+
         std::process::exit(1)
     }));
 
+    // Start sending the queued emails, if there's somewhere to send them
+    if let Some(mailer) = mailer::from_env()? {
+        mailer::start_queue(mailer);
+    }
+
+    // setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    TERMINAL.store(true, Ordering::SeqCst);
+
     // create app and run it
     let tick_rate = Duration::from_millis(250);
-    let app = App::new();
+    let mut app = App::new();
 
     // This funcion runs the event loop and renders the entire app.
     // It returns when the user has exited
-    let res = run_app(&mut terminal, app, tick_rate);
+    let res = run_app(&mut terminal, &mut app, tick_rate);
+
+    // Save the drafts even if the event loop failed
+    app.save_drafts();
+    TERMINAL.store(false, Ordering::SeqCst);
 
     // restore terminal
     disable_raw_mode()?;
@@ -84,11 +112,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         Err(err) => {
             println!("{:?}", err)
         }
-        Ok(_app) => {
+        Ok(()) => {
             //println!("{:#?}", app.posts_frame.posts.items);
         }
     }
-    TERMINAL.store(std::ptr::null_mut(), Ordering::SeqCst);
 
     Ok(())
 }
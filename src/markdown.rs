@@ -0,0 +1,569 @@
+use std::sync::OnceLock;
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Parser, Tag};
+use regex::Regex;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+use crate::highlight::{self, Palette};
+use crate::mentions;
+
+/// How the text of posts and comments is displayed
+#[derive(Debug, Clone, Copy)]
+pub struct TextOptions<'a> {
+    /// Shows the text as it was written instead of rendering the Markdown
+    pub raw: bool,
+    /// Picks colors for highlighted code that are readable on a dark background
+    pub dark_mode: bool,
+    /// The names of the users that can be mentioned, so that mentions of them stand out
+    pub names: &'a [String],
+}
+
+/// Renders the text of a post or comment using the options given.
+///
+/// The address of every link that is found is added to `links`, so that the links of a post and
+/// all of its comments can share one numbered list
+pub fn render_text(
+    text: &str,
+    options: TextOptions,
+    links: &mut Vec<String>,
+) -> Vec<Spans<'static>> {
+    let lines = if options.raw {
+        render_raw(text, links)
+    } else {
+        render(text, Palette::new(options.dark_mode), links)
+    };
+
+    mentions::highlight(lines, options.names)
+}
+
+/// Renders the Markdown in post and comment bodies into styled lines that can be drawn by a
+/// `Paragraph`. Regular text is left with the default style so that it picks up the style of the
+/// widget it is drawn in.
+///
+/// Each link is followed by a marker such as "[2]", which is its number in `links`
+pub fn render(text: &str, palette: Palette, links: &mut Vec<String>) -> Vec<Spans<'static>> {
+    let mut renderer = Renderer::new(palette, std::mem::take(links), true);
+
+    for event in Parser::new(text) {
+        renderer.event(event);
+    }
+    let (lines, found) = renderer.finish();
+
+    *links = found;
+    lines
+}
+
+/// Splits text on newlines without interpreting any Markdown, for when the raw text is wanted.
+/// URLs are still picked out so that they can be opened, but no markers are added to the text
+pub fn render_raw(text: &str, links: &mut Vec<String>) -> Vec<Spans<'static>> {
+    text.lines()
+        .map(|line| {
+            let mut spans = Vec::new();
+            let mut last = 0;
+
+            for url in url_regex().find_iter(line) {
+                if url.start() > last {
+                    spans.push(Span::raw(String::from(&line[last..url.start()])));
+                }
+                spans.push(Span::styled(String::from(url.as_str()), link_style()));
+                add_link(links, url.as_str());
+                last = url.end();
+            }
+            if last < line.len() || spans.is_empty() {
+                spans.push(Span::raw(String::from(&line[last..])));
+            }
+
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+/// The text of the rendered Markdown with all of the formatting removed, with lines separated by
+/// spaces
+pub fn plain_text(text: &str) -> String {
+    let mut renderer = Renderer::new(Palette::new(true), Vec::new(), false);
+    for event in Parser::new(text) {
+        renderer.event(event);
+    }
+
+    renderer
+        .finish()
+        .0
+        .iter()
+        .map(|line| {
+            line.0
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        })
+        .map(|line| String::from(line.trim()))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The style of the text of a link
+pub fn link_style() -> Style {
+    Style::default()
+        .fg(Color::LightBlue)
+        .add_modifier(Modifier::UNDERLINED)
+}
+
+/// The style of the "[1]" that follows a link and gives its number in the list of links
+pub fn marker_style() -> Style {
+    Style::default().fg(Color::LightBlue)
+}
+
+/// Matches the URLs written into the text without any Markdown around them
+fn url_regex() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+
+    // Punctuation at the end is left out, since it is almost always ending the sentence. Control
+    // characters are never part of a URL, and could end the escape code a hyperlink is drawn with
+    URL.get_or_init(|| Regex::new(r#"https?://[^\s<>"\p{Cc}]*[^\s<>"'.,;:!?)\]\p{Cc}]"#).unwrap())
+}
+
+/// Adds a link to the list, returning its number. A link that is already in the list keeps the
+/// number it was first given
+fn add_link(links: &mut Vec<String>, url: &str) -> usize {
+    match links.iter().position(|link| link == url) {
+        Some(i) => i + 1,
+        None => {
+            links.push(String::from(url));
+            links.len()
+        }
+    }
+}
+
+fn heading_style(level: HeadingLevel) -> Style {
+    let style = Style::default().add_modifier(Modifier::BOLD);
+
+    match level {
+        HeadingLevel::H1 => style
+            .fg(Color::LightCyan)
+            .add_modifier(Modifier::UNDERLINED),
+        HeadingLevel::H2 => style.fg(Color::LightCyan),
+        _ => style.fg(Color::Cyan),
+    }
+}
+
+fn quote_style() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+/// Text that starts every line inside of a block quote or list item
+struct Indent {
+    /// Used once on the first line, such as the bullet of a list item
+    first: Option<String>,
+    /// Used on every line after the first
+    rest: String,
+    style: Style,
+}
+
+/// Turns the stream of Markdown events into lines of spans
+struct Renderer {
+    lines: Vec<Spans<'static>>,
+    line: Vec<Span<'static>>,
+    /// Inline styles such as bold and italic, which are combined for the text inside of them
+    styles: Vec<Style>,
+    indents: Vec<Indent>,
+    /// The next number of each ordered list that we are inside of, or `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    palette: Palette,
+    /// The language and text of the code block that we are inside of, which is highlighted all
+    /// at once when the block ends
+    code_block: Option<(String, String)>,
+    /// The address of the link that we are inside of
+    link: Option<String>,
+    links: Vec<String>,
+    /// Whether links are followed by their number
+    markers: bool,
+}
+
+impl Renderer {
+    fn new(palette: Palette, links: Vec<String>, markers: bool) -> Self {
+        Self {
+            lines: Vec::new(),
+            line: Vec::new(),
+            styles: Vec::new(),
+            indents: Vec::new(),
+            lists: Vec::new(),
+            palette,
+            code_block: None,
+            link: None,
+            links,
+            markers,
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |style, s| style.patch(*s))
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.styles.push(style);
+    }
+
+    fn pop_style(&mut self) {
+        self.styles.pop();
+    }
+
+    /// Adds text to the current line, starting it with the indents if it is empty
+    fn push_span(&mut self, span: Span<'static>) {
+        if self.line.is_empty() {
+            for indent in self.indents.iter_mut() {
+                let text = indent.first.take().unwrap_or_else(|| indent.rest.clone());
+                self.line.push(Span::styled(text, indent.style));
+            }
+        }
+
+        self.line.push(span);
+    }
+
+    /// Ends the current line, if anything has been written to it
+    fn flush(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.lines.push(Spans::from(line));
+        }
+    }
+
+    /// Separates a new block from whatever came before it with a blank line
+    fn start_block(&mut self) {
+        self.flush();
+
+        let last_is_blank = self
+            .lines
+            .last()
+            .map(|line| line.0.iter().all(|span| span.content.trim().is_empty()))
+            .unwrap_or(true);
+
+        if !last_is_blank {
+            // Keep the bars of any block quotes going through the blank line
+            let quotes: String = self
+                .indents
+                .iter()
+                .filter(|indent| indent.first.is_none())
+                .map(|indent| indent.rest.as_str())
+                .collect();
+
+            self.lines.push(Spans::from(Span::styled(
+                String::from(quotes.trim_end()),
+                quote_style(),
+            )));
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some((_, code)) = &mut self.code_block {
+            code.push_str(text);
+            return;
+        }
+
+        let style = self.style();
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.flush();
+            }
+
+            // Bare URLs are turned into links, unless they are already the text of one
+            let mut last = 0;
+            if self.link.is_none() {
+                for url in url_regex().find_iter(line) {
+                    if url.start() > last {
+                        self.push_span(Span::styled(String::from(&line[last..url.start()]), style));
+                    }
+                    self.push_span(Span::styled(
+                        String::from(url.as_str()),
+                        style.patch(link_style()),
+                    ));
+                    self.marker(url.as_str());
+                    last = url.end();
+                }
+            }
+
+            if last < line.len() {
+                self.push_span(Span::styled(String::from(&line[last..]), style));
+            }
+        }
+    }
+
+    /// Numbers a link, and shows the number after it
+    fn marker(&mut self, url: &str) {
+        let number = add_link(&mut self.links, url);
+
+        if self.markers {
+            self.push_span(Span::styled(format!("[{}]", number), marker_style()));
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) | Event::Html(text) => self.text(&text),
+            Event::Code(code) => {
+                let style = self.style().patch(self.palette.code());
+                self.push_span(Span::styled(String::from(code.as_ref()), style));
+            }
+            // Posts are written with the expectation that a newline starts a new line, so soft
+            // breaks are kept rather than joined into a single paragraph
+            Event::SoftBreak | Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.start_block();
+                self.push_span(Span::styled("─".repeat(20), quote_style()));
+                self.flush();
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = if checked { "[x] " } else { "[ ] " };
+                self.push_span(Span::raw(marker));
+            }
+            Event::FootnoteReference(name) => {
+                self.push_span(Span::raw(format!("[{}]", name)));
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                // Paragraphs in list items stay next to the bullet
+                if !matches!(self.indents.last(), Some(Indent { first: Some(_), .. })) {
+                    self.start_block();
+                }
+            }
+            Tag::Heading(level, _, _) => {
+                self.start_block();
+                self.push_style(heading_style(level));
+            }
+            Tag::BlockQuote => {
+                self.start_block();
+                self.indents.push(Indent {
+                    first: None,
+                    rest: String::from("│ "),
+                    style: quote_style(),
+                });
+                self.push_style(Style::default().add_modifier(Modifier::ITALIC));
+            }
+            Tag::CodeBlock(kind) => {
+                self.start_block();
+
+                // Label the block with its language
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => String::from(lang.as_ref()),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                if !lang.is_empty() {
+                    self.push_span(Span::styled(format!("  {}", lang), quote_style()));
+                    self.flush();
+                }
+
+                self.code_block = Some((lang, String::new()));
+                self.indents.push(Indent {
+                    first: None,
+                    rest: String::from("  "),
+                    style: Style::default(),
+                });
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else {
+                    self.flush();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => String::from("• "),
+                };
+                self.indents.push(Indent {
+                    rest: " ".repeat(bullet.chars().count()),
+                    first: Some(bullet),
+                    style: Style::default().fg(Color::Cyan),
+                });
+            }
+            Tag::Emphasis => self.push_style(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => {
+                self.push_style(Style::default().add_modifier(Modifier::CROSSED_OUT))
+            }
+            Tag::Link(_, url, _) | Tag::Image(_, url, _) => {
+                self.push_style(link_style());
+                self.link = Some(String::from(url.as_ref()));
+            }
+            Tag::FootnoteDefinition(name) => {
+                self.start_block();
+                self.push_span(Span::raw(format!("[{}]: ", name)));
+            }
+            Tag::Table(_) | Tag::TableHead | Tag::TableRow | Tag::TableCell => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::FootnoteDefinition(_) => self.flush(),
+            Tag::Heading(..) => {
+                self.flush();
+                self.pop_style();
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.indents.pop();
+                self.pop_style();
+            }
+            Tag::CodeBlock(_) => {
+                self.flush();
+
+                if let Some((lang, code)) = self.code_block.take() {
+                    let code = code.strip_suffix('\n').unwrap_or(&code);
+
+                    // Every line of the block is kept, including blank ones
+                    for line in highlight::highlight(&lang, code, &self.palette) {
+                        self.push_span(Span::raw(""));
+                        self.line.extend(line);
+                        self.flush();
+                    }
+                }
+
+                self.indents.pop();
+            }
+            Tag::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Tag::Item => {
+                self.flush();
+
+                // An empty item still shows its bullet
+                if let Some(Indent {
+                    first: Some(bullet),
+                    style,
+                    ..
+                }) = self.indents.pop()
+                {
+                    self.push_span(Span::styled(bullet, style));
+                    self.flush();
+                }
+            }
+            Tag::Link(..) | Tag::Image(..) => {
+                self.pop_style();
+                if let Some(url) = self.link.take() {
+                    self.marker(&url);
+                }
+            }
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => self.pop_style(),
+            Tag::Table(_) | Tag::TableHead | Tag::TableRow | Tag::TableCell => {}
+        }
+    }
+
+    /// Returns the rendered lines along with the list of links
+    fn finish(mut self) -> (Vec<Spans<'static>>, Vec<String>) {
+        self.flush();
+        (self.lines, self.links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of each line, without the styles
+    fn text_of(lines: &[Spans]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.0.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    fn rendered(text: &str) -> (Vec<String>, Vec<String>) {
+        let mut links = Vec::new();
+        let lines = render(text, Palette::new(true), &mut links);
+        (text_of(&lines), links)
+    }
+
+    fn urls(text: &str) -> Vec<&str> {
+        url_regex()
+            .find_iter(text)
+            .map(|url| url.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn nested_lists_are_indented_under_their_bullets() {
+        let (lines, _) = rendered("- one\n  1. first\n  2. second\n- two");
+        assert_eq!(lines, ["• one", "  1. first", "  2. second", "• two"]);
+    }
+
+    #[test]
+    fn code_blocks_keep_every_line() {
+        let (lines, links) = rendered("before\n\n```rust\nlet a = 1;\n\nhttps://example.com\n```");
+        assert_eq!(
+            lines,
+            [
+                "before",
+                "",
+                "  rust",
+                "  let a = 1;",
+                "  ",
+                "  https://example.com"
+            ]
+        );
+        // Addresses in code are left as code
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn links_are_numbered_once_each() {
+        let (lines, links) =
+            rendered("[a](https://a.com) https://b.com and [again](https://a.com)");
+        assert_eq!(lines, ["a[1] https://b.com[2] and again[1]"]);
+        assert_eq!(links, ["https://a.com", "https://b.com"]);
+    }
+
+    #[test]
+    fn bare_urls_leave_out_the_punctuation_after_them() {
+        assert_eq!(urls("see https://a.com/x."), ["https://a.com/x"]);
+        assert_eq!(urls("(https://a.com/x), or"), ["https://a.com/x"]);
+        assert_eq!(urls("\"https://a.com/?q=1\"!"), ["https://a.com/?q=1"]);
+        assert_eq!(
+            urls("https://a.com/a.b/c?d=e;f"),
+            ["https://a.com/a.b/c?d=e;f"]
+        );
+        assert_eq!(urls("ftp://a.com"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn urls_stop_at_control_characters() {
+        assert_eq!(
+            urls("https://a.com/\u{1b}]8;;https://evil.com"),
+            ["https://a.com/", "https://evil.com"]
+        );
+        assert_eq!(urls("https://a.com\u{7}x"), ["https://a.com"]);
+    }
+
+    #[test]
+    fn raw_text_is_left_as_written() {
+        let mut links = Vec::new();
+        let lines = render_raw("# Title\n**bold** https://a.com", &mut links);
+        assert_eq!(text_of(&lines), ["# Title", "**bold** https://a.com"]);
+        assert_eq!(links, ["https://a.com"]);
+    }
+
+    #[test]
+    fn plain_text_has_no_formatting() {
+        assert_eq!(
+            plain_text("# Hi\n\n*some* `code` and [a link](https://a.com)"),
+            "Hi some code and a link"
+        );
+    }
+}
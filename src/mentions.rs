@@ -0,0 +1,406 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, Paragraph};
+use tui::Frame;
+
+use crate::editor::Editor;
+
+/// How many names are suggested at once
+const MAX_SUGGESTIONS: usize = 5;
+/// The longest a user's name can be, past which an @ isn't treated as the start of a mention
+const MAX_NAME_LENGTH: usize = 40;
+
+/// Loads the names of everyone who can be mentioned
+pub fn load_names() -> Vec<String> {
+    use st_read::schema::users::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::users
+        .filter(dsl::deleted.eq(false))
+        .select(dsl::name)
+        .order(dsl::name)
+        .load(&connection)
+        .unwrap_or_default()
+}
+
+pub fn mention_style() -> Style {
+    Style::default()
+        .fg(Color::LightBlue)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Whether a character would make a name part of a longer word
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Finds every @ followed by one of the names, returning where each mention is in the text. When
+/// more than one name fits, such as "Pablo" and "Pablo Lopez", the longest is used
+pub fn find_mentions(text: &str, names: &[String]) -> Vec<Range<usize>> {
+    let mut mentions: Vec<Range<usize>> = Vec::new();
+
+    for (at, _) in text.match_indices('@') {
+        // An @ in the middle of a word is part of something else, like an email address
+        if text[..at].chars().next_back().is_some_and(is_word) {
+            continue;
+        }
+
+        let rest = &text[at + 1..];
+        let longest = names
+            .iter()
+            .filter(|name| {
+                rest.get(..name.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(name))
+                    && !rest[name.len()..].chars().next().is_some_and(is_word)
+            })
+            .map(|name| name.len())
+            .max();
+
+        if let Some(len) = longest {
+            mentions.push(at..at + 1 + len);
+        }
+    }
+
+    mentions
+}
+
+/// Restyles the mentions in rendered text. A mention split between differently styled spans is
+/// left alone
+pub fn highlight(lines: Vec<Spans<'static>>, names: &[String]) -> Vec<Spans<'static>> {
+    if names.is_empty() {
+        return lines;
+    }
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut spans = Vec::new();
+
+            for span in line.0 {
+                let mentions = find_mentions(&span.content, names);
+                if mentions.is_empty() {
+                    spans.push(span);
+                    continue;
+                }
+
+                let mut last = 0;
+                for mention in mentions {
+                    if mention.start > last {
+                        spans.push(Span::styled(
+                            span.content[last..mention.start].to_owned(),
+                            span.style,
+                        ));
+                    }
+                    spans.push(Span::styled(
+                        span.content[mention.clone()].to_owned(),
+                        span.style.patch(mention_style()),
+                    ));
+                    last = mention.end;
+                }
+                if last < span.content.len() {
+                    spans.push(Span::styled(span.content[last..].to_owned(), span.style));
+                }
+            }
+
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+/// The part of a name typed after an @ just before the cursor, if a mention is being typed
+fn partial_mention(before_cursor: &str) -> Option<&str> {
+    let at = before_cursor.rfind('@')?;
+    if before_cursor[..at].chars().next_back().is_some_and(is_word) {
+        return None;
+    }
+
+    let partial = &before_cursor[at + 1..];
+    if partial.chars().count() > MAX_NAME_LENGTH {
+        return None;
+    }
+
+    Some(partial)
+}
+
+/// Suggests the names of users while a mention is typed in an editor
+pub struct Mentions {
+    /// Everyone who can be mentioned
+    names: Vec<String>,
+    /// The names starting with what has been typed after the @
+    suggestions: Vec<String>,
+    selected: usize,
+    /// How many bytes have been typed after the @
+    partial_len: usize,
+    /// Where the @ of a mention is once Esc has been pressed on its suggestions, so that they
+    /// stay hidden until the cursor leaves it
+    dismissed: Option<usize>,
+}
+
+impl Mentions {
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            suggestions: Vec::new(),
+            selected: 0,
+            partial_len: 0,
+            dismissed: None,
+        }
+    }
+
+    /// Loads the names again, to pick up new users
+    pub fn load(&mut self) {
+        self.names = load_names();
+        self.suggestions.clear();
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Finds the names to suggest for what is before the editor's cursor. This should be called
+    /// after every key the editor handles, but not after the keys handled by `handle_key`
+    pub fn update(&mut self, editor: &Editor) {
+        let selected = self.suggestions.get(self.selected).cloned();
+        self.suggestions.clear();
+        self.selected = 0;
+
+        let partial = match partial_mention(editor.before_cursor()) {
+            Some(partial) => partial,
+            None => {
+                self.dismissed = None;
+                return;
+            }
+        };
+
+        let at = editor.cursor() - partial.len() - 1;
+        if self.dismissed == Some(at) {
+            return;
+        }
+        self.dismissed = None;
+
+        let partial_lower = partial.to_lowercase();
+        self.partial_len = partial.len();
+        self.suggestions = self
+            .names
+            .iter()
+            .filter(|name| name.to_lowercase().starts_with(&partial_lower))
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect();
+
+        // Keep the same name picked while more of it is typed
+        if let Some(i) =
+            selected.and_then(|selected| self.suggestions.iter().position(|name| *name == selected))
+        {
+            self.selected = i;
+        }
+    }
+
+    /// Handles the keys for picking a suggestion while there are any: Up and Down to move, Tab to
+    /// finish the name, and Esc to stop suggesting for this mention. Returns whether the key was
+    /// used
+    pub fn handle_key(&mut self, editor: &mut Editor, key: KeyEvent) -> bool {
+        if self.suggestions.is_empty() {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % self.suggestions.len();
+            }
+            KeyCode::Up => {
+                self.selected =
+                    (self.selected + self.suggestions.len() - 1) % self.suggestions.len();
+            }
+            KeyCode::Tab => {
+                let name = format!("{} ", self.suggestions[self.selected]);
+                editor.replace_before_cursor(self.partial_len, &name);
+                self.suggestions.clear();
+            }
+            KeyCode::Esc => {
+                self.dismissed = Some(editor.cursor() - self.partial_len - 1);
+                self.suggestions.clear();
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Draws the suggestions over the bottom of the editor's area
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        if self.suggestions.is_empty() {
+            return;
+        }
+
+        let longest = self
+            .suggestions
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = (self.suggestions.len() as u16 + 2).min(area.height);
+        let width = (longest as u16 + 6)
+            .max(20)
+            .min(area.width.saturating_sub(2));
+        let popup = Rect {
+            x: area.x + 1,
+            y: area.bottom().saturating_sub(height + 1).max(area.y),
+            width,
+            height,
+        };
+
+        let lines: Vec<Spans> = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                if i == self.selected {
+                    Spans::from(Span::styled(
+                        format!(">> @{}", name),
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Spans::from(Span::styled(
+                        format!("   @{}", name),
+                        Style::default().fg(Color::Gray),
+                    ))
+                }
+            })
+            .collect();
+
+        let suggestions = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(mention_style())
+                .title("Mention (Tab)"),
+        );
+
+        f.render_widget(Clear, popup);
+        f.render_widget(suggestions, popup);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    fn found<'a>(text: &'a str, names: &[String]) -> Vec<&'a str> {
+        find_mentions(text, names)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    fn type_text(editor: &mut Editor, mentions: &mut Mentions, text: &str) {
+        for c in text.chars() {
+            editor.insert_char(c);
+            mentions.update(editor);
+        }
+    }
+
+    fn press(editor: &mut Editor, mentions: &mut Mentions, code: KeyCode) -> bool {
+        mentions.handle_key(editor, KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn find_mentions_matches_whole_names() {
+        let names = names(&["Ana", "Bo"]);
+        assert_eq!(found("hi @Ana and @bo!", &names), ["@Ana", "@bo"]);
+        // Longer words and email addresses aren't mentions
+        assert!(found("@Anastasia @Bob", &names).is_empty());
+        assert!(found("me@Ana.com", &names).is_empty());
+        assert!(found("@Ana_b", &names).is_empty());
+    }
+
+    #[test]
+    fn find_mentions_prefers_the_longest_name() {
+        let names = names(&["Pablo", "Pablo Lopez", "Pablo L"]);
+        assert_eq!(
+            found("@Pablo Lopez, @Pablo Lo, @Pablo", &names),
+            ["@Pablo Lopez", "@Pablo", "@Pablo"]
+        );
+    }
+
+    #[test]
+    fn find_mentions_handles_multibyte_names() {
+        let names = names(&["Zoë", "日本語", "Ana"]);
+        assert_eq!(
+            found("@Zoë, @日本語 y @Ana", &names),
+            ["@Zoë", "@日本語", "@Ana"]
+        );
+        // A multibyte letter after the name makes it a longer word
+        assert!(found("@Anaé", &names).is_empty());
+        // The end of the text can fall inside a multibyte name without panicking
+        assert!(found("@日本", &names).is_empty());
+        assert!(found("ü@Ana", &names).is_empty());
+    }
+
+    #[test]
+    fn partial_mention_is_the_text_after_the_last_at() {
+        assert_eq!(partial_mention("hello @Pa"), Some("Pa"));
+        assert_eq!(partial_mention("@"), Some(""));
+        assert_eq!(partial_mention("@Ana @Zo"), Some("Zo"));
+        assert_eq!(partial_mention("@Pablo Lo"), Some("Pablo Lo"));
+        assert_eq!(partial_mention("@Zoë"), Some("Zoë"));
+        assert_eq!(partial_mention("no mention"), None);
+        assert_eq!(partial_mention("me@example"), None);
+        assert_eq!(partial_mention(&format!("@{}", "a".repeat(41))), None);
+    }
+
+    #[test]
+    fn arrows_move_through_the_suggestions() {
+        let mut editor = Editor::multi_line();
+        let mut mentions = Mentions::new();
+        mentions.names = names(&["Pablo", "Paula", "Peter"]);
+
+        type_text(&mut editor, &mut mentions, "hi @P");
+        assert_eq!(mentions.suggestions.len(), 3);
+        assert!(press(&mut editor, &mut mentions, KeyCode::Down));
+        assert!(press(&mut editor, &mut mentions, KeyCode::Down));
+        assert_eq!(mentions.selected, 2);
+        assert!(press(&mut editor, &mut mentions, KeyCode::Up));
+        assert_eq!(mentions.selected, 1);
+
+        // Typing more keeps the same name picked
+        type_text(&mut editor, &mut mentions, "a");
+        assert_eq!(mentions.suggestions[mentions.selected], "Paula");
+
+        assert!(press(&mut editor, &mut mentions, KeyCode::Tab));
+        assert_eq!(editor.text(), "hi @Paula ");
+        assert!(mentions.suggestions.is_empty());
+    }
+
+    #[test]
+    fn esc_hides_the_suggestions_until_the_cursor_leaves_the_mention() {
+        let mut editor = Editor::multi_line();
+        let mut mentions = Mentions::new();
+        mentions.names = names(&["Pablo", "Paula"]);
+
+        type_text(&mut editor, &mut mentions, "@Pa");
+        assert!(press(&mut editor, &mut mentions, KeyCode::Esc));
+        assert!(mentions.suggestions.is_empty());
+
+        // With nothing suggested, Esc is left for the box to handle
+        assert!(!press(&mut editor, &mut mentions, KeyCode::Esc));
+
+        type_text(&mut editor, &mut mentions, "b");
+        assert!(mentions.suggestions.is_empty());
+
+        type_text(&mut editor, &mut mentions, "lo @P");
+        assert_eq!(mentions.suggestions.len(), 2);
+    }
+}
@@ -0,0 +1,723 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use diesel::prelude::*;
+use st_read::models::{Conversation, Message};
+use st_read::util::time_ago;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView};
+use crate::compose::Compose;
+use crate::compose_box::ComposeBox;
+use crate::drafts::{DraftCopy, DraftOf};
+use crate::list::StatefulList;
+use crate::markdown::{self, TextOptions};
+use crate::scroll::{wrapped_height, ScrollState};
+
+/// The longest a message can be, in characters
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Everyone the user with the id `$1` has messaged or been messaged by, with the last message
+/// sent either way, most recent first
+const LOAD_CONVERSATIONS: &str = "
+SELECT u.user_id, u.name, m.text AS last_text, m.sent_at AS last_sent,
+    (SELECT COUNT(*) FROM Message r
+     WHERE r.sender_id = u.user_id AND r.recipient_id = $1 AND NOT r.is_read) AS unread,
+    EXISTS (SELECT 1 FROM BlockedSender b WHERE b.user_id = $1 AND b.blocked_id = u.user_id)
+        AS blocked
+FROM (
+    SELECT DISTINCT ON (other_id)
+        CASE WHEN sender_id = $1 THEN recipient_id ELSE sender_id END AS other_id, text, sent_at
+    FROM Message
+    WHERE sender_id = $1 OR recipient_id = $1
+    ORDER BY other_id, sent_at DESC, message_id DESC
+) m
+    JOIN Users u ON u.user_id = m.other_id
+ORDER BY m.sent_at DESC";
+
+/// Counts the messages sent to the user that they haven't read yet
+pub fn count_unread(user_id: i32) -> i64 {
+    use st_read::schema::message::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::message
+        .filter(dsl::recipient_id.eq(user_id))
+        .filter(dsl::is_read.eq(false))
+        .count()
+        .get_result(&connection)
+        .unwrap_or(0)
+}
+
+fn load_conversations(user_id: i32) -> QueryResult<Vec<Conversation>> {
+    use diesel::sql_types::Integer;
+    let connection = st_read::establish_connection();
+
+    diesel::sql_query(LOAD_CONVERSATIONS)
+        .bind::<Integer, _>(user_id)
+        .load(&connection)
+}
+
+/// Loads every message sent between two users, oldest first
+fn load_messages(user_id: i32, other_id: i32) -> QueryResult<Vec<Message>> {
+    use st_read::schema::message::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::message
+        .filter(
+            (dsl::sender_id
+                .eq(user_id)
+                .and(dsl::recipient_id.eq(other_id)))
+            .or(dsl::sender_id
+                .eq(other_id)
+                .and(dsl::recipient_id.eq(user_id))),
+        )
+        .order((dsl::sent_at, dsl::message_id))
+        .load(&connection)
+}
+
+/// Marks the messages sent to the user by someone else as read
+fn mark_read(connection: &PgConnection, user_id: i32, sender_id: i32) -> QueryResult<usize> {
+    use st_read::schema::message::dsl;
+
+    diesel::update(
+        dsl::message
+            .filter(dsl::recipient_id.eq(user_id))
+            .filter(dsl::sender_id.eq(sender_id))
+            .filter(dsl::is_read.eq(false)),
+    )
+    .set(dsl::is_read.eq(true))
+    .execute(connection)
+}
+
+/// Sends a message, which fails if the recipient has blocked the sender
+fn send_message(sender_id: i32, recipient_id: i32, text: &str) -> QueryResult<()> {
+    use st_read::schema::message::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::insert_into(dsl::message)
+        .values((
+            dsl::sender_id.eq(sender_id),
+            dsl::recipient_id.eq(recipient_id),
+            dsl::text.eq(text),
+        ))
+        .execute(&connection)
+        .map(|_| ())
+}
+
+/// Blocks or unblocks a sender. Their messages are marked as read when they are blocked, since
+/// they aren't wanted
+fn set_blocked(user_id: i32, sender_id: i32, blocked: bool) -> QueryResult<()> {
+    use st_read::schema::blockedsender::dsl;
+    let connection = st_read::establish_connection();
+
+    if blocked {
+        connection.transaction(|| {
+            diesel::insert_into(dsl::blockedsender)
+                .values((dsl::user_id.eq(user_id), dsl::blocked_id.eq(sender_id)))
+                .on_conflict_do_nothing()
+                .execute(&connection)?;
+            mark_read(&connection, user_id, sender_id)
+        })?;
+    } else {
+        diesel::delete(
+            dsl::blockedsender
+                .filter(dsl::user_id.eq(user_id))
+                .filter(dsl::blocked_id.eq(sender_id)),
+        )
+        .execute(&connection)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that a message isn't empty or too long, returning it without surrounding whitespace
+fn validate_message(text: &str) -> Result<&str, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(String::from("Messages can't be empty"));
+    }
+    let length = text.chars().count();
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(format!(
+            "Messages can be at most {} characters, this one is {}",
+            MAX_MESSAGE_LENGTH, length
+        ));
+    }
+
+    Ok(text)
+}
+
+/// Enum for which box is selected in the messages view
+#[derive(Debug, Clone, Copy)]
+enum SelectedBox {
+    Conversations,
+    Message,
+}
+
+/// The user's conversations on the left, and the messages of the selected one on the right with
+/// a box to write the next message in
+pub struct MessagesFrame {
+    conversations: StatefulList<Conversation>,
+    /// The messages of the selected conversation, oldest first
+    messages: Vec<Message>,
+    selected_box: SelectedBox,
+    message_box: MessageBox,
+    scroll: ScrollState,
+    /// Scrolls to the newest message the next time the messages are drawn
+    scroll_to_bottom: bool,
+    /// Why the conversations couldn't be loaded or a message couldn't be sent
+    message: Option<String>,
+}
+
+impl MessagesFrame {
+    pub fn new() -> Self {
+        Self {
+            conversations: StatefulList::with_items(Vec::new()),
+            messages: Vec::new(),
+            selected_box: SelectedBox::Conversations,
+            message_box: MessageBox::new(),
+            scroll: ScrollState::new(),
+            scroll_to_bottom: false,
+            message: None,
+        }
+    }
+
+    /// Loads the user's conversations, opening the one with `other_id` if given, even if they
+    /// haven't messaged each other yet. Otherwise the conversation that was open stays open
+    pub fn load(&mut self, user_id: i32, other_id: Option<i32>) {
+        let open = other_id.or_else(|| self.other_id());
+        // A new conversation stays at the top until the first message is sent
+        let started = match other_id {
+            Some(other_id) => new_conversation(user_id, other_id),
+            None => self
+                .conversations
+                .selected_item()
+                .filter(|c| c.last_sent.is_none())
+                .cloned(),
+        };
+
+        let mut conversations = match load_conversations(user_id) {
+            Ok(conversations) => {
+                self.message = None;
+                conversations
+            }
+            Err(e) => {
+                self.message = Some(format!("Couldn't load your messages: {}", e));
+                Vec::new()
+            }
+        };
+
+        if let Some(started) = started {
+            if !conversations.iter().any(|c| c.user_id == started.user_id) {
+                conversations.insert(0, started);
+            }
+        }
+
+        self.conversations = StatefulList::highlighted(conversations);
+
+        let selected = open
+            .and_then(|id| {
+                self.conversations
+                    .items
+                    .iter()
+                    .position(|c| c.user_id == id)
+            })
+            .or(if self.conversations.items.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        self.conversations.select(selected);
+
+        if selected.is_none() {
+            self.selected_box = SelectedBox::Conversations;
+        }
+        self.open_selected(user_id);
+    }
+
+    /// Opens the conversation with another user, ready to write to them
+    pub fn start_conversation(&mut self, user_id: i32, other_id: i32) {
+        self.load(user_id, Some(other_id));
+        if self.other_id() == Some(other_id) {
+            self.selected_box = SelectedBox::Message;
+        }
+    }
+
+    /// Loads the names that can be mentioned in a message again
+    pub fn load_names(&mut self) {
+        self.message_box.compose.load_names();
+    }
+
+    pub fn message_text(&self) -> &str {
+        self.message_box.compose.text()
+    }
+
+    pub fn set_message_text(&mut self, text: &str) {
+        self.message_box.compose.set_text(text);
+    }
+
+    /// Saves the message being written as a draft, or deletes the draft once it is cleared
+    pub fn save_draft(&mut self, user_id: i32) {
+        if let Some(to) = self.message_box.to {
+            self.message_box
+                .compose
+                .save_draft(user_id, DraftOf::Message(to));
+        }
+    }
+
+    /// Copies the message for saving as a draft later
+    pub fn draft_copy(&self) -> Option<DraftCopy> {
+        let to = self.message_box.to?;
+        self.message_box.compose.draft_copy(DraftOf::Message(to))
+    }
+
+    /// Clears the message if it was being written to a draft that has been discarded
+    pub fn forget_draft(&mut self, draft_id: i32) {
+        self.message_box.compose.forget_draft(draft_id);
+    }
+
+    /// The user on the other side of the open conversation
+    fn other_id(&self) -> Option<i32> {
+        self.conversations.selected_item().map(|c| c.user_id)
+    }
+
+    /// Loads the messages of the selected conversation and marks the ones sent to the user as
+    /// read
+    fn open_selected(&mut self, user_id: i32) {
+        // The message being written belongs to the conversation it was written in, so it is put
+        // away as a draft and the draft of the one being opened is brought back
+        let other_id = self.other_id();
+        if self.message_box.to != other_id {
+            self.save_draft(user_id);
+            self.message_box.compose.clear();
+            self.message_box.to = other_id;
+            if let Some(other_id) = other_id {
+                self.message_box
+                    .compose
+                    .restore_draft(user_id, DraftOf::Message(other_id));
+            }
+        }
+
+        let other_id = match other_id {
+            Some(other_id) => other_id,
+            None => {
+                self.messages.clear();
+                return;
+            }
+        };
+
+        let previous = self.messages.last().map(|message| message.message_id);
+        self.messages = load_messages(user_id, other_id).unwrap_or_default();
+
+        // Only follow along when something new has come in, so that reading back isn't
+        // interrupted
+        let latest = self.messages.last().map(|message| message.message_id);
+        if previous != latest {
+            self.scroll_to_bottom = true;
+        }
+
+        if let Some(conversation) = self
+            .conversations
+            .items
+            .iter_mut()
+            .find(|c| c.user_id == other_id)
+        {
+            conversation.unread = 0;
+        }
+        // Having them on the screen is enough to have read them, so a failure isn't worth showing
+        let _ = mark_read(&st_read::establish_connection(), user_id, other_id);
+    }
+
+    /// Moves to another conversation
+    fn select(app: &mut App, next: bool) {
+        let frame = &mut app.messages_frame;
+        if frame.conversations.items.is_empty() {
+            return;
+        }
+
+        if next {
+            frame.conversations.next();
+        } else {
+            frame.conversations.previous();
+        }
+        frame.messages.clear();
+        frame.scroll.reset();
+        frame.open_selected(app.profile_frame.user_id);
+        app.refresh_unread();
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        match app.messages_frame.selected_box {
+            SelectedBox::Conversations => Self::handle_conversations_key(app, key),
+            SelectedBox::Message => MessageBox::handle_key(app, key),
+        }
+    }
+
+    fn handle_conversations_key(app: &mut App, key: KeyEvent) {
+        let user_id = app.profile_frame.user_id;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                Self::select(app, true);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                Self::select(app, false);
+            }
+            KeyCode::Enter | KeyCode::Right if app.messages_frame.other_id().is_some() => {
+                app.messages_frame.selected_box = SelectedBox::Message;
+            }
+            KeyCode::Char('a') => {
+                if let Some(other_id) = app.messages_frame.other_id() {
+                    app.show_author(other_id);
+                }
+            }
+            KeyCode::Char('b') => {
+                let conversation = match app.messages_frame.conversations.selected_item() {
+                    Some(conversation) => conversation,
+                    None => return,
+                };
+                let (other_id, blocked) = (conversation.user_id, !conversation.blocked);
+                let message = match set_blocked(user_id, other_id, blocked) {
+                    Ok(()) if blocked => format!(
+                        "Blocked {}, who can't send you messages any more",
+                        conversation.name
+                    ),
+                    Ok(()) => format!("Unblocked {}", conversation.name),
+                    Err(e) => format!("Couldn't change who is blocked: {}", e),
+                };
+
+                app.messages_frame.load(user_id, Some(other_id));
+                app.refresh_unread();
+                app.page_title.set_title(message);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, dark_mode: bool) {
+        let horizontal = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .split(area);
+        let vertical = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+            .split(horizontal[1]);
+
+        self.render_conversations(f, horizontal[0]);
+        self.render_messages(f, vertical[0], dark_mode);
+        self.message_box.render(
+            f,
+            vertical[1],
+            matches!(self.selected_box, SelectedBox::Message),
+        );
+    }
+
+    /// Renders the people the user has talked to, with the start of the last message
+    fn render_conversations<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let name_style = Style::default().fg(Color::Gray);
+        let unread_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let blocked_style = Style::default().fg(Color::Red);
+        let detail_style = Style::default().fg(Color::DarkGray);
+
+        // The row of the selected conversation, which is kept on the screen
+        let mut selected_row = 0;
+        let mut text = Vec::new();
+
+        if let Some(message) = &self.message {
+            text.push(Spans::from(message.clone()));
+        } else if self.conversations.items.is_empty() {
+            text.push(Spans::from("No messages yet"));
+            text.push(Spans::from("Press m on someone's profile to message them"));
+        }
+
+        for (i, conversation) in self.conversations.items.iter().enumerate() {
+            let is_selected = self.conversations.selected() == Some(i);
+            let style = if is_selected {
+                selected_row = text.len() as u16;
+                self.conversations.highlight_style()
+            } else if conversation.unread > 0 {
+                unread_style
+            } else {
+                name_style
+            };
+
+            let mut spans = vec![
+                Span::styled(self.conversations.prefix(i), style),
+                Span::styled(conversation.name.clone(), style),
+            ];
+            if conversation.unread > 0 {
+                spans.push(Span::styled(
+                    format!(" ({} new)", conversation.unread),
+                    unread_style,
+                ));
+            }
+            if conversation.blocked {
+                spans.push(Span::styled(" (blocked)", blocked_style));
+            }
+            text.push(Spans::from(spans));
+
+            let detail = match (&conversation.last_text, conversation.last_sent) {
+                (Some(last_text), Some(last_sent)) => {
+                    let short: String = markdown::plain_text(last_text).chars().take(30).collect();
+                    format!("{}: {}", time_ago(last_sent), short)
+                }
+                _ => String::from("New conversation"),
+            };
+            text.push(Spans::from(vec![
+                Span::raw(self.conversations.placeholder()),
+                Span::styled(detail, detail_style),
+            ]));
+            text.push(Spans::default());
+        }
+
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let offset = (selected_row + 2).saturating_sub(inner.height);
+
+        let conversations = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((offset, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(
+                        matches!(self.selected_box, SelectedBox::Conversations),
+                        false,
+                    ))
+                    .title("Conversations (b block, a profile)"),
+            );
+
+        f.render_widget(conversations, area);
+    }
+
+    /// Renders the messages of the open conversation, oldest at the top
+    fn render_messages<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, dark_mode: bool) {
+        let conversation = match self.conversations.selected_item() {
+            Some(conversation) => conversation,
+            None => {
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(false, false))
+                    .title("Messages");
+                f.render_widget(block, area);
+                return;
+            }
+        };
+
+        let options = TextOptions {
+            raw: false,
+            dark_mode,
+            names: self.message_box.compose.names(),
+        };
+        let own_style = Style::default()
+            .fg(Color::LightCyan)
+            .add_modifier(Modifier::BOLD);
+        let other_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let time_style = Style::default().fg(Color::DarkGray);
+
+        let mut text = Vec::new();
+        if conversation.blocked {
+            text.push(Spans::from(Span::styled(
+                format!(
+                    "You have blocked {}, so they can't send you messages. \
+                     Press b in the list of conversations to unblock them",
+                    conversation.name
+                ),
+                Style::default().fg(Color::Red),
+            )));
+            text.push(Spans::default());
+        }
+        if self.messages.is_empty() {
+            text.push(Spans::from(format!(
+                "Say hello to {}! Messages can only be seen by the two of you",
+                conversation.name
+            )));
+        }
+
+        for message in &self.messages {
+            let (name, style) = if message.sender_id == conversation.user_id {
+                (conversation.name.as_str(), other_style)
+            } else {
+                ("You", own_style)
+            };
+
+            text.push(Spans::from(vec![
+                Span::styled(name.to_owned(), style),
+                Span::styled(format!(" {}", time_ago(message.sent_at)), time_style),
+            ]));
+            text.extend(markdown::render_text(
+                &message.text,
+                options,
+                &mut Vec::new(),
+            ));
+            text.push(Spans::default());
+        }
+        // Leave out the gap after the last message, so that it sits at the bottom
+        if !self.messages.is_empty() {
+            text.pop();
+        }
+
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        self.scroll
+            .update(wrapped_height(&text, inner.width), inner.height);
+        if self.scroll_to_bottom {
+            self.scroll.bottom();
+            self.scroll_to_bottom = false;
+        }
+
+        let title = format!("Messages with {}", conversation.name);
+        let messages = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(false, false))
+                    .title(self.scroll.title(title)),
+            )
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll.offset(), 0));
+
+        f.render_widget(messages, area);
+    }
+}
+
+/// The box the next message is written in, which works like the reply box of a post: Enter starts
+/// writing and Esc stops. Ctrl+S sends what has been written
+struct MessageBox {
+    compose: ComposeBox,
+    /// The user the message is being written to
+    to: Option<i32>,
+}
+
+impl MessageBox {
+    fn new() -> Self {
+        Self {
+            compose: ComposeBox::new(),
+            to: None,
+        }
+    }
+
+    fn handle_key(app: &mut App, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        if app.messages_frame.message_box.compose.locked {
+            match key.code {
+                KeyCode::Char('s') if ctrl => Self::send(app),
+                // While locked, the editing keys change the message
+                _ if app.messages_frame.message_box.compose.handle_key(key) => {}
+                KeyCode::Esc => {
+                    app.messages_frame.message_box.compose.locked = false;
+                    app.quittable = true;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // The scrolling keys move through the messages while not writing
+        if app.messages_frame.scroll.handle_key(key.code) {
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Left => {
+                app.messages_frame.selected_box = SelectedBox::Conversations;
+            }
+            KeyCode::Enter => {
+                app.messages_frame.message_box.compose.locked = true;
+                app.quittable = false;
+            }
+            KeyCode::Char('e') => {
+                // Write the message in the user's own editor instead
+                app.compose = Some(Compose::Message);
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends the message to the user in the open conversation, and shows it once it is sent
+    fn send(app: &mut App) {
+        let user_id = app.profile_frame.user_id;
+        let frame = &mut app.messages_frame;
+        let other_id = match frame.other_id() {
+            Some(other_id) => other_id,
+            None => return,
+        };
+
+        let result = validate_message(frame.message_box.compose.text()).and_then(|text| {
+            send_message(user_id, other_id, text)
+                .map_err(|e| format!("Couldn't send the message: {}", e))
+        });
+
+        match result {
+            Ok(()) => {
+                frame.message_box.compose.discard();
+                frame.load(user_id, None);
+                app.page_title.set_title("Messages");
+            }
+            Err(e) => {
+                app.page_title.set_title(e);
+            }
+        }
+    }
+
+    fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.compose.locked);
+        let title = if self.compose.locked {
+            "Message (Ctrl+S to send, Esc to stop writing)"
+        } else {
+            "Message (Enter to write, e for your editor)"
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(title);
+
+        self.compose.render(f, area, block, is_selected);
+    }
+}
+
+/// A conversation with someone who hasn't been messaged yet, or `None` if they can't be found or
+/// are the user themselves
+fn new_conversation(user_id: i32, other_id: i32) -> Option<Conversation> {
+    use st_read::schema::blockedsender::dsl as blocked_dsl;
+    use st_read::schema::users::dsl as users_dsl;
+    let connection = st_read::establish_connection();
+
+    if user_id == other_id {
+        return None;
+    }
+
+    let name: String = users_dsl::users
+        .find(other_id)
+        .filter(users_dsl::deleted.eq(false))
+        .select(users_dsl::name)
+        .first(&connection)
+        .ok()?;
+    let blocked = blocked_dsl::blockedsender
+        .find((user_id, other_id))
+        .first::<(i32, i32, std::time::SystemTime)>(&connection)
+        .optional()
+        .ok()?
+        .is_some();
+
+    Some(Conversation {
+        user_id: other_id,
+        name,
+        last_text: None,
+        last_sent: None,
+        unread: 0,
+        blocked,
+    })
+}
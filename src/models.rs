@@ -1,5 +1,7 @@
 use std::time::SystemTime;
 
+use diesel::sql_types::{BigInt, Bool, Float, Integer, Nullable, Text, Timestamp};
+
 use super::schema::*;
 
 #[derive(Insertable, Debug)]
@@ -18,6 +20,8 @@ pub struct User {
     pub password_hash: Vec<u8>,
     pub dark_mode: bool,
     pub email_notifications: bool,
+    pub date_joined: SystemTime,
+    pub deleted: bool,
 }
 
 #[derive(Queryable, Debug)]
@@ -47,6 +51,7 @@ pub struct Posts {
 pub struct PostComment {
     pub comment_id: i32,
     pub text: String,
+    pub date_posted: SystemTime,
 }
 
 #[derive(Queryable, Insertable, Debug)]
@@ -56,6 +61,13 @@ pub struct PostComments {
     pub comment_id: i32,
 }
 
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "posttag"]
+pub struct PostTag {
+    pub post_id: i32,
+    pub tag: String,
+}
+
 #[derive(Queryable, Debug)]
 pub struct PostCommentOn {
     pub comment_id: i32,
@@ -83,3 +95,144 @@ pub struct CommentReaction {
     pub comment_id: i32,
     pub upvote: bool,
 }
+
+#[derive(Queryable, Debug)]
+pub struct Draft {
+    pub draft_id: i32,
+    pub user_id: i32,
+    pub post_id: Option<i32>,
+    pub title: String,
+    pub text: String,
+    pub last_saved: SystemTime,
+    pub recipient_id: Option<i32>,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "draft"]
+pub struct NewDraft {
+    pub user_id: i32,
+    pub post_id: Option<i32>,
+    pub recipient_id: Option<i32>,
+    pub title: String,
+    pub text: String,
+}
+
+/// A post that matched a full text search, with the matched words in the title and snippet
+/// surrounded by `\u{1}` and `\u{2}`
+#[derive(QueryableByName, Debug)]
+pub struct SearchResult {
+    #[sql_type = "Integer"]
+    pub post_id: i32,
+    #[sql_type = "Float"]
+    pub rank: f32,
+    #[sql_type = "Text"]
+    pub title: String,
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "community"]
+pub struct NewCommunity {
+    pub name: String,
+    pub description: String,
+    pub creator_id: i32,
+}
+
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "communitypost"]
+pub struct CommunityPost {
+    pub post_id: i32,
+    pub community_id: i32,
+}
+
+/// A community along with the name of its creator, how many subscribers and posts it has, and
+/// whether the user it was loaded for is subscribed to it
+#[derive(QueryableByName, Debug, Clone)]
+pub struct CommunityInfo {
+    #[sql_type = "Integer"]
+    pub community_id: i32,
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Text"]
+    pub description: String,
+    #[sql_type = "Text"]
+    pub creator: String,
+    #[sql_type = "BigInt"]
+    pub subscribers: i64,
+    #[sql_type = "BigInt"]
+    pub posts: i64,
+    #[sql_type = "Bool"]
+    pub subscribed: bool,
+}
+
+/// A comment written by a user, along with the post whose thread it is in
+#[derive(QueryableByName, Debug, Clone)]
+pub struct UserComment {
+    #[sql_type = "Integer"]
+    pub comment_id: i32,
+    #[sql_type = "Integer"]
+    pub post_id: i32,
+    #[sql_type = "Text"]
+    pub post_title: String,
+    #[sql_type = "Text"]
+    pub text: String,
+    #[sql_type = "Timestamp"]
+    pub date_posted: SystemTime,
+}
+
+/// Something that happened to what a user has written, along with who did it and the post it
+/// happened on
+#[derive(QueryableByName, Debug, Clone)]
+pub struct Notification {
+    #[sql_type = "Integer"]
+    pub notification_id: i32,
+    /// One of "post_reply", "comment_reply", "mention", or "vote"
+    #[sql_type = "Text"]
+    pub kind: String,
+    #[sql_type = "Text"]
+    pub actor: String,
+    #[sql_type = "Integer"]
+    pub post_id: i32,
+    #[sql_type = "Text"]
+    pub post_title: String,
+    #[sql_type = "Nullable<Integer>"]
+    pub comment_id: Option<i32>,
+    #[sql_type = "Nullable<Text>"]
+    pub comment_text: Option<String>,
+    #[sql_type = "Nullable<Bool>"]
+    pub upvote: Option<bool>,
+    #[sql_type = "Timestamp"]
+    pub created_at: SystemTime,
+    #[sql_type = "Bool"]
+    pub is_read: bool,
+}
+
+/// A private message from one user to another
+#[derive(Queryable, Debug, Clone)]
+pub struct Message {
+    pub message_id: i32,
+    pub sender_id: i32,
+    pub recipient_id: i32,
+    pub text: String,
+    pub sent_at: SystemTime,
+    pub is_read: bool,
+}
+
+/// The other user in a conversation, with the last message sent either way, how many of their
+/// messages haven't been read, and whether they have been blocked
+#[derive(QueryableByName, Debug, Clone)]
+pub struct Conversation {
+    #[sql_type = "Integer"]
+    pub user_id: i32,
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Nullable<Text>"]
+    pub last_text: Option<String>,
+    #[sql_type = "Nullable<Timestamp>"]
+    pub last_sent: Option<SystemTime>,
+    #[sql_type = "BigInt"]
+    pub unread: i64,
+    #[sql_type = "Bool"]
+    pub blocked: bool,
+}
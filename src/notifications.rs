@@ -0,0 +1,229 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use st_read::models::Notification;
+use st_read::util::time_ago;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView};
+use crate::list::StatefulList;
+use crate::markdown;
+
+/// The newest notifications of the user with the id `$1`, with the names and text they refer to
+const LOAD_NOTIFICATIONS: &str = "
+SELECT n.notification_id, n.kind, u.name AS actor, n.post_id, p.title AS post_title,
+    n.comment_id, c.text AS comment_text, n.upvote, n.created_at, n.is_read
+FROM Notification n
+    JOIN Users u ON u.user_id = n.actor_id
+    JOIN Post p ON p.post_id = n.post_id
+    LEFT JOIN PostComment c ON c.comment_id = n.comment_id
+WHERE n.user_id = $1
+ORDER BY n.created_at DESC, n.notification_id DESC
+LIMIT 100";
+
+/// Counts the notifications the user hasn't opened yet
+pub fn count_unread(user_id: i32) -> i64 {
+    use st_read::schema::notification::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::notification
+        .filter(dsl::user_id.eq(user_id))
+        .filter(dsl::is_read.eq(false))
+        .count()
+        .get_result(&connection)
+        .unwrap_or(0)
+}
+
+fn load_notifications(user_id: i32) -> QueryResult<Vec<Notification>> {
+    use diesel::sql_types::Integer;
+    let connection = st_read::establish_connection();
+
+    diesel::sql_query(LOAD_NOTIFICATIONS)
+        .bind::<Integer, _>(user_id)
+        .load(&connection)
+}
+
+/// Marks one of the user's notifications as read, or all of them without `notification_id`
+fn mark_read(user_id: i32, notification_id: Option<i32>) -> QueryResult<usize> {
+    use st_read::schema::notification::dsl;
+    let connection = st_read::establish_connection();
+
+    let unread = dsl::notification
+        .filter(dsl::user_id.eq(user_id))
+        .filter(dsl::is_read.eq(false));
+
+    match notification_id {
+        Some(id) => diesel::update(unread.filter(dsl::notification_id.eq(id)))
+            .set(dsl::is_read.eq(true))
+            .execute(&connection),
+        None => diesel::update(unread)
+            .set(dsl::is_read.eq(true))
+            .execute(&connection),
+    }
+}
+
+/// Describes what happened, such as `Pablo replied to your post "Hello"`
+fn describe(notification: &Notification) -> String {
+    let actor = &notification.actor;
+    let title = &notification.post_title;
+    let on_comment = notification.comment_id.is_some();
+
+    match notification.kind.as_str() {
+        "post_reply" => format!("{} replied to your post \"{}\"", actor, title),
+        "comment_reply" => format!("{} replied to your comment on \"{}\"", actor, title),
+        "mention" if on_comment => format!("{} mentioned you on \"{}\"", actor, title),
+        "mention" => format!("{} mentioned you in \"{}\"", actor, title),
+        _ => {
+            let vote = if notification.upvote == Some(false) {
+                "downvoted"
+            } else {
+                "upvoted"
+            };
+            if on_comment {
+                format!("{} {} your comment on \"{}\"", actor, vote, title)
+            } else {
+                format!("{} {} your post \"{}\"", actor, vote, title)
+            }
+        }
+    }
+}
+
+/// Lists the replies, mentions, and votes on what the user has written, newest first
+pub struct NotificationsFrame {
+    notifications: StatefulList<Notification>,
+    /// Why the notifications couldn't be loaded
+    message: Option<String>,
+}
+
+impl NotificationsFrame {
+    pub fn new() -> Self {
+        Self {
+            notifications: StatefulList::with_items(Vec::new()),
+            message: None,
+        }
+    }
+
+    pub fn load(&mut self, user_id: i32) {
+        let notifications = match load_notifications(user_id) {
+            Ok(notifications) => {
+                self.message = None;
+                notifications
+            }
+            Err(e) => {
+                self.message = Some(format!("Couldn't load your notifications: {}", e));
+                Vec::new()
+            }
+        };
+
+        self.notifications = StatefulList::highlighted(notifications);
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        let notifications = &mut app.notifications_frame.notifications;
+        let user_id = app.profile_frame.user_id;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !notifications.items.is_empty() => {
+                notifications.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !notifications.items.is_empty() => {
+                notifications.previous();
+            }
+            KeyCode::Enter => {
+                if let Some(notification) = notifications.selected_item() {
+                    let (id, post_id, comment_id) = (
+                        notification.notification_id,
+                        notification.post_id,
+                        notification.comment_id,
+                    );
+
+                    // Opening it is enough to have read it, so a failure here isn't worth showing
+                    let _ = mark_read(user_id, Some(id));
+                    app.refresh_unread();
+                    app.open_post_at(post_id, comment_id);
+                }
+            }
+            KeyCode::Char('R') => {
+                let message = match mark_read(user_id, None) {
+                    Ok(_) => String::from("Marked every notification as read"),
+                    Err(e) => format!("Couldn't mark the notifications as read: {}", e),
+                };
+                app.notifications_frame.load(user_id);
+                app.refresh_unread();
+                app.page_title.set_title(message);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let read_style = Style::default().fg(Color::Gray);
+        let unread_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let detail_style = Style::default().fg(Color::DarkGray);
+
+        // The row of the selected notification, which is kept on the screen
+        let mut selected_row = 0;
+        let mut text = Vec::new();
+
+        if let Some(message) = &self.message {
+            text.push(Spans::from(message.clone()));
+        } else if self.notifications.items.is_empty() {
+            text.push(Spans::from("Nothing yet"));
+        }
+
+        for (i, notification) in self.notifications.items.iter().enumerate() {
+            let is_selected = self.notifications.selected() == Some(i);
+            let style = if is_selected {
+                selected_row = text.len() as u16;
+                self.notifications.highlight_style()
+            } else if notification.is_read {
+                read_style
+            } else {
+                unread_style
+            };
+
+            text.push(Spans::from(vec![
+                Span::styled(self.notifications.prefix(i), style),
+                Span::styled(describe(notification), style),
+            ]));
+
+            // Replies and mentions show the start of what was written
+            let mut detail = time_ago(notification.created_at);
+            if let (Some(comment), false) =
+                (&notification.comment_text, notification.kind == "vote")
+            {
+                let short: String = markdown::plain_text(comment).chars().take(60).collect();
+                detail = format!("{}: {} ...", detail, short);
+            }
+            text.push(Spans::from(vec![
+                Span::raw(self.notifications.placeholder()),
+                Span::styled(detail, detail_style),
+            ]));
+            text.push(Spans::default());
+        }
+
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let offset = (selected_row + 2).saturating_sub(inner.height);
+
+        let notifications = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((offset, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(get_border_style(true, false))
+                    .title("Notifications (Enter to open, R to mark all read, Esc to go back)"),
+            );
+
+        f.render_widget(notifications, area);
+    }
+}
@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
 use crossterm::event::{KeyCode, KeyEvent};
+use st_read::models::SearchResult;
 use tui::backend::Backend;
+use tui::layout::Constraint;
+use tui::layout::Direction;
+use tui::layout::Layout;
 use tui::layout::Rect;
 use tui::style::Color;
 use tui::style::Modifier;
@@ -17,15 +24,51 @@ use crate::app::App;
 use crate::app::AppView;
 use crate::app::Post;
 use crate::app::SelectedFrame;
+use crate::editor::Editor;
 use crate::list::StatefulList;
+use crate::tags::{self, TagInput};
+use crate::viewing_post::count_new_comments;
+
+/// Which of the posts are shown in the list
+#[derive(Debug, Clone)]
+pub enum FeedFilter {
+    /// The posts in the communities with these ids, which the user is subscribed to
+    Home(Vec<i32>),
+    /// Every post
+    All,
+    /// The posts in one community, given by its id and name
+    Community(i32, String),
+}
 
 pub struct PostsListFrame {
     pub posts: StatefulList<Post>,
+    /// Every post, so that the full feed can be brought back after searching
+    feed: Vec<Post>,
+    /// Posts made since the feed was loaded, which are only added once the user asks so that the
+    /// list doesn't move under them
+    pending: Vec<Post>,
+    search: SearchBox,
+    filter: FeedFilter,
+    /// Only shows posts with this tag, on top of the filter
+    tag: Option<String>,
+    tag_box: TagBox,
 }
 
 impl PostsListFrame {
     /// Creates a new post list frame with the provided items
     pub fn with_items(posts: Vec<Post>) -> Self {
+        Self {
+            posts: Self::make_list(posts.clone()),
+            feed: posts,
+            pending: Vec::new(),
+            search: SearchBox::new(),
+            filter: FeedFilter::All,
+            tag: None,
+            tag_box: TagBox::new(),
+        }
+    }
+
+    fn make_list(posts: Vec<Post>) -> StatefulList<Post> {
         let mut posts = StatefulList::with_items(posts);
         posts.with_highlight_style(
             Style::default()
@@ -35,17 +78,193 @@ impl PostsListFrame {
         posts.with_highlight_symbol(">> ");
         posts.next();
 
-        Self { posts }
+        posts
+    }
+
+    /// Returns true if the post passes the filter and has the tag being shown
+    fn in_feed(&self, post: &Post) -> bool {
+        if let Some(tag) = &self.tag {
+            if !post.tags.contains(tag) {
+                return false;
+            }
+        }
+
+        match &self.filter {
+            FeedFilter::Home(subscriptions) => post
+                .community_id
+                .is_some_and(|id| subscriptions.contains(&id)),
+            FeedFilter::All => true,
+            FeedFilter::Community(community_id, _) => post.community_id == Some(*community_id),
+        }
+    }
+
+    /// Replaces the list with the posts that matched a search, in the order they were ranked.
+    /// Every post is searched, whichever feed is being shown
+    pub fn show_results(&mut self, results: Vec<SearchResult>) {
+        let posts = results
+            .iter()
+            .filter_map(|result| {
+                self.feed
+                    .iter()
+                    .chain(&self.pending)
+                    .find(|p| p.post_id == result.post_id)
+            })
+            .cloned()
+            .collect();
+
+        self.posts = Self::make_list(posts);
+        self.search.hits = Some(results.into_iter().map(|r| (r.post_id, r)).collect());
+    }
+
+    /// Goes back to listing every post after a search
+    pub fn show_feed(&mut self) {
+        let posts = self
+            .feed
+            .iter()
+            .filter(|post| self.in_feed(post))
+            .cloned()
+            .collect();
+
+        self.posts = Self::make_list(posts);
+        self.search.hits = None;
+        self.search.clear();
+    }
+
+    /// Shows the posts that pass a different filter
+    pub fn show_filter(&mut self, filter: FeedFilter) {
+        self.filter = filter;
+        self.show_feed();
+    }
+
+    pub fn filter(&self) -> &FeedFilter {
+        &self.filter
+    }
+
+    /// Shows only the posts with a tag, or stops filtering by tag when `None`
+    pub fn show_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+        self.show_feed();
+    }
+
+    pub fn tag(&self) -> Option<&String> {
+        self.tag.as_ref()
+    }
+
+    /// The id of the community whose posts are being shown, if only one community is shown
+    pub fn community_id(&self) -> Option<i32> {
+        match self.filter {
+            FeedFilter::Community(community_id, _) => Some(community_id),
+            _ => None,
+        }
+    }
+
+    /// Finds a post by its id, even if it isn't in the list being shown
+    pub fn find_post(&self, post_id: i32) -> Option<&Post> {
+        self.feed.iter().find(|post| post.post_id == post_id)
+    }
+
+    /// Brings in the latest version of every post without moving the selection. Posts that are
+    /// already listed are updated in place and deleted ones are removed, while new ones wait
+    /// until `show_pending` is called
+    pub fn update_posts(&mut self, posts: Vec<Post>) {
+        let selected = self.posts.selected_item().map(|post| post.post_id);
+        let mut latest: HashMap<i32, Post> =
+            posts.into_iter().map(|post| (post.post_id, post)).collect();
+
+        self.feed = self
+            .feed
+            .iter()
+            .filter_map(|post| latest.get(&post.post_id).cloned())
+            .collect();
+        self.posts.items = self
+            .posts
+            .items
+            .iter()
+            .filter_map(|post| latest.get(&post.post_id).cloned())
+            .collect();
+
+        for post in &self.feed {
+            latest.remove(&post.post_id);
+        }
+        let mut pending: Vec<Post> = latest.into_values().collect();
+        pending.sort_by_key(|post| post.post_id);
+        self.pending = pending;
+
+        self.reselect(selected);
+    }
+
+    /// How many of the posts waiting to be added would be shown in the list
+    pub fn pending_count(&self) -> usize {
+        self.pending
+            .iter()
+            .filter(|post| self.in_feed(post))
+            .count()
+    }
+
+    /// Adds the posts made since the feed was loaded, keeping the same post selected. Search
+    /// results are left as they are
+    pub fn show_pending(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let selected = self.posts.selected_item().map(|post| post.post_id);
+        self.feed.append(&mut self.pending);
+
+        if !self.is_searching() {
+            let posts = self
+                .feed
+                .iter()
+                .filter(|post| self.in_feed(post))
+                .cloned()
+                .collect();
+            self.posts.items = posts;
+            self.reselect(selected);
+        }
+    }
+
+    /// Selects the post with the id given again after the list has changed, or the post that is
+    /// now where the selection was if it is gone
+    fn reselect(&mut self, post_id: Option<i32>) {
+        let index = post_id.and_then(|id| self.posts.items.iter().position(|p| p.post_id == id));
+
+        let index = match index {
+            Some(index) => Some(index),
+            None if self.posts.items.is_empty() => None,
+            None => Some(
+                self.posts
+                    .selected()
+                    .unwrap_or(0)
+                    .min(self.posts.items.len() - 1),
+            ),
+        };
+        self.posts.select(index);
+    }
+
+    /// Returns true if search results are being shown instead of the full feed
+    pub fn is_searching(&self) -> bool {
+        self.search.hits.is_some()
     }
 
     /// Handles the key that the user has pressed
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // Typing into the search prompt takes every key until it is submitted or cancelled
+        if app.posts_frame.search.locked {
+            SearchBox::handle_key(app, key);
+            return;
+        }
+
+        if app.posts_frame.tag_box.locked {
+            TagBox::handle_key(app, key);
+            return;
+        }
+
         match key.code {
             KeyCode::Down | KeyCode::Char('j') => app.posts_frame.posts.next(),
             KeyCode::Up | KeyCode::Char('k') => app.posts_frame.posts.previous(),
             KeyCode::Enter => {
-                if let Some(selected) = app.posts_frame.posts.selected_item() {
-                    app.viewing_frame.view(selected.clone());
+                if let Some(post_id) = app.posts_frame.posts.selected_item().map(|p| p.post_id) {
+                    app.view_post(post_id);
                 }
             }
             KeyCode::Right => {
@@ -59,11 +278,118 @@ impl PostsListFrame {
             KeyCode::Char('c') => {
                 app.set_view(AppView::CreatePost);
             }
+            KeyCode::Char('d') => {
+                app.set_view(AppView::Drafts);
+            }
+            KeyCode::Char('b') => {
+                app.set_view(AppView::Communities);
+            }
+            KeyCode::Char('/') => {
+                app.posts_frame.search.locked = true;
+                app.quittable = false;
+            }
+            KeyCode::Char('s') => {
+                if let Some(post_id) = app.posts_frame.posts.selected_item().map(|p| p.post_id) {
+                    app.toggle_saved(post_id);
+                }
+            }
+            KeyCode::Char('S') => {
+                app.set_view(AppView::Saved);
+            }
+            KeyCode::Char('L') => {
+                app.set_view(AppView::Leaderboard);
+            }
+            KeyCode::Char('n') => {
+                app.set_view(AppView::Notifications);
+            }
+            KeyCode::Char('m') => {
+                app.show_messages(None);
+            }
+            KeyCode::Char('r') => {
+                // Check for anything new right away, rather than waiting for the next check
+                app.refresh_feed();
+            }
+            KeyCode::Char('R') => {
+                // Mark every post in the list as read
+                let post_ids: Vec<i32> = app
+                    .posts_frame
+                    .posts
+                    .items
+                    .iter()
+                    .map(|post| post.post_id)
+                    .collect();
+
+                match app.mark_read(&post_ids) {
+                    Ok(()) => app.page_title.set_title("Marked every post as read"),
+                    Err(e) => app
+                        .page_title
+                        .set_title(format!("Couldn't mark the posts as read: {}", e)),
+                }
+            }
+            KeyCode::Char('t') => {
+                app.posts_frame.tag_box.input.load();
+                app.posts_frame.tag_box.locked = true;
+                app.quittable = false;
+            }
+            KeyCode::Esc if app.posts_frame.is_searching() => {
+                app.posts_frame.show_feed();
+            }
+            KeyCode::Esc if app.posts_frame.tag.is_some() => {
+                app.posts_frame.show_tag(None);
+            }
+            KeyCode::Esc if matches!(app.posts_frame.filter, FeedFilter::Community(..)) => {
+                app.show_home_feed();
+            }
+            KeyCode::Char('a') => {
+                // Switch between the home feed and every post
+                if matches!(app.posts_frame.filter, FeedFilter::All) {
+                    app.show_home_feed();
+                } else {
+                    app.posts_frame.show_filter(FeedFilter::All);
+                }
+            }
             _ => {}
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+    /// Renders the list of posts, marking the ones in `saved` and showing the ones missing from
+    /// `viewed` as unread
+    pub fn render<B: Backend>(
+        &self,
+        f: &mut Frame<B>,
+        area: Rect,
+        is_selected: bool,
+        saved: &[i32],
+        viewed: &HashMap<i32, SystemTime>,
+    ) {
+        // The tag prompt sits under the list while typing
+        let area = if self.tag_box.locked {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+                .split(area);
+
+            self.tag_box.render(f, layout[1], is_selected);
+
+            layout[0]
+        } else {
+            area
+        };
+
+        // The search prompt sits under the list while typing or while results are shown
+        let area = if self.search.locked || self.is_searching() {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+                .split(area);
+
+            self.search.render(f, layout[1], is_selected);
+
+            layout[0]
+        } else {
+            area
+        };
+
         let highlight_symbol_width = self.posts.highlight_symbol().len();
         // The placeholder used instead of the highlighting symbol to keep everything properly
         // indented
@@ -74,6 +400,11 @@ impl PostsListFrame {
             .add_modifier(Modifier::ITALIC)
             .fg(Color::Gray);
 
+        // The style used for posts that haven't been opened yet
+        let unread_style = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+
         let selected_style = self.posts.highlight_style();
 
         let mut text = Vec::new();
@@ -81,38 +412,68 @@ impl PostsListFrame {
         for (i, item) in self.posts.items.iter().enumerate() {
             let mut post = String::new();
 
-            post.push_str(&format!("{}\n", item.title));
+            // Search results show the matched words highlighted in the title and a snippet
+            let (title, short) = match self.search.hit(item.post_id) {
+                Some(hit) => (&hit.title, &hit.snippet),
+                None => (&item.title, &item.short),
+            };
+
+            post.push_str(&format!("{}\n", title));
 
-            post.push_str(&format!("{}\n", item.short));
+            post.push_str(&format!("{}\n", short));
             post.push_str(&format!("{}\n", ""));
-            post.push_str(&format!("{}\n", item.stats));
-            post.push_str(&format!("by {}\n", item.author));
+            let mut stats = item.stats.clone();
+            match viewed.get(&item.post_id) {
+                Some(&since) => match count_new_comments(&item.comments, since) {
+                    0 => {}
+                    new => stats.push_str(&format!(", {} new", new)),
+                },
+                None => stats.push_str(", unread"),
+            }
+            if saved.contains(&item.post_id) {
+                stats.push_str(", saved");
+            }
+            post.push_str(&format!("{}\n", stats));
+            let author = format!("{} ({} karma)", item.author, item.author_karma);
+            match &item.community {
+                Some(community) => post.push_str(&format!("by {} in {}\n", author, community)),
+                None => post.push_str(&format!("by {}\n", author)),
+            }
             post.push_str("\n");
 
             match self.posts.selected() {
                 Some(s) if s == i => {
                     // If the post is selected
                     for (i, line) in post.lines().enumerate() {
-                        if i == 0 {
-                            text.push(Spans::from(vec![
-                                Span::styled(self.posts.highlight_symbol(), selected_style),
-                                Span::styled(String::from(line), selected_style),
-                            ]))
+                        let mut spans = if i == 0 {
+                            vec![Span::styled(self.posts.highlight_symbol(), selected_style)]
                         } else {
-                            text.push(Spans::from(vec![
-                                Span::raw(placeholder.clone()),
-                                Span::styled(String::from(line), selected_style),
-                            ]))
+                            vec![Span::raw(placeholder.clone())]
+                        };
+                        spans.append(&mut highlight_matches(line, selected_style));
+
+                        text.push(Spans::from(spans));
+                        if i == 0 {
+                            text.extend(tag_line(&item.tags, &placeholder));
                         }
                     }
                 }
                 _ => {
                     // If the post is not selected
-                    for line in post.lines() {
-                        text.push(Spans::from(vec![
-                            Span::raw(placeholder.clone()),
-                            Span::styled(String::from(line), deselected_style),
-                        ]));
+                    let style = if viewed.contains_key(&item.post_id) {
+                        deselected_style
+                    } else {
+                        unread_style
+                    };
+
+                    for (i, line) in post.lines().enumerate() {
+                        let mut spans = vec![Span::raw(placeholder.clone())];
+                        spans.append(&mut highlight_matches(line, style));
+
+                        text.push(Spans::from(spans));
+                        if i == 0 {
+                            text.extend(tag_line(&item.tags, &placeholder));
+                        }
                     }
                 }
             }
@@ -120,15 +481,230 @@ impl PostsListFrame {
 
         let border_style = get_border_style(is_selected, false);
 
+        let mut title = match (self.is_searching(), &self.filter) {
+            (true, _) => format!(
+                "Search Results from every post ({})",
+                self.posts.items.len()
+            ),
+            (false, FeedFilter::Home(_)) => String::from("Home (a to show all posts)"),
+            (false, FeedFilter::All) => String::from("All Posts (a to show your home feed)"),
+            (false, FeedFilter::Community(_, name)) => {
+                format!("Posts in {} (Esc to go back home)", name)
+            }
+        };
+
+        if let (false, Some(tag)) = (self.is_searching(), &self.tag) {
+            title = format!("{} tagged {} (Esc to show every tag)", title, tag);
+        }
+
+        // New posts come first so that they are still mentioned when the title is cut off
+        match self.pending_count() {
+            0 => {}
+            1 => title = format!("[1 new post, press r to refresh] {}", title),
+            new => title = format!("[{} new posts, press r to refresh] {}", new, title),
+        }
+
+        // An empty home feed explains how to fill it
+        if text.is_empty() && matches!(self.filter, FeedFilter::Home(_)) && !self.is_searching() {
+            text.push(Spans::from(Span::styled(
+                format!(
+                    "{}Subscribe to communities with 'b' to see their posts here, or press 'a' to see every post",
+                    placeholder
+                ),
+                deselected_style,
+            )));
+        }
+
         // Render it as a "paragraph"
         let posts = Paragraph::new(text).wrap(Wrap { trim: false }).block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(border_style)
-                .title("Posts"),
+                .title(title),
         );
 
         // We can now render the posts list
         f.render_widget(posts, area);
     }
 }
+
+/// The chips of a post's tags, indented under its title, or nothing if it has no tags
+fn tag_line(tags: &[String], placeholder: &str) -> Option<Spans<'static>> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    let mut spans = vec![Span::raw(String::from(placeholder))];
+    spans.extend(tags::chips(tags));
+
+    Some(Spans::from(spans))
+}
+
+/// Splits a line from a search result into spans, highlighting the words that were wrapped in
+/// `\u{1}` and `\u{2}` by the search query. Lines without any matches become a single span
+fn highlight_matches(line: &str, style: Style) -> Vec<Span<'static>> {
+    let highlighted = style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+
+    let mut spans = Vec::new();
+    let mut parts = line.split('\u{1}');
+
+    if let Some(before) = parts.next() {
+        spans.push(Span::styled(String::from(before), style));
+    }
+
+    for part in parts {
+        let (matched, rest) = part.split_once('\u{2}').unwrap_or((part, ""));
+
+        spans.push(Span::styled(String::from(matched), highlighted));
+        spans.push(Span::styled(String::from(rest), style));
+    }
+
+    spans
+}
+
+/// The prompt that is opened with '/' to search through posts and their comments
+struct SearchBox {
+    editor: Editor,
+    locked: bool,
+    /// The highlighted title and snippet of each result by post id, or `None` when the full feed
+    /// is shown
+    hits: Option<HashMap<i32, SearchResult>>,
+}
+
+impl SearchBox {
+    pub fn new() -> Self {
+        Self {
+            editor: Editor::single_line(),
+            locked: false,
+            hits: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.editor.clear();
+    }
+
+    pub fn hit(&self, post_id: i32) -> Option<&SearchResult> {
+        self.hits.as_ref()?.get(&post_id)
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                app.posts_frame.search.locked = false;
+                app.quittable = true;
+
+                // Cancelling a new search keeps showing the previous results, if there were any
+                if !app.posts_frame.is_searching() {
+                    app.posts_frame.search.clear();
+                }
+            }
+            KeyCode::Enter => {
+                app.posts_frame.search.locked = false;
+                app.quittable = true;
+
+                let query = app.posts_frame.search.editor.text().trim().to_owned();
+                if query.is_empty() {
+                    app.posts_frame.show_feed();
+                } else {
+                    match app.search_posts(&query) {
+                        Ok(results) => app.posts_frame.show_results(results),
+                        Err(e) => app
+                            .page_title
+                            .set_title(format!("Couldn't search the posts: {}", e)),
+                    }
+                }
+            }
+            _ => {
+                app.posts_frame.search.editor.handle_key(key);
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.locked);
+
+        let input = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let mut text = vec![Span::styled("/", Style::default().fg(Color::Gray))];
+        for line in self.editor.lines(input, self.locked) {
+            text.extend(line.0);
+        }
+
+        let title = if self.locked {
+            "Search (Enter to search, Esc to cancel)"
+        } else {
+            "Search (Esc to show all posts)"
+        };
+
+        let search = Paragraph::new(Spans::from(text))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(title),
+            )
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(search, area);
+    }
+}
+
+/// The prompt that is opened with 't' to only show the posts with a tag
+struct TagBox {
+    input: TagInput,
+    locked: bool,
+}
+
+impl TagBox {
+    pub fn new() -> Self {
+        Self {
+            input: TagInput::new(),
+            locked: false,
+        }
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                app.posts_frame.tag_box.locked = false;
+                app.posts_frame.tag_box.input.editor.clear();
+                app.quittable = true;
+            }
+            KeyCode::Enter => {
+                app.posts_frame.tag_box.locked = false;
+                app.quittable = true;
+
+                // An empty prompt goes back to showing every tag
+                let typed = app.posts_frame.tag_box.input.editor.take();
+                let tag = tags::normalize_tag(&typed).ok();
+                app.posts_frame.show_tag(tag);
+            }
+            _ => {
+                app.posts_frame.tag_box.input.handle_key(key);
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.locked);
+
+        let input = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let mut text = vec![Span::styled("#", Style::default().fg(Color::Gray))];
+        text.extend(self.input.line(input, self.locked));
+
+        let prompt = Paragraph::new(Spans::from(text))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title("Tag (Enter to filter, Esc to cancel)"),
+            )
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(prompt, area);
+    }
+}
@@ -1,4 +1,10 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
 use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error};
+use st_read::models::User;
 use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Borders, Paragraph};
@@ -7,14 +13,197 @@ use tui::{backend::Backend, widgets::Block};
 
 use tui::layout::{Constraint, Direction, Layout, Rect};
 
-use crate::app::{App, AppView};
+use crate::account::{self, DELETED_NAME};
+use crate::app::{get_border_style, App, AppView};
+use crate::editor::Editor;
+
+/// The most characters a name can have, as limited by the `Users` table
+const MAX_NAME_LENGTH: usize = 40;
+/// The most characters an email address can have, as limited by the `Users` table
+const MAX_EMAIL_LENGTH: usize = 40;
 
 pub enum SelectedOption {
     DarkMode,
     EmailNotifications,
+    ChangeName,
+    ChangeEmail,
+    ChangePassword,
+    ExportData,
+    DeleteAccount,
     None,
 }
 
+impl SelectedOption {
+    /// The option below this one
+    fn next(&self) -> Self {
+        match self {
+            SelectedOption::None => SelectedOption::DarkMode,
+            SelectedOption::DarkMode => SelectedOption::EmailNotifications,
+            SelectedOption::EmailNotifications => SelectedOption::ChangeName,
+            SelectedOption::ChangeName => SelectedOption::ChangeEmail,
+            SelectedOption::ChangeEmail => SelectedOption::ChangePassword,
+            SelectedOption::ChangePassword => SelectedOption::ExportData,
+            SelectedOption::ExportData | SelectedOption::DeleteAccount => {
+                SelectedOption::DeleteAccount
+            }
+        }
+    }
+
+    /// The option above this one
+    fn previous(&self) -> Self {
+        match self {
+            SelectedOption::None => SelectedOption::DeleteAccount,
+            SelectedOption::DeleteAccount => SelectedOption::ExportData,
+            SelectedOption::ExportData => SelectedOption::ChangePassword,
+            SelectedOption::ChangePassword => SelectedOption::ChangeEmail,
+            SelectedOption::ChangeEmail => SelectedOption::ChangeName,
+            SelectedOption::ChangeName => SelectedOption::EmailNotifications,
+            SelectedOption::EmailNotifications | SelectedOption::DarkMode => {
+                SelectedOption::DarkMode
+            }
+        }
+    }
+}
+
+/// Loads the user's account, if `password` is their current password
+pub fn check_password(user_id: i32, password: &str) -> Result<User, String> {
+    use st_read::schema::users::dsl;
+    let connection = st_read::establish_connection();
+
+    let user: User = dsl::users
+        .find(user_id)
+        .first(&connection)
+        .map_err(|e| format!("Couldn't load your account: {}", e))?;
+
+    // The name is the salt of the password
+    if user.password_hash.as_slice() == st_read::util::hash(password, &user.name) {
+        Ok(user)
+    } else {
+        Err(String::from("The current password is wrong"))
+    }
+}
+
+/// Changes the user's name, returning it trimmed of surrounding whitespace. Since the name is the
+/// salt of the password, the password is hashed again with the new name
+fn change_name(user_id: i32, password: &str, name: &str) -> Result<String, String> {
+    use st_read::schema::users::dsl;
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("Type a new name"));
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Names can't be over {} characters",
+            MAX_NAME_LENGTH
+        ));
+    } else if name.eq_ignore_ascii_case(DELETED_NAME) {
+        return Err(format!(
+            "{} is only used for deleted accounts",
+            DELETED_NAME
+        ));
+    }
+
+    check_password(user_id, password)?;
+
+    let connection = st_read::establish_connection();
+
+    // Mentions find people by their name in any case, so two people can't share one. The name is
+    // escaped so that it is matched as it is, rather than as a pattern
+    let pattern = name
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let taken = diesel::select(diesel::dsl::exists(
+        dsl::users
+            .filter(dsl::user_id.ne(user_id))
+            .filter(dsl::name.ilike(pattern)),
+    ))
+    .get_result(&connection)
+    .map_err(|e| format!("Couldn't change your name: {}", e))?;
+    if taken {
+        return Err(format!("{} is already someone else's name", name));
+    }
+    let hash = st_read::util::hash(password, name);
+
+    diesel::update(dsl::users.find(user_id))
+        .set((dsl::name.eq(name), dsl::password_hash.eq(hash.to_vec())))
+        .execute(&connection)
+        .map_err(|e| format!("Couldn't change your name: {}", e))?;
+
+    Ok(String::from(name))
+}
+
+/// Changes the email the user logs in with, returning it trimmed of surrounding whitespace
+fn change_email(user_id: i32, password: &str, email: &str) -> Result<String, String> {
+    use st_read::schema::users::dsl;
+
+    let email = email.trim();
+    let valid = match email.split_once('@') {
+        Some((user, domain)) => !user.is_empty() && !domain.is_empty(),
+        None => false,
+    };
+    if !valid || email.contains(char::is_whitespace) {
+        return Err(String::from("Type a valid email address"));
+    } else if email.chars().count() > MAX_EMAIL_LENGTH {
+        return Err(format!(
+            "Email addresses can't be over {} characters",
+            MAX_EMAIL_LENGTH
+        ));
+    }
+
+    check_password(user_id, password)?;
+
+    let connection = st_read::establish_connection();
+
+    match diesel::update(dsl::users.find(user_id))
+        .set(dsl::email.eq(email))
+        .execute(&connection)
+    {
+        Ok(_) => Ok(String::from(email)),
+        Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            Err(format!("{} is already used by another account", email))
+        }
+        Err(e) => Err(format!("Couldn't change your email: {}", e)),
+    }
+}
+
+/// Changes the user's password, after checking the current one
+fn change_password(
+    user_id: i32,
+    password: &str,
+    new_password: &str,
+    confirm: &str,
+) -> Result<(), String> {
+    use st_read::schema::users::dsl;
+
+    if new_password.is_empty() {
+        return Err(String::from("Type a new password"));
+    } else if new_password != confirm {
+        return Err(String::from("The new passwords don't match"));
+    }
+
+    let user = check_password(user_id, password)?;
+
+    let connection = st_read::establish_connection();
+    let hash = st_read::util::hash(new_password, &user.name);
+
+    diesel::update(dsl::users.find(user_id))
+        .set(dsl::password_hash.eq(hash.to_vec()))
+        .execute(&connection)
+        .map(|_| ())
+        .map_err(|e| format!("Couldn't change your password: {}", e))
+}
+
+/// Saves whether the user is emailed about replies to what they've written
+fn save_email_notifications(user_id: i32, enabled: bool) -> QueryResult<usize> {
+    use st_read::schema::users::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::update(dsl::users.find(user_id))
+        .set(dsl::email_notifications_enabled.eq(enabled))
+        .execute(&connection)
+}
+
 /// Displays the user profile information
 pub struct UserProfileFrame {
     pub selected: SelectedOption,
@@ -23,10 +212,49 @@ pub struct UserProfileFrame {
     pub email_notifications: bool,
     pub email: String,
     pub name: String,
+    /// The sum of the votes on everything the user has written
+    pub karma: i32,
+    /// The ids of the communities the user is subscribed to
+    pub subscriptions: Vec<i32>,
+    /// The ids of the posts the user has saved
+    pub saved_posts: Vec<i32>,
+    /// When the user last viewed each post they have opened, by post id
+    pub viewed_posts: HashMap<i32, SystemTime>,
+    /// The form for changing the name, email, or password, while it is open
+    account_form: Option<AccountForm>,
+    /// The confirmation for deleting the account, while it is open
+    delete_form: Option<DeleteForm>,
 }
 
 impl UserProfileFrame {
+    pub fn new() -> Self {
+        Self {
+            selected: SelectedOption::None,
+            dark_mode: false,
+            user_id: 0,
+            email_notifications: false,
+            email: String::new(),
+            name: String::new(),
+            karma: 0,
+            subscriptions: Vec::new(),
+            saved_posts: Vec::new(),
+            viewed_posts: HashMap::new(),
+            account_form: None,
+            delete_form: None,
+        }
+    }
+
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // The form takes every key until it is submitted or cancelled
+        if app.profile_frame.account_form.is_some() {
+            AccountForm::handle_key(app, key);
+            return;
+        }
+        if app.profile_frame.delete_form.is_some() {
+            DeleteForm::handle_key(app, key);
+            return;
+        }
+
         // Handles key presses in the profile code
         match key.code {
             KeyCode::Esc => {
@@ -35,30 +263,50 @@ impl UserProfileFrame {
             }
             // The arrow keys move between selected elements
             KeyCode::Down => {
-                if matches!(app.profile_frame.selected, SelectedOption::None) {
-                    app.profile_frame.selected = SelectedOption::DarkMode;
-                } else if matches!(app.profile_frame.selected, SelectedOption::DarkMode) {
-                    app.profile_frame.selected = SelectedOption::EmailNotifications;
-                }
+                app.profile_frame.selected = app.profile_frame.selected.next();
             }
             KeyCode::Up => {
-                if matches!(app.profile_frame.selected, SelectedOption::None) {
-                    app.profile_frame.selected = SelectedOption::EmailNotifications;
-                } else if matches!(
-                    app.profile_frame.selected,
-                    SelectedOption::EmailNotifications
-                ) {
-                    app.profile_frame.selected = SelectedOption::DarkMode;
-                }
+                app.profile_frame.selected = app.profile_frame.selected.previous();
             }
             KeyCode::Enter => {
-                if matches!(app.profile_frame.selected, SelectedOption::DarkMode) {
-                    app.profile_frame.dark_mode = !app.profile_frame.dark_mode;
-                } else if matches!(
-                    app.profile_frame.selected,
-                    SelectedOption::EmailNotifications
-                ) {
-                    app.profile_frame.email_notifications = !app.profile_frame.email_notifications;
+                let change = match app.profile_frame.selected {
+                    SelectedOption::DarkMode => {
+                        app.profile_frame.dark_mode = !app.profile_frame.dark_mode;
+                        None
+                    }
+                    SelectedOption::EmailNotifications => {
+                        let enabled = !app.profile_frame.email_notifications;
+                        match save_email_notifications(app.profile_frame.user_id, enabled) {
+                            Ok(_) => app.profile_frame.email_notifications = enabled,
+                            Err(e) => app.page_title.set_title(format!(
+                                "Couldn't change your email notifications: {}",
+                                e
+                            )),
+                        }
+                        None
+                    }
+                    SelectedOption::ChangeName => Some(AccountChange::Name),
+                    SelectedOption::ChangeEmail => Some(AccountChange::Email),
+                    SelectedOption::ChangePassword => Some(AccountChange::Password),
+                    SelectedOption::ExportData => {
+                        let message = match account::export_account(app.profile_frame.user_id) {
+                            Ok(path) => format!("Exported your account to {}", path.display()),
+                            Err(e) => e,
+                        };
+                        app.page_title.set_title(message);
+                        None
+                    }
+                    SelectedOption::DeleteAccount => {
+                        app.profile_frame.delete_form = Some(DeleteForm::new());
+                        app.quittable = false;
+                        None
+                    }
+                    SelectedOption::None => None,
+                };
+
+                if let Some(change) = change {
+                    app.profile_frame.account_form = Some(AccountForm::new(change));
+                    app.quittable = false;
                 }
             }
             // 'c' goes to the create post screen
@@ -69,7 +317,7 @@ impl UserProfileFrame {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let main = Block::default()
             .border_style(
                 Style::default()
@@ -103,7 +351,17 @@ impl UserProfileFrame {
                     Constraint::Percentage(7),
                     Constraint::Percentage(2),
                     Constraint::Percentage(7),
-                    Constraint::Percentage(58),
+                    Constraint::Percentage(2),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(2),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(2),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(2),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(2),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(13),
                 ]
                 .as_ref(),
             )
@@ -111,8 +369,16 @@ impl UserProfileFrame {
 
         let user_name_row = left_rows[1];
         let email_row = left_rows[2];
+        let karma_row = left_rows[3];
         let dark_mode_row = left_rows[4];
         let email_notifs_row = left_rows[6];
+        let action_rows = [
+            left_rows[8],
+            left_rows[10],
+            left_rows[12],
+            left_rows[14],
+            left_rows[16],
+        ];
 
         f.render_widget(main, area);
 
@@ -136,6 +402,17 @@ impl UserProfileFrame {
 
         f.render_widget(email, email_row);
 
+        // Display the karma
+        let karma = Paragraph::new(Spans::from(vec![
+            Span::styled("Karma: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                self.karma.to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]));
+
+        f.render_widget(karma, karma_row);
+
         // Display the dark mode toggle
         let dark_mode_layout = Layout::default()
             .direction(Direction::Horizontal)
@@ -189,6 +466,230 @@ impl UserProfileFrame {
         );
 
         f.render_widget(email_notifs, email_notifs_row);
+
+        // Display the account actions
+        let actions = [
+            (
+                "Change Name",
+                matches!(self.selected, SelectedOption::ChangeName),
+            ),
+            (
+                "Change Email",
+                matches!(self.selected, SelectedOption::ChangeEmail),
+            ),
+            (
+                "Change Password",
+                matches!(self.selected, SelectedOption::ChangePassword),
+            ),
+            (
+                "Export My Data",
+                matches!(self.selected, SelectedOption::ExportData),
+            ),
+            (
+                "Delete My Account",
+                matches!(self.selected, SelectedOption::DeleteAccount),
+            ),
+        ];
+
+        for ((label, selected), row) in actions.into_iter().zip(action_rows) {
+            let (prompt_style, _, border_style) = get_styles(selected);
+
+            let row = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(row)[0];
+
+            let action = Paragraph::new(Span::styled(label, prompt_style)).block(
+                Block::default()
+                    .border_style(border_style)
+                    .borders(Borders::ALL),
+            );
+
+            f.render_widget(action, row);
+        }
+
+        // The form sits in the right column while it is open
+        if let Some(form) = &mut self.account_form {
+            form.render(f, columns[3]);
+        }
+        if let Some(form) = &mut self.delete_form {
+            form.render(f, columns[3]);
+        }
+    }
+}
+
+/// Which part of the account is being changed
+#[derive(Debug, Clone, Copy)]
+enum AccountChange {
+    Name,
+    Email,
+    Password,
+}
+
+/// The form for changing the name, email, or password, which always asks for the current
+/// password
+struct AccountForm {
+    change: AccountChange,
+    /// The new name, email, or password
+    value: Editor,
+    /// The new password typed again, only used when changing the password
+    confirm: Editor,
+    current: Editor,
+    /// The index of the field being typed in
+    field: usize,
+    /// Why the change couldn't be made
+    error: Option<String>,
+}
+
+impl AccountForm {
+    fn new(change: AccountChange) -> Self {
+        Self {
+            change,
+            value: match change {
+                AccountChange::Password => Editor::password(),
+                _ => Editor::single_line(),
+            },
+            confirm: Editor::password(),
+            current: Editor::password(),
+            field: 0,
+            error: None,
+        }
+    }
+
+    /// The titles of the fields that are shown, in order
+    fn fields(&self) -> &'static [&'static str] {
+        match self.change {
+            AccountChange::Name => &["New Name", "Current Password"],
+            AccountChange::Email => &["New Email", "Current Password"],
+            AccountChange::Password => &["New Password", "New Password Again", "Current Password"],
+        }
+    }
+
+    /// The editor of a field by its index
+    fn editor(&mut self, field: usize) -> &mut Editor {
+        match (self.change, field) {
+            (_, 0) => &mut self.value,
+            (AccountChange::Password, 1) => &mut self.confirm,
+            _ => &mut self.current,
+        }
+    }
+
+    fn handle_key(app: &mut App, key: KeyEvent) {
+        let form = match &mut app.profile_frame.account_form {
+            Some(form) => form,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                app.profile_frame.account_form = None;
+                app.quittable = true;
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                form.field = (form.field + 1) % form.fields().len();
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                let count = form.fields().len();
+                form.field = (form.field + count - 1) % count;
+            }
+            KeyCode::Enter => {
+                let user_id = app.profile_frame.user_id;
+                let change = form.change;
+                let mut value = form.value.take();
+                let mut confirm = form.confirm.take();
+                let mut current = form.current.take();
+
+                let result = match change {
+                    AccountChange::Name => change_name(user_id, &current, &value).map(|name| {
+                        app.profile_frame.name = name;
+                        "Your name has been changed"
+                    }),
+                    AccountChange::Email => change_email(user_id, &current, &value).map(|email| {
+                        app.profile_frame.email = email;
+                        "Your email has been changed"
+                    }),
+                    AccountChange::Password => change_password(user_id, &current, &value, &confirm)
+                        .map(|()| "Your password has been changed"),
+                };
+
+                // Don't keep passwords in memory
+                zeroize::Zeroize::zeroize(&mut current);
+                zeroize::Zeroize::zeroize(&mut confirm);
+
+                match result {
+                    Ok(message) => {
+                        app.profile_frame.account_form = None;
+                        app.quittable = true;
+                        app.page_title.set_title(message);
+
+                        // The author names shown with each post come from the database
+                        if matches!(change, AccountChange::Name) {
+                            app.reload_posts();
+                        }
+                    }
+                    Err(e) => {
+                        let form = app.profile_frame.account_form.as_mut().unwrap();
+                        // Give back what was typed, except for the passwords
+                        if !matches!(change, AccountChange::Password) {
+                            form.value.set_text(&value);
+                        }
+                        form.field = form.fields().len() - 1;
+                        form.error = Some(e);
+                    }
+                }
+                zeroize::Zeroize::zeroize(&mut value);
+            }
+            _ => {
+                let field = form.field;
+                form.editor(field).handle_key(key);
+            }
+        }
+    }
+
+    fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let title = match self.change {
+            AccountChange::Name => "Change Name",
+            AccountChange::Email => "Change Email",
+            AccountChange::Password => "Change Password",
+        };
+        let title = match &self.error {
+            Some(error) => format!("{} - {}", title, error),
+            None => format!("{} (Tab to switch, Enter to save, Esc to cancel)", title),
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(true, true))
+            .title(title);
+
+        let fields = self.fields();
+        let height = 2 + 3 * fields.len() as u16;
+        let area = Rect {
+            height: height.min(area.height),
+            ..area
+        };
+
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3); fields.len()])
+            .split(inner);
+
+        let input = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+
+        for (i, (title, row)) in fields.iter().zip(layout).enumerate() {
+            let selected = i == self.field;
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(selected, false))
+                .title(*title);
+
+            self.editor(i).render(f, row, block, input, selected);
+        }
     }
 }
 
@@ -218,3 +719,142 @@ fn get_styles(selected: bool) -> (Style, Style, Style) {
         )
     }
 }
+
+/// Asks for the password before deleting the account, and whether to keep what the user has
+/// written
+struct DeleteForm {
+    password: Editor,
+    /// Whether the posts, comments, and votes are removed instead of kept as "[deleted user]"
+    remove_content: bool,
+    /// Whether the choice is selected instead of the password
+    on_choice: bool,
+    /// Why the account couldn't be deleted
+    error: Option<String>,
+}
+
+impl DeleteForm {
+    fn new() -> Self {
+        Self {
+            password: Editor::password(),
+            remove_content: false,
+            on_choice: false,
+            error: None,
+        }
+    }
+
+    fn handle_key(app: &mut App, key: KeyEvent) {
+        let form = match &mut app.profile_frame.delete_form {
+            Some(form) => form,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                app.profile_frame.delete_form = None;
+                app.quittable = true;
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                form.on_choice = !form.on_choice;
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if form.on_choice => {
+                form.remove_content = !form.remove_content;
+            }
+            KeyCode::Enter => {
+                let mut password = form.password.take();
+                let result = account::delete_account(
+                    app.profile_frame.user_id,
+                    &password,
+                    form.remove_content,
+                );
+                zeroize::Zeroize::zeroize(&mut password);
+
+                match result {
+                    Ok(()) => {
+                        app.quittable = true;
+                        app.log_out();
+                        app.page_title.set_title("Your account has been deleted");
+                    }
+                    Err(e) => {
+                        form.on_choice = false;
+                        form.error = Some(e);
+                    }
+                }
+            }
+            _ if !form.on_choice => {
+                form.password.handle_key(key);
+            }
+            _ => {}
+        }
+    }
+
+    fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let title = match &self.error {
+            Some(error) => format!("Delete My Account - {}", error),
+            None => String::from("Delete My Account (Enter to delete, Esc to cancel)"),
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(true, true))
+            .title(title);
+
+        let area = Rect {
+            height: area.height.min(11),
+            ..area
+        };
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .split(inner);
+
+        let warning = Paragraph::new(Span::styled(
+            "This can't be undone. Type your password to confirm",
+            Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .wrap(tui::widgets::Wrap { trim: true });
+        f.render_widget(warning, layout[0]);
+
+        let password = Block::default()
+            .borders(Borders::ALL)
+            .border_style(get_border_style(!self.on_choice, false))
+            .title("Password");
+        self.password.render(
+            f,
+            layout[1],
+            password,
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            !self.on_choice,
+        );
+
+        let (prompt_style, value_style, border_style) = get_styles(self.on_choice);
+        let choice = if self.remove_content {
+            String::from("Remove them")
+        } else {
+            format!("Keep them as {}", DELETED_NAME)
+        };
+        let choice = Paragraph::new(Spans::from(vec![
+            Span::styled("My posts, comments, and messages: ", prompt_style),
+            Span::styled(format!("< {} >", choice), value_style),
+        ]))
+        .block(
+            Block::default()
+                .border_style(border_style)
+                .borders(Borders::ALL),
+        );
+        f.render_widget(choice, layout[2]);
+    }
+}
@@ -8,12 +8,13 @@ use tui::Frame;
 use tui::{backend::Backend, widgets::Block};
 
 use crate::app::{get_border_style, App, AppView};
-use crate::initial::{InitialButtonBox, LoginSelectedBox};
+use crate::editor::Editor;
+use crate::initial::{InitialButtonBox, LoginSelectedBox, TextField};
 
 pub struct RegisterFrame {
-    username: UsernameBox,
-    password: PasswordBox,
-    name: NameBox,
+    username: TextField,
+    password: TextField,
+    name: TextField,
     register_button: RegisterButtonBox,
     back_button: InitialButtonBox,
     selected: LoginSelectedBox,
@@ -22,9 +23,9 @@ pub struct RegisterFrame {
 impl RegisterFrame {
     pub fn new() -> Self {
         Self {
-            username: UsernameBox::new(),
-            password: PasswordBox::new(),
-            name: NameBox::new(),
+            username: TextField::new("Email", Editor::single_line()),
+            password: TextField::new("Password", Editor::password()),
+            name: TextField::new("Name", Editor::single_line()),
             register_button: RegisterButtonBox::new(),
             back_button: InitialButtonBox::new("Back", false),
             selected: LoginSelectedBox::Username,
@@ -32,16 +33,29 @@ impl RegisterFrame {
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match app.register_frame.selected {
-            LoginSelectedBox::Username => {
-                UsernameBox::handle_key(app, key);
-            }
-            LoginSelectedBox::Name => {
-                NameBox::handle_key(app, key);
-            }
-            LoginSelectedBox::Password => {
-                PasswordBox::handle_key(app, key);
+        let frame = &mut app.register_frame;
+        let field = match frame.selected {
+            LoginSelectedBox::Username => Some(&mut frame.username),
+            LoginSelectedBox::Name => Some(&mut frame.name),
+            LoginSelectedBox::Password => Some(&mut frame.password),
+            _ => None,
+        };
+        if let Some(field) = field {
+            if !field.handle_key(key, &mut app.quittable) {
+                frame.selected = match (frame.selected, key.code) {
+                    (LoginSelectedBox::Username, KeyCode::Down) => LoginSelectedBox::Name,
+                    (LoginSelectedBox::Name, KeyCode::Up) => LoginSelectedBox::Username,
+                    (LoginSelectedBox::Name, KeyCode::Down) => LoginSelectedBox::Password,
+                    (LoginSelectedBox::Password, KeyCode::Up) => LoginSelectedBox::Name,
+                    (LoginSelectedBox::Password, KeyCode::Down) => LoginSelectedBox::ActionButton,
+                    (selected, _) => selected,
+                };
             }
+            return;
+        }
+
+        match app.register_frame.selected {
+            LoginSelectedBox::Username | LoginSelectedBox::Name | LoginSelectedBox::Password => {}
             LoginSelectedBox::ActionButton => {
                 RegisterButtonBox::handle_key(app, key);
             }
@@ -58,7 +72,7 @@ impl RegisterFrame {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let main = Block::default()
             .border_style(
                 Style::default()
@@ -135,247 +149,6 @@ impl RegisterFrame {
     }
 }
 
-pub struct UsernameBox {
-    text: String,
-    locked: bool,
-}
-
-impl UsernameBox {
-    pub fn new() -> Self {
-        Self {
-            text: String::new(),
-            locked: false,
-        }
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match key.code {
-            KeyCode::Down => {
-                if !app.register_frame.username.locked {
-                    app.register_frame.selected = LoginSelectedBox::Name;
-                }
-            }
-            KeyCode::Enter => {
-                app.register_frame.username.locked = true;
-            }
-            KeyCode::Esc => {
-                app.register_frame.username.locked = false;
-            }
-            KeyCode::Backspace => {
-                if app.register_frame.username.locked {
-                    app.register_frame.username.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.register_frame.username.locked {
-                    app.register_frame.username.add_char(c);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let mut text = vec![Span::styled(
-            self.text.clone(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )];
-
-        if self.locked {
-            let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-            text.push(cursor);
-        }
-
-        let username = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Email"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(username, area);
-    }
-}
-
-pub struct NameBox {
-    text: String,
-    locked: bool,
-}
-
-impl NameBox {
-    pub fn new() -> Self {
-        Self {
-            text: String::new(),
-            locked: false,
-        }
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match key.code {
-            KeyCode::Down => {
-                if !app.register_frame.name.locked {
-                    app.register_frame.selected = LoginSelectedBox::Password;
-                }
-            }
-            KeyCode::Up => {
-                if !app.register_frame.name.locked {
-                    app.register_frame.selected = LoginSelectedBox::Username;
-                }
-            }
-            KeyCode::Enter => {
-                app.register_frame.name.locked = true;
-            }
-            KeyCode::Esc => {
-                app.register_frame.name.locked = false;
-            }
-            KeyCode::Backspace => {
-                if app.register_frame.name.locked {
-                    app.register_frame.name.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.register_frame.name.locked {
-                    app.register_frame.name.add_char(c);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let mut text = vec![Span::styled(
-            self.text.clone(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )];
-
-        if self.locked {
-            let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-            text.push(cursor);
-        }
-
-        let username = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Name"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(username, area);
-    }
-}
-
-pub struct PasswordBox {
-    text: String,
-    locked: bool,
-}
-
-impl PasswordBox {
-    pub fn new() -> Self {
-        Self {
-            text: String::new(),
-            locked: false,
-        }
-    }
-
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn handle_key(app: &mut App, key: KeyEvent) {
-        match key.code {
-            KeyCode::Down => {
-                if !app.register_frame.password.locked {
-                    app.register_frame.selected = LoginSelectedBox::ActionButton;
-                }
-            }
-            KeyCode::Up => {
-                if !app.register_frame.password.locked {
-                    app.register_frame.selected = LoginSelectedBox::Name;
-                }
-            }
-            KeyCode::Enter => {
-                app.register_frame.password.locked = true;
-            }
-            KeyCode::Esc => {
-                app.register_frame.password.locked = false;
-            }
-            KeyCode::Backspace => {
-                if app.register_frame.password.locked {
-                    app.register_frame.password.del_char();
-                }
-            }
-            KeyCode::Char(c) => {
-                if app.register_frame.password.locked {
-                    app.register_frame.password.add_char(c);
-                }
-            }
-            _ => {}
-        }
-    }
-
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let mut text = vec![Span::styled(
-            self.text.clone(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )];
-
-        if self.locked {
-            let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-            text.push(cursor);
-        }
-
-        let password = Paragraph::new(Spans::from(Spans::from(text)))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Password"),
-            )
-            .wrap(Wrap { trim: false });
-
-        f.render_widget(password, area);
-    }
-}
-
 pub struct RegisterButtonBox {}
 
 impl RegisterButtonBox {
@@ -386,9 +159,9 @@ impl RegisterButtonBox {
     pub fn handle_key(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Enter => {
-                let email = std::mem::take(&mut app.register_frame.username.text);
-                let mut password = std::mem::take(&mut app.register_frame.password.text);
-                let name = std::mem::take(&mut app.register_frame.name.text);
+                let email = app.register_frame.username.editor.take();
+                let mut password = app.register_frame.password.editor.take();
+                let name = app.register_frame.name.editor.take();
 
                 use st_read::schema::users::dsl as users_dsl;
                 let connection = st_read::establish_connection();
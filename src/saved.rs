@@ -0,0 +1,212 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use st_read::util::time_ago;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Paragraph, Wrap};
+use tui::Frame;
+
+use crate::app::{get_border_style, App, AppView, SelectedFrame};
+use crate::list::StatefulList;
+
+/// Loads the ids of the posts the user has saved, leaving out the ones that have been deleted
+pub fn load_saved_ids(user_id: i32) -> Vec<i32> {
+    use st_read::schema::savedpost::dsl;
+    let connection = st_read::establish_connection();
+
+    let ids: Vec<Option<i32>> = dsl::savedpost
+        .filter(dsl::user_id.eq(user_id))
+        .select(dsl::post_id)
+        .load(&connection)
+        .unwrap_or_default();
+
+    ids.into_iter().flatten().collect()
+}
+
+/// Bookmarks a post for the user
+pub fn save_post(user_id: i32, post_id: i32) -> QueryResult<()> {
+    use st_read::schema::savedpost::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::insert_into(dsl::savedpost)
+        .values((dsl::user_id.eq(user_id), dsl::post_id.eq(post_id)))
+        .on_conflict_do_nothing()
+        .execute(&connection)
+        .map(|_| ())
+}
+
+/// Removes the user's bookmark of a post
+pub fn unsave_post(user_id: i32, post_id: i32) -> QueryResult<()> {
+    use st_read::schema::savedpost::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::delete(
+        dsl::savedpost
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::post_id.eq(post_id)),
+    )
+    .execute(&connection)
+    .map(|_| ())
+}
+
+/// Removes a bookmark by its own id, which still works once the post has been deleted
+fn delete_saved(saved_id: i32) -> QueryResult<()> {
+    use st_read::schema::savedpost::dsl;
+    let connection = st_read::establish_connection();
+
+    diesel::delete(dsl::savedpost.find(saved_id))
+        .execute(&connection)
+        .map(|_| ())
+}
+
+/// A bookmarked post. The post and its title are `None` once it has been deleted
+struct SavedItem {
+    saved_id: i32,
+    post_id: Option<i32>,
+    title: Option<String>,
+    saved_at: std::time::SystemTime,
+}
+
+/// Lists the posts the user has saved, most recently saved first
+pub struct SavedFrame {
+    saved: StatefulList<SavedItem>,
+    /// Why the selected post couldn't be opened
+    message: Option<String>,
+}
+
+impl SavedFrame {
+    pub fn new() -> Self {
+        Self {
+            saved: StatefulList::highlighted(Vec::new()),
+            message: None,
+        }
+    }
+
+    /// Loads the user's saved posts. The titles are loaded from the posts themselves, so that they
+    /// are up to date after a post is edited
+    pub fn load(&mut self, user_id: i32) {
+        use st_read::schema::post::dsl as post_dsl;
+        use st_read::schema::savedpost::dsl as saved_dsl;
+        let connection = st_read::establish_connection();
+
+        let saved: Vec<(i32, Option<i32>, Option<String>, std::time::SystemTime)> =
+            saved_dsl::savedpost
+                .left_join(post_dsl::post)
+                .filter(saved_dsl::user_id.eq(user_id))
+                .order(saved_dsl::saved_at.desc())
+                .select((
+                    saved_dsl::saved_id,
+                    saved_dsl::post_id,
+                    post_dsl::title.nullable(),
+                    saved_dsl::saved_at,
+                ))
+                .load(&connection)
+                .unwrap_or_default();
+
+        let saved = saved
+            .into_iter()
+            .map(|(saved_id, post_id, title, saved_at)| SavedItem {
+                saved_id,
+                post_id,
+                title,
+                saved_at,
+            })
+            .collect();
+
+        self.saved = StatefulList::highlighted(saved);
+        self.message = None;
+    }
+
+    pub fn handle_key(app: &mut App, key: KeyEvent) {
+        app.saved_frame.message = None;
+        let saved = &mut app.saved_frame.saved;
+
+        match key.code {
+            KeyCode::Esc => {
+                app.set_view(AppView::Homepage);
+            }
+            KeyCode::Down | KeyCode::Char('j') if !saved.items.is_empty() => {
+                saved.next();
+            }
+            KeyCode::Up | KeyCode::Char('k') if !saved.items.is_empty() => {
+                saved.previous();
+            }
+            KeyCode::Enter => match saved.selected_item().map(|item| item.post_id) {
+                Some(Some(post_id)) => {
+                    app.set_view(AppView::Homepage);
+                    app.view_post(post_id);
+                    app.selected_frame = SelectedFrame::ViewPost;
+                }
+                Some(None) => {
+                    app.saved_frame.message = Some(String::from("This post has been deleted"));
+                }
+                None => {}
+            },
+            KeyCode::Char('x') | KeyCode::Char('s') | KeyCode::Delete => {
+                let i = match saved.selected() {
+                    Some(i) if i < saved.items.len() => i,
+                    _ => return,
+                };
+
+                if delete_saved(saved.items[i].saved_id).is_err() {
+                    return;
+                }
+
+                let item = saved.items.remove(i);
+                if saved.items.is_empty() {
+                    saved.unselect();
+                } else if i >= saved.items.len() {
+                    saved.select(Some(saved.items.len() - 1));
+                }
+
+                if let Some(post_id) = item.post_id {
+                    app.profile_frame.saved_posts.retain(|&id| id != post_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let deselected_style = Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .fg(Color::Gray);
+
+        let mut text = Vec::new();
+
+        if self.saved.items.is_empty() {
+            text.push(Spans::from(Span::styled(
+                format!(
+                    "{}No saved posts. Press 's' on a post to save it",
+                    self.saved.placeholder()
+                ),
+                deselected_style,
+            )));
+        }
+
+        for (i, item) in self.saved.items.iter().enumerate() {
+            let title = match &item.title {
+                Some(title) => title.clone(),
+                None => String::from("[deleted]"),
+            };
+            let lines = [title, format!("saved {}", time_ago(item.saved_at))];
+            self.saved.push_rows(&mut text, i, lines, deselected_style);
+        }
+
+        let mut title = String::from("Saved Posts (Enter to open, x to unsave, Esc to go back)");
+        if let Some(message) = &self.message {
+            title = format!("{} - {}", title, message);
+        }
+
+        let saved = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_border_style(true, false))
+                .title(title),
+        );
+
+        f.render_widget(saved, area);
+    }
+}
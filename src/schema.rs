@@ -1,3 +1,11 @@
+table! {
+    blockedsender (user_id, blocked_id) {
+        user_id -> Int4,
+        blocked_id -> Int4,
+        blocked_at -> Timestamp,
+    }
+}
+
 table! {
     commentreaction (user_id, comment_id) {
         user_id -> Int4,
@@ -6,6 +14,79 @@ table! {
     }
 }
 
+table! {
+    community (community_id) {
+        community_id -> Int4,
+        name -> Varchar,
+        description -> Text,
+        creator_id -> Int4,
+    }
+}
+
+table! {
+    communitypost (post_id) {
+        post_id -> Int4,
+        community_id -> Int4,
+    }
+}
+
+table! {
+    draft (draft_id) {
+        draft_id -> Int4,
+        user_id -> Int4,
+        post_id -> Nullable<Int4>,
+        title -> Text,
+        text -> Text,
+        last_saved -> Timestamp,
+        recipient_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    emailqueue (email_id) {
+        email_id -> Int4,
+        user_id -> Int4,
+        subject -> Text,
+        body -> Text,
+        queued_at -> Timestamp,
+        sent_at -> Nullable<Timestamp>,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+    }
+}
+
+table! {
+    feedversion (only_row) {
+        only_row -> Bool,
+        version -> Int8,
+    }
+}
+
+table! {
+    message (message_id) {
+        message_id -> Int4,
+        sender_id -> Int4,
+        recipient_id -> Int4,
+        text -> Text,
+        sent_at -> Timestamp,
+        is_read -> Bool,
+    }
+}
+
+table! {
+    notification (notification_id) {
+        notification_id -> Int4,
+        user_id -> Int4,
+        kind -> Varchar,
+        actor_id -> Int4,
+        post_id -> Int4,
+        comment_id -> Nullable<Int4>,
+        upvote -> Nullable<Bool>,
+        created_at -> Timestamp,
+        is_read -> Bool,
+    }
+}
+
 table! {
     post (post_id) {
         post_id -> Int4,
@@ -19,6 +100,7 @@ table! {
     postcomment (comment_id) {
         comment_id -> Int4,
         text -> Text,
+        date_posted -> Timestamp,
     }
 }
 
@@ -51,6 +133,21 @@ table! {
     }
 }
 
+table! {
+    posttag (post_id, tag) {
+        post_id -> Int4,
+        tag -> Varchar,
+    }
+}
+
+table! {
+    postview (user_id, post_id) {
+        user_id -> Int4,
+        post_id -> Int4,
+        last_viewed -> Timestamp,
+    }
+}
+
 table! {
     replyto (parent_comment, child_comment) {
         parent_comment -> Int4,
@@ -58,6 +155,29 @@ table! {
     }
 }
 
+table! {
+    savedpost (saved_id) {
+        saved_id -> Int4,
+        user_id -> Int4,
+        post_id -> Nullable<Int4>,
+        saved_at -> Timestamp,
+    }
+}
+
+table! {
+    subscription (user_id, community_id) {
+        user_id -> Int4,
+        community_id -> Int4,
+    }
+}
+
+table! {
+    userkarma (user_id) {
+        user_id -> Int4,
+        karma -> Int4,
+    }
+}
+
 table! {
     users (user_id) {
         user_id -> Int4,
@@ -66,11 +186,21 @@ table! {
         password_hash -> Bytea,
         dark_mode -> Bool,
         email_notifications_enabled -> Bool,
+        date_joined -> Timestamp,
+        deleted -> Bool,
     }
 }
 
 joinable!(commentreaction -> postcomment (comment_id));
 joinable!(commentreaction -> users (user_id));
+joinable!(community -> users (creator_id));
+joinable!(communitypost -> community (community_id));
+joinable!(communitypost -> post (post_id));
+joinable!(draft -> post (post_id));
+joinable!(draft -> users (user_id));
+joinable!(emailqueue -> users (user_id));
+joinable!(notification -> post (post_id));
+joinable!(notification -> postcomment (comment_id));
 joinable!(postcommenton -> post (post_id));
 joinable!(postcommenton -> postcomment (comment_id));
 joinable!(postcomments -> postcomment (comment_id));
@@ -78,16 +208,37 @@ joinable!(postcomments -> users (user_id));
 joinable!(postreaction -> post (post_id));
 joinable!(postreaction -> users (user_id));
 joinable!(posts -> post (post_id));
+joinable!(posttag -> post (post_id));
 joinable!(posts -> users (user_id));
+joinable!(postview -> post (post_id));
+joinable!(postview -> users (user_id));
+joinable!(savedpost -> post (post_id));
+joinable!(savedpost -> users (user_id));
+joinable!(subscription -> community (community_id));
+joinable!(subscription -> users (user_id));
+joinable!(userkarma -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    blockedsender,
     commentreaction,
+    community,
+    communitypost,
+    draft,
+    emailqueue,
+    feedversion,
+    message,
+    notification,
     post,
     postcomment,
     postcommenton,
     postcomments,
     postreaction,
     posts,
+    posttag,
+    postview,
     replyto,
+    savedpost,
+    subscription,
+    userkarma,
     users,
 );
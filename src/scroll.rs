@@ -0,0 +1,308 @@
+use crossterm::event::KeyCode;
+use tui::style::Style;
+use tui::text::Spans;
+use unicode_width::UnicodeWidthStr;
+
+const NBSP: &str = "\u{00a0}";
+
+/// Keeps track of how far a pane has been scrolled, along with the size of its contents and the
+/// area it was last drawn in so that the offset never runs past the end of the text
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScrollState {
+    offset: u16,
+    content_height: u16,
+    viewport_height: u16,
+}
+
+impl ScrollState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Jumps back to the top and forgets the size of the previous contents
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The number of wrapped lines hidden above the visible area
+    pub fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    /// Records the size of the contents and the visible area, and keeps the offset in range.
+    /// This should be called every time the pane is rendered
+    pub fn update(&mut self, content_height: u16, viewport_height: u16) {
+        self.content_height = content_height;
+        self.viewport_height = viewport_height;
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    pub fn up(&mut self, lines: u16) {
+        self.offset = self.offset.saturating_sub(lines);
+    }
+
+    pub fn down(&mut self, lines: u16) {
+        self.offset = self.offset.saturating_add(lines).min(self.max_offset());
+    }
+
+    pub fn page_up(&mut self) {
+        self.up(self.viewport_height.max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        self.down(self.viewport_height.max(1));
+    }
+
+    pub fn top(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn bottom(&mut self) {
+        self.offset = self.max_offset();
+    }
+
+    /// Scrolls just enough that the wrapped line `row` is visible
+    pub fn show_line(&mut self, row: u16) {
+        if row < self.offset {
+            self.offset = row;
+        } else if row >= self.offset + self.viewport_height {
+            self.offset = (row + 1)
+                .saturating_sub(self.viewport_height)
+                .min(self.max_offset());
+        }
+    }
+
+    /// Scrolls so that the wrapped line `row` is at the top, or as close to it as the contents
+    /// allow
+    pub fn show_line_at_top(&mut self, row: u16) {
+        self.offset = row.min(self.max_offset());
+    }
+
+    /// Scrolls in response to the keys shared by every scrollable pane.
+    /// Returns false if the key isn't one of them
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.down(1),
+            KeyCode::PageUp => self.page_up(),
+            KeyCode::PageDown => self.page_down(),
+            KeyCode::Home | KeyCode::Char('g') => self.top(),
+            KeyCode::End | KeyCode::Char('G') => self.bottom(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Creates a block title that also shows which lines are visible, for example
+    /// "Comments (lines 20-40 of 130)". Content that fits entirely just gets the plain title
+    pub fn title(&self, title: impl AsRef<str>) -> String {
+        if self.content_height <= self.viewport_height {
+            String::from(title.as_ref())
+        } else {
+            let first = self.offset + 1;
+            let last = (self.offset + self.viewport_height).min(self.content_height);
+
+            format!(
+                "{} (lines {}-{} of {})",
+                title.as_ref(),
+                first,
+                last,
+                self.content_height
+            )
+        }
+    }
+
+    fn max_offset(&self) -> u16 {
+        self.content_height.saturating_sub(self.viewport_height)
+    }
+}
+
+/// Calculates how many rows `lines` will take up once word wrapped to `width` columns.
+///
+/// This mirrors the word wrapping that `tui` does for a `Paragraph` with `Wrap { trim: false }`,
+/// which isn't public, so that scroll offsets line up exactly with what is drawn
+pub fn wrapped_height(lines: &[Spans], width: u16) -> u16 {
+    lines.iter().fold(0, |height: u16, line| {
+        height.saturating_add(row_starts(line, width).len() as u16)
+    })
+}
+
+/// Finds which of the rows that `line` wraps onto holds the byte at `index` of its text
+pub fn wrapped_row(line: &Spans, width: u16, index: usize) -> u16 {
+    let rows = row_starts(line, width)
+        .into_iter()
+        .filter(|&start| start <= index)
+        .count();
+
+    rows.saturating_sub(1) as u16
+}
+
+/// Word wraps one line to `width` columns and returns the byte index in its text where each row
+/// starts. A row that ends up empty starts where the previous symbol did
+fn row_starts(line: &Spans, width: u16) -> Vec<usize> {
+    if width == 0 {
+        return Vec::new();
+    }
+
+    let len = line.0.iter().map(|span| span.content.len()).sum();
+    let mut offset = 0;
+    let mut symbols = line
+        .0
+        .iter()
+        .flat_map(|span| span.styled_graphemes(Style::default()))
+        .map(|grapheme| {
+            let start = offset;
+            offset += grapheme.symbol.len();
+            (start, grapheme.symbol)
+        })
+        .chain(std::iter::once((len, "\n")));
+
+    let mut starts = Vec::new();
+    let mut last_start = 0;
+    let mut current_line: Vec<(usize, &str)> = Vec::new();
+    let mut next_line: Vec<(usize, &str)> = Vec::new();
+
+    loop {
+        std::mem::swap(&mut current_line, &mut next_line);
+        next_line.clear();
+
+        let mut current_line_width: u16 = current_line.iter().map(|(_, s)| s.width() as u16).sum();
+        let mut symbols_to_last_word_end = 0;
+        let mut prev_whitespace = false;
+        let mut symbols_exhausted = true;
+
+        for (start, symbol) in &mut symbols {
+            symbols_exhausted = false;
+            last_start = start;
+            let symbol_whitespace = symbol.chars().all(char::is_whitespace) && symbol != NBSP;
+
+            // Characters wider than the whole line are never drawn
+            if symbol.width() as u16 > width {
+                continue;
+            }
+
+            if symbol == "\n" {
+                break;
+            }
+
+            if symbol_whitespace && !prev_whitespace {
+                symbols_to_last_word_end = current_line.len();
+            }
+
+            current_line.push((start, symbol));
+            current_line_width += symbol.width() as u16;
+
+            if current_line_width > width {
+                // Break at the last word if there was one, otherwise in the middle of the word
+                let truncate_at = if symbols_to_last_word_end != 0 {
+                    symbols_to_last_word_end
+                } else {
+                    current_line.len() - 1
+                };
+
+                // The rest of the word moves to the next line without its leading whitespace
+                let remainder = &current_line[truncate_at..];
+                if let Some(start) = remainder
+                    .iter()
+                    .position(|(_, s)| !s.chars().all(char::is_whitespace))
+                {
+                    next_line.extend_from_slice(&remainder[start..]);
+                }
+                current_line.truncate(truncate_at);
+                break;
+            }
+
+            prev_whitespace = symbol_whitespace;
+        }
+
+        if symbols_exhausted && current_line.is_empty() {
+            break;
+        }
+
+        starts.push(current_line.first().map_or(last_start, |&(start, _)| start));
+    }
+
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::backend::TestBackend;
+    use tui::widgets::{Paragraph, Wrap};
+    use tui::Terminal;
+
+    /// Draws `lines` with tui followed by a marker line, and returns the row the marker lands on
+    fn drawn_height(lines: &[&str], width: u16) -> u16 {
+        let mut text: Vec<Spans> = lines.iter().map(|&line| Spans::from(line)).collect();
+        text.push(Spans::from("#"));
+
+        let mut terminal = Terminal::new(TestBackend::new(width, 50)).unwrap();
+        terminal
+            .draw(|f| {
+                let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+                f.render_widget(paragraph, f.size());
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        (0..50).find(|&y| buffer.get(0, y).symbol == "#").unwrap()
+    }
+
+    fn assert_same_as_tui(lines: &[&str], width: u16) {
+        let spans: Vec<Spans> = lines.iter().map(|&line| Spans::from(line)).collect();
+        assert_eq!(
+            wrapped_height(&spans, width),
+            drawn_height(lines, width),
+            "{:?} at width {}",
+            lines,
+            width
+        );
+    }
+
+    #[test]
+    fn height_matches_tui() {
+        assert_same_as_tui(&["", "short", ""], 10);
+        assert_same_as_tui(&["exactly10!"], 10);
+        assert_same_as_tui(&["the quick brown fox jumps over the lazy dog"], 10);
+    }
+
+    #[test]
+    fn long_words_are_broken() {
+        assert_same_as_tui(&["abcdefghijklmnopqrstuvwxyz"], 10);
+        assert_same_as_tui(&["a abcdefghijklmnopqrstuvwxyz b"], 10);
+        assert_same_as_tui(&["日本語のテキストを折り返す"], 7);
+    }
+
+    #[test]
+    fn trailing_and_repeated_spaces() {
+        assert_same_as_tui(&["word      "], 6);
+        assert_same_as_tui(&["one two          three"], 8);
+        assert_same_as_tui(&["          "], 4);
+        assert_same_as_tui(&["a\u{a0}b\u{a0}c\u{a0}d\u{a0}e f"], 4);
+    }
+
+    #[test]
+    fn zero_width_has_no_rows() {
+        assert_eq!(wrapped_height(&[Spans::from("text")], 0), 0);
+    }
+
+    #[test]
+    fn row_of_a_byte_in_a_wrapped_line() {
+        let line = Spans::from("the quick brown fox");
+
+        assert_eq!(wrapped_row(&line, 10, 0), 0);
+        assert_eq!(wrapped_row(&line, 10, 8), 0);
+        // "brown" doesn't fit after "the quick" so it starts the second row
+        assert_eq!(wrapped_row(&line, 10, 10), 1);
+        assert_eq!(wrapped_row(&line, 10, 18), 1);
+        // "the", "quic", "k", "brow", "n", "fox"
+        assert_eq!(wrapped_row(&line, 4, 18), 5);
+
+        let word = Spans::from("abcdefghij");
+        assert_eq!(wrapped_row(&word, 4, 3), 0);
+        assert_eq!(wrapped_row(&word, 4, 4), 1);
+        assert_eq!(wrapped_row(&word, 4, 9), 2);
+    }
+}
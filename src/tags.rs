@@ -0,0 +1,160 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use diesel::prelude::*;
+use tui::style::{Color, Modifier, Style};
+use tui::text::Span;
+
+use crate::editor::Editor;
+
+/// The most characters a tag can have
+pub const MAX_TAG_LENGTH: usize = 30;
+/// The most tags a post can have
+pub const MAX_TAGS: usize = 5;
+
+/// The colors that tags are drawn on, picked from by the tag's name so that a tag always has the
+/// same color
+const CHIP_COLORS: [Color; 8] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+];
+
+/// Turns what was typed into a tag, lowercased and without a leading '#', or returns why it
+/// can't be one
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+
+    if tag.is_empty() {
+        Err(String::from("Type a tag first"))
+    } else if tag.chars().count() > MAX_TAG_LENGTH {
+        Err(format!("Tags can't be over {} characters", MAX_TAG_LENGTH))
+    } else if !tag
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        Err(String::from(
+            "Tags can only have letters, numbers, '-' and '_'",
+        ))
+    } else {
+        Ok(tag)
+    }
+}
+
+/// Loads every tag that has been used on a post, for autocompleting
+pub fn load_tags() -> Vec<String> {
+    use st_read::schema::posttag::dsl;
+    let connection = st_read::establish_connection();
+
+    dsl::posttag
+        .select(dsl::tag)
+        .distinct()
+        .order(dsl::tag)
+        .load(&connection)
+        .unwrap_or_default()
+}
+
+/// Draws a tag as a colored chip, such as " rust "
+pub fn chip(tag: &str) -> Span<'static> {
+    let hash = tag.bytes().fold(0usize, |hash, b| {
+        hash.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    let color = CHIP_COLORS[hash % CHIP_COLORS.len()];
+
+    Span::styled(
+        format!(" {} ", tag),
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Draws tags as chips separated by spaces
+pub fn chips(tags: &[String]) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+
+    for tag in tags {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(chip(tag));
+    }
+
+    spans
+}
+
+/// A prompt for typing a tag, which suggests the tags that have been used before
+pub struct TagInput {
+    pub editor: Editor,
+    /// Every tag that has been used, for suggesting
+    known: Vec<String>,
+}
+
+impl TagInput {
+    pub fn new() -> Self {
+        Self {
+            editor: Editor::single_line(),
+            known: Vec::new(),
+        }
+    }
+
+    /// Loads the tags to suggest
+    pub fn load(&mut self) {
+        self.known = load_tags();
+    }
+
+    /// The tags that start with what has been typed so far
+    pub fn suggestions(&self) -> Vec<&str> {
+        let typed = self
+            .editor
+            .text()
+            .trim()
+            .trim_start_matches('#')
+            .to_lowercase();
+        if typed.is_empty() {
+            return Vec::new();
+        }
+
+        self.known
+            .iter()
+            .filter(|tag| tag.starts_with(&typed) && **tag != typed)
+            .map(String::as_str)
+            .take(5)
+            .collect()
+    }
+
+    /// Handles the editing keys, plus Tab to fill in the first suggestion.
+    /// Returns false if the key isn't one of them
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if matches!(key.code, KeyCode::Tab) {
+            if let Some(tag) = self.suggestions().first().map(|tag| String::from(*tag)) {
+                self.editor.set_text(tag);
+            }
+            return true;
+        }
+
+        self.editor.handle_key(key)
+    }
+
+    /// The typed text followed by the suggestions, if there are any
+    pub fn line(&self, style: Style, show_cursor: bool) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for line in self.editor.lines(style, show_cursor) {
+            spans.extend(line.0);
+        }
+
+        let suggestions = self.suggestions();
+        if show_cursor && !suggestions.is_empty() {
+            spans.push(Span::styled(
+                format!("  Tab: {}", suggestions.join(", ")),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+
+        spans
+    }
+}
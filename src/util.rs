@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 /// Hashes a password using the given salt, producing 512 bits of output
 /// pbkdf2 + SHA 512 is the algorithm of choice for hashing, with 1000 iterations being used
 pub fn hash(password: &str, salt: &str) -> [u8; 64] {
@@ -7,3 +9,103 @@ pub fn hash(password: &str, salt: &str) -> [u8; 64] {
     crypto::pbkdf2::pbkdf2(&mut mac, &salt.as_bytes(), 1000, &mut buf);
     buf
 }
+
+/// Describes how long ago something happened, such as "5 minutes ago" or "just now"
+pub fn time_ago(time: SystemTime) -> String {
+    let secs = time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+
+    let (n, unit) = match secs {
+        0..=59 => return String::from("just now"),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+
+    format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" })
+}
+
+/// Formats the day something happened as "YYYY-MM-DD", in UTC
+pub fn format_date(time: SystemTime) -> String {
+    let (year, month, day) = civil_date(seconds(time) / 86400);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats when something happened as "YYYY-MM-DDTHH:MM:SSZ", in UTC
+pub fn format_datetime(time: SystemTime) -> String {
+    let secs = seconds(time);
+    let (year, month, day) = civil_date(secs / 86400);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// The seconds since 1970-01-01, or 0 for anything before it
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Turns the days since 1970-01-01 into a year, month, and day
+fn civil_date(days: u64) -> (i64, i64, i64) {
+    // Count in 400 year eras that start on the 1st of March, so that leap days come at the end of
+    // each year
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn civil_date_starts_at_the_epoch() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(10956), (1999, 12, 31));
+        assert_eq!(civil_date(20088), (2024, 12, 31));
+    }
+
+    #[test]
+    fn civil_date_handles_leap_days() {
+        assert_eq!(civil_date(789), (1972, 2, 29));
+        // 2000 is a leap year even though it is divisible by 100, since it is divisible by 400
+        assert_eq!(civil_date(11016), (2000, 2, 29));
+        assert_eq!(civil_date(11017), (2000, 3, 1));
+        // 2100 isn't a leap year
+        assert_eq!(civil_date(47540), (2100, 2, 28));
+        assert_eq!(civil_date(47541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn format_datetime_is_utc() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_211_909);
+        assert_eq!(format_datetime(time), "2024-02-29T13:05:09Z");
+        assert_eq!(format_date(time), "2024-02-29");
+    }
+
+    #[test]
+    fn times_before_the_epoch_are_clamped() {
+        let time = SystemTime::UNIX_EPOCH - Duration::from_secs(60);
+        assert_eq!(format_datetime(time), "1970-01-01T00:00:00Z");
+    }
+}
@@ -1,4 +1,6 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::time::SystemTime;
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use tui::backend::Backend;
 use tui::layout::Constraint;
 use tui::layout::Direction;
@@ -19,6 +21,15 @@ use crate::app::App;
 use crate::app::AppView;
 use crate::app::Post;
 use crate::app::SelectedFrame;
+use crate::author::AuthorList;
+use crate::compose::Compose;
+use crate::compose_box::ComposeBox;
+use crate::drafts::{DraftCopy, DraftOf};
+use crate::find::FindBar;
+use crate::links::LinkList;
+use crate::markdown::{self, TextOptions};
+use crate::scroll::{wrapped_height, ScrollState};
+use crate::tags;
 
 #[derive(Debug, Clone, Copy)]
 enum SelectedBox {
@@ -35,6 +46,12 @@ pub struct ViewingPostFrame {
     /// A boolean for if we are "locked" into the "PostBox" section so that we can scroll through
     /// the post. Esc must be pressed to un-lock
     locked: bool,
+    scroll: ScrollState,
+    pub find: FindBar,
+    pub links: LinkList,
+    pub authors: AuthorList,
+    /// Shows the text of the post and comments as it was written instead of rendering the Markdown
+    raw: bool,
 }
 
 impl ViewingPostFrame {
@@ -45,38 +62,201 @@ impl ViewingPostFrame {
             comment_box: CommentBox::new(),
             reply_box: ReplyBox::new(),
             locked: false,
+            scroll: ScrollState::new(),
+            find: FindBar::new(),
+            links: LinkList::new(),
+            authors: AuthorList::new(),
+            raw: false,
         }
     }
 
-    pub fn view(&mut self, post: Post) {
+    /// Shows a post, marking the comments posted since `last_visit` as new
+    pub fn view(&mut self, post: Post, last_visit: Option<SystemTime>) {
         self.comment_box.clear();
+        self.comment_box.last_visit = last_visit;
         for comment in &post.comments {
             self.comment_box.add_comment(comment.clone());
         }
 
         self.post = Some(post);
+        self.scroll.reset();
+        self.find.clear();
+        self.links.clear();
+        self.authors.locked = false;
 
-        self.reply_box.clear();
+        self.reply_box.compose.clear();
+        self.reply_box.compose.load_names();
     }
 
     pub fn clear(&mut self) {
         self.post = None;
+        self.scroll.reset();
+        self.find.clear();
+        self.links.clear();
+        self.authors.locked = false;
         self.comment_box.clear();
-        self.reply_box.clear();
+        self.reply_box.compose.clear();
+    }
+
+    /// Swaps in the latest version of the post being viewed, such as with comments that have
+    /// been posted since it was opened. The scroll positions and the reply are left alone
+    pub fn refresh(&mut self, post: Post) {
+        if self.post.as_ref().map(|open| open.post_id) != Some(post.post_id) {
+            return;
+        }
+
+        self.comment_box.comments = post.comments.clone();
+        self.post = Some(post);
     }
 
     pub fn has_post(&self) -> bool {
         self.post.is_some()
     }
 
+    /// Shows the comments, scrolled to one of them and with it highlighted
+    pub fn focus_comment(&mut self, comment_id: i32) {
+        self.selected_box = SelectedBox::Comments;
+        self.comment_box.focus = Some(comment_id);
+        self.comment_box.scroll_to_focus = true;
+    }
+
+    pub fn post(&self) -> Option<&Post> {
+        self.post.as_ref()
+    }
+
+    pub fn reply_text(&self) -> &str {
+        self.reply_box.compose.text()
+    }
+
+    pub fn set_reply_text(&mut self, text: &str) {
+        self.reply_box.compose.set_text(text);
+    }
+
+    /// Saves the reply being written as a draft, or deletes the draft once the reply is cleared
+    pub fn save_draft(&mut self, user_id: i32) {
+        if let Some(post) = &self.post {
+            self.reply_box
+                .compose
+                .save_draft(user_id, DraftOf::Reply(post.post_id));
+        }
+    }
+
+    /// Copies the reply for saving as a draft later
+    pub fn draft_copy(&self) -> Option<DraftCopy> {
+        let post = self.post.as_ref()?;
+        self.reply_box
+            .compose
+            .draft_copy(DraftOf::Reply(post.post_id))
+    }
+
+    /// Brings back the draft of a reply to the post being viewed
+    pub fn restore_draft(&mut self, user_id: i32) {
+        if let Some(post) = &self.post {
+            self.reply_box
+                .compose
+                .restore_draft(user_id, DraftOf::Reply(post.post_id));
+        }
+    }
+
+    /// Clears the reply if it was being written to a draft that has been discarded
+    pub fn forget_draft(&mut self, draft_id: i32) {
+        self.reply_box.compose.forget_draft(draft_id);
+    }
+
+    /// Returns true if the selected box has been locked into
+    fn is_locked(&self) -> bool {
+        match self.selected_box {
+            SelectedBox::Main => self.locked,
+            SelectedBox::Comments => self.comment_box.locked,
+            SelectedBox::Reply => self.reply_box.compose.locked,
+        }
+    }
+
     pub fn handle_key(app: &mut App, key: KeyEvent) {
-        // If the user presses the left arrow, shift selection to the posts list
-        if matches!(key.code, KeyCode::Left) {
+        // Typing a pattern to find takes every key until it is submitted or cancelled
+        if app.viewing_frame.find.locked {
+            FindBar::handle_key(app, key);
+            return;
+        }
+
+        // The list of links takes every key until it is closed
+        if app.viewing_frame.links.locked {
+            LinkList::handle_key(app, key);
+            return;
+        }
+
+        // So does the list of authors
+        if app.viewing_frame.authors.locked {
+            AuthorList::handle_key(app, key);
+            return;
+        }
+
+        // Finding works from every box, unless a reply is being typed
+        if !app.viewing_frame.reply_box.compose.locked {
+            let locked = app.viewing_frame.is_locked();
+            let find = &mut app.viewing_frame.find;
+
+            match key.code {
+                KeyCode::Char('/') => {
+                    find.clear();
+                    find.locked = true;
+                    app.quittable = false;
+                    return;
+                }
+                KeyCode::Char('n') if find.is_active() => {
+                    find.next();
+                    return;
+                }
+                KeyCode::Char('N') if find.is_active() => {
+                    find.previous();
+                    return;
+                }
+                KeyCode::Esc if find.is_active() && !locked => {
+                    find.clear();
+                    return;
+                }
+                KeyCode::Char('m') => {
+                    app.viewing_frame.raw = !app.viewing_frame.raw;
+                    return;
+                }
+                KeyCode::Char('s') => {
+                    if let Some(post_id) = app.viewing_frame.post().map(|post| post.post_id) {
+                        app.toggle_saved(post_id);
+                    }
+                    return;
+                }
+                KeyCode::Char('a') => {
+                    // Pick the author of the post or of one of the comments to see their profile
+                    if let Some(post) = &app.viewing_frame.post {
+                        let mut authors = vec![(post.author_id, post.author.clone())];
+                        comment_authors(&post.comments, &mut authors);
+
+                        app.viewing_frame.authors.open(authors);
+                        app.quittable = false;
+                    }
+                    return;
+                }
+                KeyCode::Char('l') if !app.viewing_frame.links.is_empty() => {
+                    app.viewing_frame.links.locked = true;
+                    app.quittable = false;
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        // If the user presses the left arrow, shift selection to the posts list, unless it is moving
+        // the cursor of a reply
+        if matches!(key.code, KeyCode::Left) && !app.viewing_frame.reply_box.compose.locked {
             app.selected_frame = SelectedFrame::Posts;
         } else {
             match app.viewing_frame.selected_box {
                 SelectedBox::Main => {
-                    // This stuff
+                    // While locked, the scrolling keys move through the post
+                    if app.viewing_frame.locked && app.viewing_frame.scroll.handle_key(key.code) {
+                        return;
+                    }
+
                     match key.code {
                         KeyCode::Enter => {
                             // Lock this box
@@ -87,17 +267,12 @@ impl ViewingPostFrame {
                             if app.viewing_frame.locked {
                                 app.viewing_frame.locked = false;
                             } else {
-                                app.selected_frame = SelectedFrame::Posts;
-                                app.viewing_frame.clear();
+                                app.close_post();
                             }
                         }
-                        KeyCode::Down => {
-                            if app.viewing_frame.locked {
-                                // TODO: Scroll through the post
-                            } else {
-                                // Move down to the CommentBox
-                                app.viewing_frame.selected_box = SelectedBox::Comments;
-                            }
+                        KeyCode::Down if !app.viewing_frame.locked => {
+                            // Move down to the CommentBox
+                            app.viewing_frame.selected_box = SelectedBox::Comments;
                         }
                         KeyCode::Char('p') => {
                             if !app.viewing_frame.locked {
@@ -109,6 +284,14 @@ impl ViewingPostFrame {
                                 app.set_view(AppView::CreatePost);
                             }
                         }
+                        KeyCode::Char('e') if !app.viewing_frame.locked => {
+                            // Authors can edit their own posts in their editor
+                            let user_id = app.profile_frame.user_id;
+                            if app.viewing_frame.post().map(|post| post.author_id) == Some(user_id)
+                            {
+                                app.compose = Some(Compose::EditPost);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -122,8 +305,62 @@ impl ViewingPostFrame {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+    /// Shows the link that was clicked on, if there is one, asking before it is opened
+    pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+            let links = &mut app.viewing_frame.links;
+
+            // Something else is taking the keys that would answer
+            if !app.quittable && !links.locked {
+                return;
+            }
+
+            if let Some(url) = links.link_at(mouse.column, mouse.row).map(String::from) {
+                links.ask_open(&url);
+                app.selected_frame = SelectedFrame::ViewPost;
+                app.quittable = false;
+            }
+        }
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        is_selected: bool,
+        dark_mode: bool,
+        saved: bool,
+    ) {
         if let Some(post) = self.post.clone() {
+            let options = TextOptions {
+                raw: self.raw,
+                dark_mode,
+                names: self.reply_box.compose.names(),
+            };
+
+            // The find bar and the list of links take up the bottom of the view while they are
+            // in use
+            let mut constraints = vec![Constraint::Min(0)];
+            if self.find.is_active() {
+                constraints.push(Constraint::Length(3));
+            }
+            if self.links.locked || self.authors.locked {
+                constraints.push(Constraint::Length(8));
+            }
+
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(area);
+            let area = layout[0];
+            let mut bottom = layout[1..].iter().copied();
+            let find_area = if self.find.is_active() {
+                bottom.next()
+            } else {
+                None
+            };
+            let links_area = bottom.next();
+
             let layout = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints(
@@ -139,43 +376,61 @@ impl ViewingPostFrame {
             let comments_area = layout[1];
             let reply_area = layout[2];
 
-            let mut text = Vec::new();
-
-            // Render the title
-            text.push(Spans::from(Span::styled(
-                post.title,
-                Style::default().add_modifier(Modifier::BOLD),
-            )));
-
-            // Render the author's name
-            text.push(Spans::from(Span::styled(
-                format!("   by {}", post.author),
-                Style::default().add_modifier(Modifier::ITALIC),
-            )));
-
-            // Render the actual text
-            let text_strs = format!("\n{}\n\n", post.full);
-
-            // Here we have to split by newlines, because newlines aren't displayed properly
-            for line in text_strs.split('\n') {
-                text.push(Spans::from(vec![Span::raw(line)]));
-            }
+            let mut links = Vec::new();
+            let author = match &post.community {
+                Some(community) => format!(
+                    "{} ({} karma) in {}",
+                    post.author, post.author_karma, community
+                ),
+                None => format!("{} ({} karma)", post.author, post.author_karma),
+            };
+            let mut text = post_text(
+                &post.title,
+                &author,
+                &post.tags,
+                &post.full,
+                options,
+                &mut links,
+            );
 
             let border_style = get_border_style(
                 is_selected && matches!(self.selected_box, SelectedBox::Main),
                 self.locked,
             );
 
+            // Measure the wrapped text so that we know how far it can be scrolled
+            let inner = Block::default().borders(Borders::ALL).inner(main_area);
+            self.scroll
+                .update(wrapped_height(&text, inner.width), inner.height);
+
+            self.find.begin();
+            if let Some(row) = self.find.search(&mut text, inner.width) {
+                self.scroll.show_line(row);
+            }
+
+            let mut title = String::from(if self.raw {
+                "Viewing Post (raw)"
+            } else {
+                "Viewing Post"
+            });
+            if saved {
+                title.push_str(" (saved)");
+            }
+            if let (false, Some(message)) = (self.links.locked, self.links.message()) {
+                title = format!("{} - {}", title, message);
+            }
+
             // Finally render it all as a "Paragraph" widget
             let post = Paragraph::new(text)
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
                         .border_style(border_style)
-                        .title("Viewing Post"),
+                        .title(self.scroll.title(title)),
                 )
                 .style(Style::default().fg(Color::Gray))
-                .wrap(tui::widgets::Wrap { trim: false });
+                .wrap(tui::widgets::Wrap { trim: false })
+                .scroll((self.scroll.offset(), 0));
 
             f.render_widget(post, main_area);
 
@@ -183,37 +438,140 @@ impl ViewingPostFrame {
                 f,
                 comments_area,
                 is_selected && matches!(self.selected_box, SelectedBox::Comments),
+                &mut self.find,
+                options,
+                &mut links,
             );
 
+            // The links are found on the screen once everything has been drawn
+            self.links.begin(links);
+            self.links.add_pane(inner);
+            self.links
+                .add_pane(Block::default().borders(Borders::ALL).inner(comments_area));
+
             self.reply_box.render(
                 f,
                 reply_area,
                 is_selected && matches!(self.selected_box, SelectedBox::Reply),
             );
+
+            self.find.end();
+            if let Some(find_area) = find_area {
+                self.find.render(f, find_area);
+            }
+            if let Some(bottom_area) = links_area {
+                if self.authors.locked {
+                    self.authors.render(f, bottom_area);
+                } else {
+                    self.links.render(f, bottom_area);
+                }
+            }
         }
     }
 }
 
+/// Renders the title, author line, tags, and body of a post the way it is shown in the viewing
+/// frame
+pub fn post_text(
+    title: &str,
+    author: &str,
+    tags: &[String],
+    body: &str,
+    options: TextOptions,
+    links: &mut Vec<String>,
+) -> Vec<Spans<'static>> {
+    let mut text = Vec::new();
+
+    // Render the title
+    text.push(Spans::from(Span::styled(
+        String::from(title),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+
+    // Render the author's name
+    text.push(Spans::from(Span::styled(
+        format!("   by {}", author),
+        Style::default().add_modifier(Modifier::ITALIC),
+    )));
+
+    // Render the tags
+    if !tags.is_empty() {
+        text.push(Spans::default());
+        text.push(Spans::from(tags::chips(tags)));
+    }
+
+    // Render the actual text
+    text.push(Spans::default());
+    text.append(&mut markdown::render_text(body, options, links));
+    text.push(Spans::default());
+    text.push(Spans::default());
+
+    text
+}
+
 #[derive(Debug, Clone)]
 pub struct Comment {
+    pub comment_id: i32,
     pub text: String,
+    pub author_id: i32,
     pub author: String,
+    pub author_karma: i32,
+    pub date_posted: SystemTime,
     pub children: Vec<Comment>,
 }
 
 impl Comment {
-    pub fn new(text: impl AsRef<str>, author: impl AsRef<str>, children: Vec<Comment>) -> Self {
+    pub fn new(
+        comment_id: i32,
+        text: impl AsRef<str>,
+        author_id: i32,
+        author: impl AsRef<str>,
+        author_karma: i32,
+        date_posted: SystemTime,
+        children: Vec<Comment>,
+    ) -> Self {
         Self {
+            comment_id,
             text: String::from(text.as_ref()),
+            author_id,
             author: String::from(author.as_ref()),
+            author_karma,
+            date_posted,
             children,
         }
     }
 }
 
+/// Adds the ids and names of the authors of the comments and their replies, in the order they are
+/// shown
+fn comment_authors(comments: &[Comment], authors: &mut Vec<(i32, String)>) {
+    for comment in comments {
+        authors.push((comment.author_id, comment.author.clone()));
+        comment_authors(&comment.children, authors);
+    }
+}
+
+/// Counts the comments, including replies, that were posted after `since`
+pub fn count_new_comments(comments: &[Comment], since: SystemTime) -> usize {
+    comments
+        .iter()
+        .map(|comment| {
+            let new = usize::from(comment.date_posted > since);
+            new + count_new_comments(&comment.children, since)
+        })
+        .sum()
+}
+
 pub struct CommentBox {
     comments: Vec<Comment>,
     locked: bool,
+    scroll: ScrollState,
+    /// When the user last viewed the post, if they had before. Comments posted since are new
+    last_visit: Option<SystemTime>,
+    /// The comment that was jumped to, which is highlighted
+    focus: Option<i32>,
+    /// Whether the focused comment still has to be scrolled to
+    scroll_to_focus: bool,
 }
 
 impl CommentBox {
@@ -221,6 +579,10 @@ impl CommentBox {
         Self {
             comments: Vec::new(),
             locked: false,
+            scroll: ScrollState::new(),
+            last_visit: None,
+            focus: None,
+            scroll_to_focus: false,
         }
     }
 
@@ -230,32 +592,35 @@ impl CommentBox {
 
     pub fn clear(&mut self) {
         self.comments.clear();
+        self.scroll.reset();
+        self.focus = None;
+        self.scroll_to_focus = false;
     }
 
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // While locked, the scrolling keys move through the comments
+        if app.viewing_frame.comment_box.locked
+            && app.viewing_frame.comment_box.scroll.handle_key(key.code)
+        {
+            return;
+        }
+
         match key.code {
             KeyCode::Esc => {
                 if app.viewing_frame.comment_box.locked {
                     app.viewing_frame.comment_box.locked = false;
                 } else {
-                    app.selected_frame = SelectedFrame::Posts;
-                    app.viewing_frame.clear();
+                    app.close_post();
                 }
             }
             KeyCode::Enter => {
                 app.viewing_frame.comment_box.locked = true;
             }
-            KeyCode::Down => {
-                if app.viewing_frame.comment_box.locked {
-                } else {
-                    app.viewing_frame.selected_box = SelectedBox::Reply;
-                }
+            KeyCode::Down if !app.viewing_frame.comment_box.locked => {
+                app.viewing_frame.selected_box = SelectedBox::Reply;
             }
-            KeyCode::Up => {
-                if app.viewing_frame.comment_box.locked {
-                } else {
-                    app.viewing_frame.selected_box = SelectedBox::Main;
-                }
+            KeyCode::Up if !app.viewing_frame.comment_box.locked => {
+                app.viewing_frame.selected_box = SelectedBox::Main;
             }
             KeyCode::Char('p') => {
                 if !app.viewing_frame.comment_box.locked {
@@ -271,33 +636,82 @@ impl CommentBox {
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+    pub fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        is_selected: bool,
+        find: &mut FindBar,
+        options: TextOptions,
+        links: &mut Vec<String>,
+    ) {
         let border_style = get_border_style(is_selected, self.locked);
 
         let mut spans = Vec::new();
+        let mut focused_line = None;
 
         for comment in &self.comments {
-            let mut comment_spans = Self::render_comment(comment, 0);
+            let line = Self::render_comment(
+                comment,
+                0,
+                self.last_visit,
+                self.focus,
+                options,
+                links,
+                &mut spans,
+            );
+            focused_line = focused_line.or(line);
+        }
+
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        self.scroll
+            .update(wrapped_height(&spans, inner.width), inner.height);
 
-            spans.append(&mut comment_spans);
+        if let (Some(line), true) = (focused_line, self.scroll_to_focus) {
+            self.scroll
+                .show_line_at_top(wrapped_height(&spans[..line], inner.width));
+            self.scroll_to_focus = false;
         }
 
+        if let Some(row) = find.search(&mut spans, inner.width) {
+            self.scroll.show_line(row);
+        }
+
+        let new = self
+            .last_visit
+            .map_or(0, |since| count_new_comments(&self.comments, since));
+        let title = if new > 0 {
+            format!("Comments ({} new)", new)
+        } else {
+            String::from("Comments")
+        };
+
         let comments = Paragraph::new(spans)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(border_style)
-                    .title("Comments"),
+                    .title(self.scroll.title(title)),
             )
             .style(Style::default().fg(Color::Gray))
-            .wrap(tui::widgets::Wrap { trim: false });
+            .wrap(tui::widgets::Wrap { trim: false })
+            .scroll((self.scroll.offset(), 0));
 
         f.render_widget(comments, area);
     }
 
-    /// Handles recursively rendering comments
-    fn render_comment(comment: &Comment, depth: usize) -> Vec<Spans> {
-        let mut spans = Vec::new();
+    /// Handles recursively rendering comments into `spans`, marking the ones posted after
+    /// `last_visit` as new. Returns the line the `focus` comment starts on, if it was rendered
+    fn render_comment(
+        comment: &Comment,
+        depth: usize,
+        last_visit: Option<SystemTime>,
+        focus: Option<i32>,
+        options: TextOptions,
+        links: &mut Vec<String>,
+        spans: &mut Vec<Spans<'static>>,
+    ) -> Option<usize> {
+        let mut focused_line = None;
 
         let spaces = "   ";
 
@@ -307,130 +721,105 @@ impl CommentBox {
             space_s.push_str(spaces);
         }
 
-        spans.push(Spans::from(Span::styled(
-            format!("{}{}", space_s, comment.author),
-            Style::default().add_modifier(Modifier::BOLD),
-        )));
+        let mut author_style = Style::default().add_modifier(Modifier::BOLD);
+        if focus == Some(comment.comment_id) {
+            focused_line = Some(spans.len());
+            author_style = author_style.fg(Color::LightCyan);
+        }
+
+        let mut author = vec![
+            Span::raw(space_s.clone()),
+            Span::styled(comment.author.clone(), author_style),
+        ];
+        author.push(Span::styled(
+            format!(" ({} karma)", comment.author_karma),
+            Style::default().fg(Color::DarkGray),
+        ));
+        if last_visit.is_some_and(|since| comment.date_posted > since) {
+            author.push(Span::raw(" "));
+            author.push(Span::styled(
+                " new ",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.push(Spans::from(author));
 
         space_s.push_str(spaces);
 
-        for line in comment.text.lines() {
-            spans.push(Spans::from(Span::raw(format!("{}{}", space_s, line))));
+        for mut line in markdown::render_text(&comment.text, options, links) {
+            line.0.insert(0, Span::raw(space_s.clone()));
+            spans.push(line);
         }
 
         for comment in comment.children.iter() {
             // This is the recursive part
-            let mut child_spans = Self::render_comment(comment, depth + 1);
-
-            spans.append(&mut child_spans);
+            let line =
+                Self::render_comment(comment, depth + 1, last_visit, focus, options, links, spans);
+            focused_line = focused_line.or(line);
         }
 
-        spans
+        focused_line
     }
 }
 
 pub struct ReplyBox {
-    text: String,
-    locked: bool,
+    compose: ComposeBox,
 }
 
 impl ReplyBox {
     pub fn new() -> Self {
         Self {
-            text: String::new(),
-            locked: false,
+            compose: ComposeBox::new(),
         }
     }
 
-    pub fn add_char(&mut self, c: char) {
-        self.text.push(c);
-    }
-
-    pub fn del_char(&mut self) {
-        self.text.pop();
-    }
-
-    pub fn clear(&mut self) {
-        self.text.clear();
-    }
-
     pub fn handle_key(app: &mut App, key: KeyEvent) {
+        // While locked, the editing keys change the reply
+        if app.viewing_frame.reply_box.compose.handle_key(key) {
+            return;
+        }
+
         match key.code {
             KeyCode::Esc => {
-                if app.viewing_frame.reply_box.locked {
-                    app.viewing_frame.reply_box.locked = false;
+                if app.viewing_frame.reply_box.compose.locked {
+                    app.viewing_frame.reply_box.compose.locked = false;
                     app.quittable = true;
                 } else {
-                    app.selected_frame = SelectedFrame::Posts;
-                    app.viewing_frame.clear();
+                    app.close_post();
                 }
             }
             KeyCode::Enter => {
-                if !app.viewing_frame.reply_box.locked {
-                    app.viewing_frame.reply_box.locked = true;
-                    app.quittable = false;
-                } else {
-                    app.viewing_frame.reply_box.add_char('\n');
-                }
-            }
-            KeyCode::Up => {
-                if app.viewing_frame.reply_box.locked {
-                } else {
-                    app.viewing_frame.selected_box = SelectedBox::Comments;
-                }
+                app.viewing_frame.reply_box.compose.locked = true;
+                app.quittable = false;
             }
-            KeyCode::Backspace => {
-                if app.viewing_frame.reply_box.locked {
-                    app.viewing_frame.reply_box.del_char();
-                }
+            KeyCode::Up if !app.viewing_frame.reply_box.compose.locked => {
+                app.viewing_frame.selected_box = SelectedBox::Comments;
             }
-            KeyCode::Char('p') if !app.viewing_frame.reply_box.locked => {
+            KeyCode::Char('p') if !app.viewing_frame.reply_box.compose.locked => {
                 app.set_view(AppView::UserProfile);
             }
-            KeyCode::Char('c') if !app.viewing_frame.reply_box.locked => {
+            KeyCode::Char('c') if !app.viewing_frame.reply_box.compose.locked => {
                 app.set_view(AppView::CreatePost);
             }
-            KeyCode::Char(c) => {
-                if app.viewing_frame.reply_box.locked {
-                    app.viewing_frame.reply_box.add_char(c);
-                }
+            KeyCode::Char('e') if !app.viewing_frame.reply_box.compose.locked => {
+                // Write the reply in the user's own editor instead
+                app.compose = Some(Compose::Reply);
             }
             _ => {}
         }
     }
 
-    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
-        let border_style = get_border_style(is_selected, self.locked);
-
-        let cursor = Span::styled("█", Style::default().fg(Color::White));
-
-        let mut spans = Vec::new();
-
-        if !self.text.is_empty() {
-            for line in self.text.split('\n') {
-                spans.push(Spans::from(Span::raw(String::from(line))));
-            }
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, is_selected: bool) {
+        let border_style = get_border_style(is_selected, self.compose.locked);
 
-            let mut last = spans.pop().unwrap();
-            last.0.push(cursor);
-
-            spans.push(last);
-        } else {
-            if self.locked && is_selected {
-                spans.push(Spans::from(cursor));
-            }
-        }
-
-        let reply = Paragraph::new(spans)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(border_style)
-                    .title("Reply"),
-            )
-            .style(Style::default().fg(Color::Gray))
-            .wrap(tui::widgets::Wrap { trim: false });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title("Reply");
 
-        f.render_widget(reply, area);
+        self.compose.render(f, area, block, is_selected);
     }
 }